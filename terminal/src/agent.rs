@@ -0,0 +1,294 @@
+//! Implements a background agent caching verified keys in memory, such that they need not be
+//! re-entered for every command.
+//!
+//! The agent listens on a unix socket in a directory accessible only to the current user, and both
+//! ends of a connection check that the other belongs to the same user. Keys are never written to
+//! disk, and are zeroized once they expire or are cleared.
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    env, fs,
+    io::{self, BufRead, BufReader, Write},
+    os::unix::{
+        fs::{DirBuilderExt, MetadataExt},
+        io::AsRawFd,
+        net::{UnixListener, UnixStream},
+        process::CommandExt,
+    },
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+use zeroize::Zeroizing;
+
+/// Specifies how long keys are cached unless otherwise specified.
+pub const DEFAULT_TTL: Duration = Duration::from_secs(15 * 60);
+
+/// Specifies how often the agent checks for new connections and expired keys.
+const POLL_RATE: Duration = Duration::from_millis(200);
+
+/// Specifies the name of the socket within the [socket directory](socket_dir).
+const SOCKET_NAME: &str = "agent.sock";
+
+/// Defines the requests understood by the agent.
+#[derive(Serialize, Deserialize)]
+enum Request<'a> {
+    /// Retrieves the cached key of a vault.
+    Get(Cow<'a, str>),
+    /// Caches the key of a vault.
+    Set(Cow<'a, str>, Cow<'a, str>),
+    /// Forgets all cached keys.
+    Clear,
+    /// Forgets all cached keys and shuts down the agent.
+    Stop,
+}
+
+/// Returns the cached key of a vault, or [None] if it isn't cached or the agent isn't running.
+pub fn get(vault: &str) -> Option<Zeroizing<String>> {
+    request(&Request::Get(Cow::Borrowed(vault))).ok().flatten()
+}
+
+/// Caches the key of a vault if the agent is running.
+pub fn set(vault: &str, key: &str) {
+    let _ = request(&Request::Set(Cow::Borrowed(vault), Cow::Borrowed(key)));
+}
+
+/// Forgets all cached keys.
+pub fn clear() -> Result<()> {
+    ensure_running()?;
+    request(&Request::Clear)?;
+    Ok(())
+}
+
+/// Shuts down the agent.
+pub fn stop() -> Result<()> {
+    ensure_running()?;
+    request(&Request::Stop)?;
+    Ok(())
+}
+
+/// Returns whether the agent is running.
+pub fn is_running() -> bool {
+    connect().is_ok()
+}
+
+/// Starts the agent in a detached background process, caching keys for the specified duration.
+pub fn start(ttl: Duration) -> Result<()> {
+    if is_running() {
+        bail!("Agent is already running.");
+    }
+    Command::new(env::current_exe()?)
+        .args(["agent", "serve", &ttl.as_secs().to_string()])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .process_group(0)
+        .spawn()?;
+
+    // wait for the agent to start listening, such that it may be used immediately
+    for _ in 0..10 {
+        if is_running() {
+            return Ok(());
+        }
+        thread::sleep(POLL_RATE);
+    }
+    bail!("Agent did not start.")
+}
+
+/// Runs the agent in the current process until it is stopped.
+pub fn serve(ttl: Duration) -> Result<()> {
+    let dir = socket_dir();
+
+    match fs::DirBuilder::new().mode(0o700).create(&dir) {
+        Err(e) if e.kind() != io::ErrorKind::AlreadyExists => return Err(e.into()),
+        _ => check_private(&dir)?,
+    }
+    let path = dir.join(SOCKET_NAME);
+
+    // a socket may be left over if a previous agent was killed
+    if path.exists() {
+        fs::remove_file(&path)?;
+    }
+    // the socket is created with restricted permissions, rather than restricted once created
+    let umask = unsafe { libc::umask(0o177) };
+    let listener = UnixListener::bind(&path);
+    unsafe { libc::umask(umask) };
+    let listener = listener?;
+    listener.set_nonblocking(true)?;
+
+    let mut keys: HashMap<String, (Zeroizing<String>, Instant)> = HashMap::new();
+
+    loop {
+        // dropping the keys zeroizes them
+        keys.retain(|_, (_, expiry)| *expiry > Instant::now());
+
+        match listener.accept() {
+            Ok((stream, _)) => {
+                // connections from other users are dropped, should they get past the directory
+                if !matches!(peer_uid(&stream), Ok(uid) if uid == current_uid()) {
+                    continue;
+                }
+                // errors are specific to the connection and should not bring down the agent
+                if let Ok(false) = handle(stream, &mut keys, ttl) {
+                    break;
+                }
+            }
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => thread::sleep(POLL_RATE),
+            Err(e) => return Err(e.into()),
+        }
+    }
+    fs::remove_file(&path)?;
+    Ok(())
+}
+
+/// Handles a single connection to the agent. Returns whether the agent should keep running.
+fn handle(
+    stream: UnixStream,
+    keys: &mut HashMap<String, (Zeroizing<String>, Instant)>,
+    ttl: Duration,
+) -> io::Result<bool> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(Duration::from_secs(1)))?;
+
+    let mut line = Zeroizing::new(String::new());
+    BufReader::new(&stream).read_line(&mut line)?;
+
+    let (response, keep_running) = match serde_json::from_str(&line)? {
+        Request::Get(vault) => (keys.get(vault.as_ref()).map(|(key, _)| key.as_str()), true),
+        Request::Set(vault, key) => {
+            let key = Zeroizing::new(key.into_owned());
+            keys.insert(vault.into_owned(), (key, Instant::now() + ttl));
+            (None, true)
+        }
+        Request::Clear => {
+            keys.clear();
+            (None, true)
+        }
+        Request::Stop => {
+            keys.clear();
+            (None, false)
+        }
+    };
+    let mut message = Zeroizing::new(serde_json::to_string(&response)?);
+    message.push('\n');
+    (&stream).write_all(message.as_bytes())?;
+
+    Ok(keep_running)
+}
+
+/// Sends a request to the agent and returns its response.
+fn request(request: &Request) -> io::Result<Option<Zeroizing<String>>> {
+    let mut stream = connect()?;
+    let mut message = Zeroizing::new(serde_json::to_string(request)?);
+    message.push('\n');
+    stream.write_all(message.as_bytes())?;
+
+    let mut line = Zeroizing::new(String::new());
+    BufReader::new(stream).read_line(&mut line)?;
+    let response: Option<String> = serde_json::from_str(&line)?;
+    Ok(response.map(Zeroizing::new))
+}
+
+fn ensure_running() -> Result<()> {
+    if is_running() {
+        Ok(())
+    } else {
+        bail!("Agent is not running.")
+    }
+}
+
+/// Connects to the agent, after checking that the socket and the process listening on it belong to
+/// the current user. Otherwise, another user could pose as the agent and receive the keys sent to
+/// it.
+fn connect() -> io::Result<UnixStream> {
+    let dir = socket_dir();
+    check_private(&dir)?;
+    let path = dir.join(SOCKET_NAME);
+    check_private(&path)?;
+
+    let stream = UnixStream::connect(&path)?;
+
+    if peer_uid(&stream)? != current_uid() {
+        return Err(io::Error::new(io::ErrorKind::PermissionDenied, "agent belongs to another user"));
+    }
+    Ok(stream)
+}
+
+/// Calculates the directory of the agent socket, within the per-user runtime directory if there is
+/// one and the shared temporary directory otherwise. The directory is specific to the user, and
+/// created by the agent accessible only to them.
+fn socket_dir() -> PathBuf {
+    let parent = env::var_os("XDG_RUNTIME_DIR").map_or_else(env::temp_dir, PathBuf::from);
+    parent.join(format!("svalbard-{}", current_uid()))
+}
+
+/// Checks that a file is owned by the current user and inaccessible to others. Symbolic links are
+/// not followed, such that they cannot redirect to files of other users.
+fn check_private(path: &Path) -> io::Result<()> {
+    let metadata = fs::symlink_metadata(path)?;
+
+    if metadata.file_type().is_symlink()
+        || metadata.uid() != current_uid()
+        || metadata.mode() & 0o077 != 0
+    {
+        let message = format!("{} is not private to the current user", path.display());
+        return Err(io::Error::new(io::ErrorKind::PermissionDenied, message));
+    }
+    Ok(())
+}
+
+fn current_uid() -> u32 {
+    unsafe { libc::geteuid() }
+}
+
+/// Returns the user of the process at the other end of a socket.
+#[cfg(target_os = "linux")]
+fn peer_uid(stream: &UnixStream) -> io::Result<u32> {
+    let mut cred = libc::ucred { pid: 0, uid: 0, gid: 0 };
+    let mut len = std::mem::size_of::<libc::ucred>() as libc::socklen_t;
+    let result = unsafe {
+        libc::getsockopt(
+            stream.as_raw_fd(),
+            libc::SOL_SOCKET,
+            libc::SO_PEERCRED,
+            &mut cred as *mut libc::ucred as *mut libc::c_void,
+            &mut len,
+        )
+    };
+    match result {
+        0 => Ok(cred.uid),
+        _ => Err(io::Error::last_os_error()),
+    }
+}
+
+/// Returns the user of the process at the other end of a socket.
+#[cfg(not(target_os = "linux"))]
+fn peer_uid(stream: &UnixStream) -> io::Result<u32> {
+    let (mut uid, mut gid) = (0, 0);
+
+    match unsafe { libc::getpeereid(stream.as_raw_fd(), &mut uid, &mut gid) } {
+        0 => Ok(uid),
+        _ => Err(io::Error::last_os_error()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    #[test]
+    fn private_dir() {
+        let dir = env::temp_dir().join(format!("svalbard-test-agent-{}", std::process::id()));
+        fs::DirBuilder::new().mode(0o700).create(&dir).unwrap();
+        assert!(check_private(&dir).is_ok());
+
+        fs::set_permissions(&dir, fs::Permissions::from_mode(0o755)).unwrap();
+        assert!(check_private(&dir).is_err());
+        fs::remove_dir(&dir).unwrap();
+    }
+}
@@ -1,5 +1,902 @@
-use anyhow::Result;
+#[cfg(unix)]
+use crate::agent;
+use crate::{clipboard, config, qr, shared};
+use anyhow::{anyhow, bail, Result};
+use crossterm::tty::IsTty;
+use data_encoding::BASE64;
+use indoc::{formatdoc, indoc};
+use std::{
+    collections::HashMap,
+    env,
+    fs::{self, OpenOptions},
+    io::{self, BufRead, Write},
+    path::Path,
+};
+use vault::{
+    audit::Severity,
+    generate::{self, Argon2Params, Argon2Variant, Normalization},
+    seed::{Characters, Seed, SeedBuilder, SeedKind},
+    PepperSource, SortKey, Vault,
+};
+use zeroize::Zeroizing;
 
-pub fn launch() -> Result<()> {
-    todo!()
+/// Name of the environment variable the key may be read from.
+const KEY_VAR: &str = "SVALBARD_KEY";
+
+/// Names of the environment variables the pepper and seed of `generate` without a vault may be read
+/// from. See [`generate_ephemeral`].
+const PEPPER_VAR: &str = "SVALBARD_PEPPER";
+const SEED_VAR: &str = "SVALBARD_SEED";
+
+/// Exit code of commands failing because a key is incorrect, allowing scripts to tell such failures
+/// apart from others, which exit with code 1.
+const KEY_MISMATCH_CODE: u8 = 2;
+
+/// Warns that changing the key of a vault changes all of its passwords.
+const CHANGE_KEY_WARNING: &str = indoc!(
+    "WARNING: Every password of the vault is derived from its key, and will change under the new
+     key. The passwords of all services in the vault must be updated to the new ones, or they can
+     only be recovered by changing the key back."
+);
+
+/// Contains the seed fields which may be imported from a CSV file, each paired with the column
+/// headers recognized by default. These cover the exports of Bitwarden and KeePass.
+const CSV_FIELDS: [(&str, &[&str]); 4] = [
+    ("name",     &["name", "title"]),
+    ("username", &["username", "login_username", "user name"]),
+    ("url",      &["url", "login_uri"]),
+    ("notes",    &["notes"]),
+];
+
+const USAGE: &str = indoc!(
+    "Usage: svalbard [--profile <name>] [command]
+
+       svalbard                         Launch the terminal user interface
+       svalbard scratch                 Launch the interface on a vault kept in memory only, to
+                                        try things out. It may be saved from the interface
+       svalbard copy <vault> <seed>     Copy the password of a seed to the clipboard
+       svalbard generate <vault> <seed> [--field <field>] [--out <file> [--force]]
+                                        Print the password of a seed, or write it to a file
+                                        readable only by the current user. Existing files are
+                                        only overwritten if --force is specified. The field may
+                                        be username, url, or password (the default), of which
+                                        only password requires the key
+       svalbard generate [--pepper-stdin] [--seed <spec>] [--argon2 <variant>]
+                                        Print a password without a vault file, e.g. in CI. The
+                                        pepper is base64 encoded as in vault files, and is read
+                                        from the SVALBARD_PEPPER environment variable, or from the
+                                        first line of stdin with --pepper-stdin. It is never
+                                        accepted as an argument, since arguments are visible to
+                                        other users. The seed is specified as
+                                        identifier:length:salt:sets, e.g. GitHub:12-20:0:ULNS, or
+                                        in the SVALBARD_SEED environment variable. Exclusions,
+                                        custom sets, and exclude_space cannot be specified. The
+                                        variant must match that of the vault, as printed by info:
+                                        argon2id (the default) or argon2d
+       svalbard selftest                Confirm that this build generates the same passwords as
+                                        previous builds, e.g. after upgrading
+       svalbard stats <vault>           Summarize the seeds of a vault
+       svalbard audit <vault> [level]   Flag weak seeds, failing on findings of at least the given
+                                        severity (low, medium, or high; defaults to high)
+       svalbard check <vault>           Check that a vault loads and that each of its seeds can
+                                        produce a password, e.g. after editing the file by hand
+       svalbard manifest <vault> [file] Print or save everything needed to recreate the seeds, except
+                                        the key and pepper
+       svalbard manifest-qr <vault>     Print the manifest as QR codes, e.g. to back it up on a
+                                        phone. Together with the pepper and key, the codes
+                                        regenerate every password, so store them apart
+       svalbard seeds <vault> [--json]  List the identifier, username, and URL of each seed, or
+                                        print every seed as JSON
+       svalbard info <vault>            Print the parameters of a vault, e.g. to find out why its
+                                        passwords changed. Secrets such as the pepper are omitted
+       svalbard change-key <vault>      Change the key of a vault. This changes every password of
+                                        the vault
+       svalbard sort <vault> [field]    Reorder the seeds stored in a vault by identifier (the
+                                        default), username, or url, e.g. for clean diffs
+       svalbard set-prefix <vault> [prefix]
+                                        Start every password of a vault with a fixed prefix, or
+                                        remove it if omitted. This changes every password of the
+                                        vault
+       svalbard restore-backup <vault> <n>
+                                        Restore backup n of a vault, where 1 is the most recent.
+                                        The current version becomes backup 1
+       svalbard move-pepper <vault> <file>
+                                        Move the pepper of a vault to a file, e.g. on removable
+                                        media. The vault can then only be opened while the file is
+                                        present, so make sure to back it up
+       svalbard import-csv <vault> <file> [options]
+                                        Add seeds for the services of a password manager export.
+                                        Passwords are not imported; new ones are derived instead.
+                                        Columns are picked with --name, --username, --url, and
+                                        --notes <column>
+       svalbard agent start [seconds]   Start the key agent, caching keys for a duration
+       svalbard agent add <vault>       Enter the key of a vault and cache it in the agent
+       svalbard agent clear             Forget all keys cached in the agent
+       svalbard agent stop              Stop the key agent
+       svalbard profiles                List the configured profiles and their vault folders. The
+                                        active one is marked with *
+       svalbard list                    List the vaults in the vault folder, named as their files
+
+     The profile selects a vault folder configured in config.json, and may also be set with the
+     SVALBARD_PROFILE environment variable. Without one, vaults are kept next to the executable.
+
+     Commands exit with code 2 if a key is incorrect, and 1 on other errors."
+);
+
+/// Executes the command given by the arguments, excluding the program name and profile.
+pub fn launch(args: &[&str]) -> Result<()> {
+    match args {
+        ["copy", vault, seed] => copy(vault, seed),
+        ["generate", options @ ..] if options.first().is_some_and(|o| o.starts_with("--")) => {
+            generate_ephemeral(options)
+        }
+        ["generate", vault, seed, options @ ..] => generate(vault, seed, options),
+        ["selftest"] => {
+            println!("All {} vectors passed.", vault::selftest::run()?);
+            Ok(())
+        }
+        ["stats", vault] => {
+            println!("{}", load(vault)?.stats());
+            Ok(())
+        }
+        ["audit", vault] => audit(vault, Severity::High),
+        ["audit", vault, level] => audit(vault, parse_severity(level)?),
+        ["check", vault] => check(vault),
+        ["manifest", vault] => {
+            print!("{}", manifest(&load(vault)?));
+            Ok(())
+        }
+        ["manifest", vault, file] => Ok(fs::write(file, manifest(&load(vault)?))?),
+        ["manifest-qr", vault] => {
+            print!("{}", manifest_qr(&load(vault)?)?);
+            Ok(())
+        }
+        ["seeds", vault, options @ ..] if options.len() <= 1 => {
+            let json = match options {
+                [] => false,
+                ["--json"] => true,
+                _ => bail!("Invalid option '{}'.\n\n{USAGE}", options[0]),
+            };
+            print!("{}", seeds(&load(vault)?, json)?);
+            Ok(())
+        }
+        ["info", vault] => {
+            print!("{}", info(&load(vault)?));
+            Ok(())
+        }
+        ["change-key", vault] => change_key(vault),
+        ["sort", vault, field @ ..] if field.len() <= 1 => {
+            let field = field.first().copied().unwrap_or("identifier");
+            let by = SortKey::ALL
+                .into_iter()
+                .find(|key| key.name() == field)
+                .ok_or_else(|| anyhow!("Invalid field '{field}'. Must be identifier, username, or url."))?;
+            let mut vault = load(vault)?;
+            vault.sort_seeds(by);
+            Ok(vault.save()?)
+        }
+        ["set-prefix", vault, prefix @ ..] if prefix.len() <= 1 => {
+            let mut vault = load(vault)?;
+            vault.set_prefix(prefix.first().map(|prefix| prefix.to_string()))?;
+            vault.save()?;
+            eprintln!("Every password of vault '{}' has changed.", vault.identifier());
+            Ok(())
+        }
+        ["restore-backup", vault, n] => {
+            let mut vault = load(vault)?;
+            let n = n.parse().map_err(|_| anyhow!("Invalid backup number '{n}'."))?;
+            vault.restore_backup(n)?;
+            Ok(vault.save()?)
+        }
+        ["move-pepper", vault, file] => {
+            let mut vault = load(vault)?;
+            vault.externalize_pepper(Path::new(file))?;
+            Ok(vault.save()?)
+        }
+        ["import-csv", vault, file, options @ ..] => import_csv(vault, file, options),
+        ["agent", command @ ..] => agent_command(command),
+        ["profiles"] => {
+            print!("{}", profiles());
+            Ok(())
+        }
+        ["list"] => {
+            Vault::list(&shared::vault_folder())?
+                .iter()
+                .for_each(|identifier| println!("{identifier}"));
+            Ok(())
+        }
+        _ => bail!("Invalid arguments.\n\n{USAGE}"),
+    }
+}
+
+/// Copies the password of a seed to the clipboard without any user interaction, making it suitable
+/// to be bound to a hotkey. Fails if the key is not available or incorrect.
+fn copy(vault: &str, seed: &str) -> Result<()> {
+    let mut vault = load(vault)?;
+    let key = key(&mut vault, false)?;
+    let seed = find_seed(&vault, seed)?;
+    let password = vault.password(seed, &key)?;
+    warn_site_length(seed, &password);
+
+    // the native clipboard is the expected case, and only returns once its contents are replaced
+    match clipboard::copy(&password, true)? {
+        clipboard::Method::Native => (),
+        method => eprintln!("Copied using {method}."),
+    }
+    Ok(())
+}
+
+/// Prints a field of a seed, or writes it to a file if `--out` is specified. See [`write_secret`].
+/// The field is the password unless another is picked with `--field`, in which case no key is
+/// needed.
+fn generate(vault: &str, seed: &str, options: &[&str]) -> Result<()> {
+    let (mut field, mut out, mut force) = ("password", None, false);
+    let mut options = options.iter();
+
+    while let Some(&option) = options.next() {
+        let mut value = || {
+            options
+                .next()
+                .copied()
+                .ok_or_else(|| anyhow!("Missing value for option '{option}'."))
+        };
+        match option {
+            "--field" => field = value()?,
+            "--out" => out = Some(value()?),
+            "--force" => force = true,
+            _ => bail!("Invalid option '{option}'.\n\n{USAGE}"),
+        }
+    }
+    if force && out.is_none() {
+        bail!("--force may only be specified with --out.");
+    }
+
+    let mut vault = load(vault)?;
+    let value = match field {
+        "password" => {
+            let key = key(&mut vault, true)?;
+            let seed = find_seed(&vault, seed)?;
+            let password = vault.password(seed, &key)?;
+            warn_site_length(seed, &password);
+            password
+        }
+        "username" | "url" => {
+            let seed = find_seed(&vault, seed)?;
+            let value = if field == "username" { &seed.username } else { &seed.url };
+            let value = value
+                .clone()
+                .ok_or_else(|| anyhow!("Seed '{}' has no {field}.", seed.identifier))?;
+            Zeroizing::new(value)
+        }
+        _ => bail!("Invalid field '{field}'. Must be one of username, url, or password."),
+    };
+
+    match out {
+        Some(out) => write_secret(Path::new(out), &value, force),
+        None => {
+            println!("{}", value.as_str());
+            Ok(())
+        }
+    }
+}
+
+/// Prints the password of a seed specified on the command line or in the environment, with a pepper
+/// read from the environment or stdin. Nothing is read from or written to disk, and the key is not
+/// verified since there is no authentication token to verify it against. See [`parse_seed_spec`].
+///
+/// The pepper is deliberately not accepted as an argument, since the arguments of a process can be
+/// read by other users of the system, e.g. with `ps`.
+///
+/// The key is normalized to NFC and hashed with the argon2 variant given by `--argon2`, which
+/// defaults to that of new vaults. The password therefore equals that of a vault seed with the
+/// same parameters if the vault normalizes text, uses the same variant, and has no prefix. Vaults
+/// created before the variant could be chosen use argon2d.
+fn generate_ephemeral(options: &[&str]) -> Result<()> {
+    let (mut pepper, mut spec) = (env::var(PEPPER_VAR).ok(), env::var(SEED_VAR).ok());
+    let mut variant = Argon2Variant::Argon2id;
+    let mut options = options.iter();
+
+    while let Some(&option) = options.next() {
+        let mut value = || {
+            options
+                .next()
+                .map(|value| value.to_string())
+                .ok_or_else(|| anyhow!("Missing value for option '{option}'."))
+        };
+        match option {
+            "--pepper-stdin" => {
+                let mut line = Zeroizing::new(String::new());
+                io::stdin().lock().read_line(&mut line)?;
+                pepper = Some(line.trim().to_owned());
+            }
+            "--seed" => spec = Some(value()?),
+            "--argon2" => variant = parse_variant(&value()?)?,
+            _ => bail!("Invalid option '{option}'.\n\n{USAGE}"),
+        }
+    }
+    let pepper = Zeroizing::new(
+        pepper.ok_or_else(|| anyhow!("No pepper given. Specify --pepper-stdin or set {PEPPER_VAR}."))?,
+    );
+    let spec = spec.ok_or_else(|| anyhow!("No seed given. Specify --seed or set {SEED_VAR}."))?;
+
+    let pepper = Zeroizing::new(
+        BASE64
+            .decode(pepper.trim().as_bytes())
+            .map_err(|e| anyhow!("The pepper is not valid base64: {e}"))?,
+    );
+    let seed = parse_seed_spec(&spec)?;
+    let key = match env::var(KEY_VAR) {
+        Ok(key) => Zeroizing::new(key),
+        Err(_) => read_key("Key: ")?,
+    };
+    let password = generate::derive_with(&key, &pepper, &seed, Normalization::Nfc, variant)?;
+    println!("{}", password.as_str());
+    Ok(())
+}
+
+/// Parses a seed from a specification of the form `identifier:length:salt:sets`, where:
+/// * the identifier is also the generation tag, and may itself contain colons,
+/// * the length is either fixed, e.g. `16`, or a range, e.g. `12-20`,
+/// * the salt is a decimal number,
+/// * the sets are the letters of the selected sets in the order `ULNSR` (upper case, lower case,
+///   numerical, special, and rare), as listed by the manifest. Dashes are ignored.
+///
+/// Parameters not included are those of [`SeedBuilder`]. In particular, the specification cannot
+/// express [exclusions](Seed::exclude), a [custom set](Seed::custom_set), or
+/// [`exclude_space`](Seed::exclude_space), so seeds using them cannot be reproduced.
+fn parse_seed_spec(spec: &str) -> Result<Seed> {
+    const FORMAT: &str = "Expected identifier:length:salt:sets, e.g. GitHub:12-20:0:ULNS";
+
+    // the identifier is split off last, such that it may contain colons
+    let mut parts = spec.rsplitn(4, ':');
+    let (sets, salt, length, identifier) = match (parts.next(), parts.next(), parts.next(), parts.next()) {
+        (Some(sets), Some(salt), Some(length), Some(identifier)) => (sets, salt, length, identifier),
+        _ => bail!("Invalid seed '{spec}'. {FORMAT}."),
+    };
+    let parse_length = |length: &str| {
+        length
+            .trim()
+            .parse::<u8>()
+            .map_err(|_| anyhow!("Invalid length '{length}'. Must be a number from 1 to {}.", u8::MAX))
+    };
+    let (min_len, max_len) = match length.split_once('-') {
+        Some((min, max)) => (parse_length(min)?, parse_length(max)?),
+        None => (parse_length(length)?, parse_length(length)?),
+    };
+    let salt = salt
+        .trim()
+        .parse()
+        .map_err(|_| anyhow!("Invalid salt '{salt}'. Must be a non-negative number."))?;
+
+    let characters: Characters = sets.trim().parse()?;
+    let seed = SeedBuilder::new()
+        .identifier(identifier.to_owned())
+        .length_range(min_len, max_len)
+        .salt(salt)
+        .characters(characters)
+        .build()?;
+    Ok(seed)
+}
+
+/// Prints a warning to stderr if the password exceeds the maximum length noted for the seed's
+/// service.
+fn warn_site_length(seed: &Seed, password: &str) {
+    if let Some(max) = seed.max_site_length.filter(|_| seed.exceeds_site_length(password)) {
+        eprintln!(
+            "Warning: the password is {} characters long, which exceeds the maximum of {max} noted \
+             for the service. It may be rejected.",
+            password.chars().count()
+        );
+    }
+}
+
+/// Writes a secret to a file without a trailing newline, e.g. for other programs to read it from a
+/// fifo or tmpfs. On unix, the file is made readable and writable only by the current user.
+///
+/// Existing regular files are only overwritten if `force` is set, while special files such as
+/// fifos are always written to.
+fn write_secret(path: &Path, secret: &str, force: bool) -> Result<()> {
+    if !force && fs::metadata(path).is_ok_and(|metadata| metadata.is_file()) {
+        bail!("'{}' already exists. Specify --force to overwrite it.", path.display());
+    }
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+
+        // the mode only applies to created files, so overwritten files are restricted explicitly
+        options.mode(0o600);
+        let file = options.open(path)?;
+
+        if file.metadata()?.is_file() {
+            file.set_permissions(fs::Permissions::from_mode(0o600))?;
+        }
+        (&file).write_all(secret.as_bytes())?;
+    }
+    #[cfg(not(unix))]
+    options.open(path)?.write_all(secret.as_bytes())?;
+
+    Ok(())
+}
+
+/// Prints all audit findings of a vault, failing if any is at least as severe as `fail_on`.
+fn audit(vault: &str, fail_on: Severity) -> Result<()> {
+    let vault = load(vault)?;
+    let findings = vault.audit_with(&config::get().audit_thresholds());
+
+    for (seed_index, finding) in &findings {
+        let identifier = &vault.get(*seed_index)?.identifier;
+        println!("{identifier}: {finding} ({:?} severity)", finding.severity());
+    }
+
+    let failures = findings
+        .iter()
+        .filter(|(_, finding)| finding.severity() >= fail_on)
+        .count();
+
+    if failures > 0 {
+        bail!("{failures} finding(s) of at least {fail_on:?} severity.");
+    }
+    Ok(())
+}
+
+/// Loads a vault and validates each of its seeds, printing whether each passed. Fails if any seed
+/// did. No key is needed, since only the structure of the vault is checked.
+fn check(vault: &str) -> Result<()> {
+    let vault = load(vault)?;
+    let mut failures = 0;
+
+    for seed in vault.seeds() {
+        let result = if seed.identifier.trim().is_empty() {
+            Err(vault::Error::EmptyIdentifier.to_string())
+        } else {
+            generate::validate(seed).map_err(|e| e.to_string())
+        };
+        match result {
+            Ok(()) => println!("pass  {}", seed.identifier),
+            Err(e) => {
+                println!("FAIL  {}: {e}", seed.identifier);
+                failures += 1;
+            }
+        }
+    }
+
+    if failures > 0 {
+        bail!("{failures} of {} seed(s) failed the check.", vault.seeds().len());
+    }
+    println!("All {} seed(s) of vault '{}' passed.", vault.seeds().len(), vault.identifier());
+    Ok(())
+}
+
+fn parse_severity(level: &str) -> Result<Severity> {
+    match level.to_lowercase().as_str() {
+        "low"    => Ok(Severity::Low),
+        "medium" => Ok(Severity::Medium),
+        "high"   => Ok(Severity::High),
+        _ => bail!("Invalid severity '{level}'. Expected low, medium, or high."),
+    }
+}
+
+fn parse_variant(name: &str) -> Result<Argon2Variant> {
+    Argon2Variant::ALL
+        .into_iter()
+        .find(|variant| variant.name() == name.to_lowercase())
+        .ok_or_else(|| anyhow!("Invalid argon2 variant '{name}'. Expected argon2d or argon2id."))
+}
+
+/// Lists the configured profiles and their vault folders, marking the active one with `*`. The
+/// default folder is listed first, and is active if no profile is.
+fn profiles() -> String {
+    let active = shared::profile();
+    let profiles = &config::get().profiles;
+    let width = profiles.keys().map(String::len).max().unwrap_or(0).max("default".len());
+    let mark = |is_active: bool| if is_active { '*' } else { ' ' };
+    let mut out = format!(
+        "{} {:width$}  {}\n",
+        mark(active.is_none()),
+        "default",
+        shared::default_vault_folder().display()
+    );
+
+    for name in profiles.keys() {
+        let folder = config::get().profile_folder(name).unwrap();
+        let line = format!("{} {name:width$}  {}\n", mark(active == Some(name)), folder.display());
+        out.push_str(&line);
+    }
+    out
+}
+
+/// Lists the parameters of all seeds in a vault, which together with the key and pepper suffice to
+/// regenerate every password. The key and pepper are deliberately excluded, so the manifest alone
+/// is of low sensitivity and may be kept as a recovery aid. The pepper, in contrast, must be kept
+/// as secret as the vault file itself.
+fn manifest(vault: &Vault) -> String {
+    let mut manifest = format!(
+        "# Seeds of vault '{}'. Passwords may only be regenerated with its key and pepper.\n\
+         # identifier\tgeneration tag\tusername\tmin length\tmax length\tsalt\tsets\tcustom set\texclude space\texcluded\tencoding\tpassphrase\tvariant\targon2\n",
+        vault.identifier()
+    );
+
+    for seed in vault.seeds() {
+        manifest += &format!(
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{:?}\t{}\t{}\t{}\t{}\n",
+            seed.identifier,
+            seed.gen_tag,
+            seed.username.as_deref().unwrap_or("-"),
+            seed.min_len,
+            seed.max_len,
+            seed.salt,
+            seed.characters.to_string(),
+            seed.custom_set.as_ref().map_or("-".to_owned(), |set| format!("{set:?}")),
+            seed.exclude_space,
+            seed.exclude,
+            seed.encoding.name(),
+            match &seed.kind {
+                SeedKind::Password => "-".to_owned(),
+                SeedKind::Passphrase { words, separator, hybrid } => {
+                    let hybrid = if *hybrid { " hybrid" } else { "" };
+                    format!("{words}{hybrid} words, {separator:?}")
+                }
+            },
+            seed.variant,
+            seed.argon2.map_or("default".to_owned(), |params| params.to_string()),
+        );
+    }
+    manifest
+}
+
+/// Renders the [manifest] of a vault as QR codes, split over several if it is too large for one.
+/// Each code starts with a line numbering it, e.g. `svalbard manifest 1/2`, such that the manifest
+/// can be pieced back together in order.
+///
+/// The codes are no more sensitive than the manifest itself. Whoever obtains both them and the
+/// pepper, however, is only the key away from every password.
+fn manifest_qr(vault: &Vault) -> Result<String> {
+    let chunks = qr::chunk(&manifest(vault), qr::MAX_CHUNK_LEN);
+    let mut out = String::new();
+
+    for (i, chunk) in chunks.iter().enumerate() {
+        let numbered = format!("svalbard manifest {}/{}\n{chunk}", i + 1, chunks.len());
+        out += &format!("Code {} of {}:\n{}\n\n", i + 1, chunks.len(), qr::render(&numbered)?);
+    }
+    Ok(out)
+}
+
+/// Lists the seeds of a vault, one per line with their identifier, username, and URL separated by
+/// tabs, or as a JSON array of every seed with all of its fields if `json` is set. Neither includes
+/// secrets, since seeds contain none.
+fn seeds(vault: &Vault, json: bool) -> Result<String> {
+    if json {
+        return Ok(serde_json::to_string_pretty(vault.seeds())? + "\n");
+    }
+    let lines = vault.seeds().iter().map(|seed| {
+        format!(
+            "{}\t{}\t{}\n",
+            seed.identifier,
+            seed.username.as_deref().unwrap_or("-"),
+            seed.url.as_deref().unwrap_or("-"),
+        )
+    });
+    Ok(lines.collect())
+}
+
+/// Describes the parameters of a vault which affect its passwords or the verification of its key.
+/// Nothing compromising is included: neither the pepper nor the authentication token is.
+fn info(vault: &Vault) -> String {
+    let pepper_location = match vault.pepper_source() {
+        PepperSource::Inline(_) => "stored in the vault file".to_owned(),
+        PepperSource::External { path } => format!("stored in {}", path.display()),
+    };
+    // passwords are hashed with the default cost unless overridden per seed, see `Seed::argon2`
+    formatdoc!(
+        "Identifier:    {}
+         File:          {}
+         Format:        JSON
+         Seeds:         {}
+         Pepper:        {} bytes, {pepper_location}
+         Key hash:      argon2i, {}
+         Password hash: {}, {}{}
+         Normalization: {}
+         Prefix:        {}
+        ",
+        vault.identifier(),
+        vault.path().display(),
+        vault.seeds().len(),
+        vault.pepper().len(),
+        vault.auth_params(),
+        vault.argon2_variant(),
+        Argon2Params::default(),
+        match vault.seeds().iter().filter(|seed| seed.argon2.is_some()).count() {
+            0 => String::new(),
+            n => format!(" (overridden by {n} seed(s))"),
+        },
+        vault.normalization(),
+        vault.prefix().unwrap_or("none"),
+    )
+}
+
+/// Adds a seed with the default parameters of the vault for each row of a CSV file, such as those
+/// exported by other password managers. Rows without a name, or whose name is already used by a
+/// seed, are skipped.
+///
+/// Columns are recognized by their headers, which may be specified per field with `--<field>
+/// <column>` options. Passwords are never imported, since Svalbard derives its own.
+fn import_csv(vault: &str, file: &str, options: &[&str]) -> Result<()> {
+    let mut columns = HashMap::new();
+
+    for option in options.chunks(2) {
+        match option {
+            [flag, column] => match flag.strip_prefix("--") {
+                Some(field) if CSV_FIELDS.iter().any(|(f, _)| *f == field) => {
+                    columns.insert(field, column.to_lowercase());
+                }
+                _ => bail!("Invalid option '{flag}'.\n\n{USAGE}"),
+            },
+            _ => bail!("Missing column for option '{}'.", option[0]),
+        }
+    }
+
+    let mut vault = load(vault)?;
+    let mut reader = csv::Reader::from_path(file)?;
+    let headers: Vec<String> = reader.headers()?.iter().map(str::to_lowercase).collect();
+
+    // resolve the index of each field's column, requiring explicitly specified columns to exist
+    let mut indices = HashMap::new();
+
+    for (field, defaults) in CSV_FIELDS {
+        let index = match columns.get(field) {
+            Some(column) => Some(
+                headers
+                    .iter()
+                    .position(|header| header == column)
+                    .ok_or_else(|| anyhow!("Column '{column}' not found in '{file}'."))?,
+            ),
+            None => headers.iter().position(|header| defaults.contains(&header.as_str())),
+        };
+        if let Some(index) = index {
+            indices.insert(field, index);
+        }
+    }
+    let name_index = *indices
+        .get("name")
+        .ok_or_else(|| anyhow!("No name column found in '{file}'. Specify one with --name."))?;
+
+    let (mut imported, mut skipped) = (0, 0);
+
+    for record in reader.records() {
+        let record = record?;
+        let field = |field| {
+            indices
+                .get(field)
+                .and_then(|&i| record.get(i))
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(str::to_owned)
+        };
+        let identifier = record.get(name_index).unwrap_or_default().trim();
+
+        let mut seed = vault.new_seed(identifier.to_owned(), field("username"));
+        seed.url = field("url");
+        seed.notes = field("notes");
+
+        // rows without a name or with a taken one are skipped
+        match vault.try_push(seed) {
+            Ok(()) => imported += 1,
+            Err(e) if e.is_invalid_input() => skipped += 1,
+            Err(e) => return Err(e.into()),
+        }
+    }
+    vault.save()?;
+
+    println!("Imported {imported} seed(s), skipped {skipped} row(s).");
+    println!("Note that passwords are not imported. Update each service to the password Svalbard derives.");
+    Ok(())
+}
+
+#[cfg(unix)]
+fn agent_command(args: &[&str]) -> Result<()> {
+    let parse_ttl = |secs: &str| {
+        secs.parse()
+            .map(std::time::Duration::from_secs)
+            .map_err(|_| anyhow!("Invalid duration '{secs}'."))
+    };
+
+    match args {
+        ["start"] => agent::start(agent::DEFAULT_TTL),
+        ["start", secs] => agent::start(parse_ttl(secs)?),
+        ["serve", secs] => agent::serve(parse_ttl(secs)?),
+        ["add", vault] => {
+            if !agent::is_running() {
+                bail!("Agent is not running.");
+            }
+            let mut vault = load(vault)?;
+            let key = key(&mut vault, true)?;
+
+            // the key is cached explicitly, since one read from the environment is never cached
+            agent::set(&vault.path().to_string_lossy(), &key);
+            Ok(())
+        }
+        ["clear"] => agent::clear(),
+        ["stop"] => agent::stop(),
+        _ => bail!("Invalid arguments.\n\n{USAGE}"),
+    }
+}
+
+#[cfg(not(unix))]
+fn agent_command(_: &[&str]) -> Result<()> {
+    bail!("The key agent is only supported on unix platforms.")
+}
+
+/// Returns the exit code of a failed command. See [`KEY_MISMATCH_CODE`].
+pub fn exit_code(error: &anyhow::Error) -> u8 {
+    match error.downcast_ref() {
+        Some(vault::Error::KeyMismatch(_) | vault::Error::KeyVerificationFailed(_)) => {
+            KEY_MISMATCH_CODE
+        }
+        _ => 1,
+    }
+}
+
+/// Prompts for the current key of a vault and a new one, and changes the key after warning that
+/// all passwords change.
+fn change_key(vault: &str) -> Result<()> {
+    let mut vault = load(vault)?;
+    let old_key = read_key(&format!("Current key for '{}': ", vault.identifier()))?;
+
+    if !vault.verify_key(&old_key) {
+        return Err(vault::Error::KeyVerificationFailed(vault.identifier().to_owned()).into());
+    }
+    eprintln!("{CHANGE_KEY_WARNING}");
+
+    let new_key = read_key("New key: ")?;
+
+    if *read_key("Repeat new key: ")? != *new_key {
+        bail!("Keys do not match. The key was not changed.");
+    }
+    let change = vault.change_key(&old_key, &new_key)?;
+    vault.save()?;
+    println!("Key of vault '{}' changed. {change}", vault.identifier());
+    Ok(())
+}
+
+fn load(vault: &str) -> Result<Vault> {
+    let folder = shared::vault_folder();
+
+    if !Vault::exists(&folder, vault) {
+        bail!("Vault '{vault}' does not exist.");
+    }
+    let mut vault = Vault::load(&folder, vault.to_owned())?;
+    config::get().configure(&mut vault);
+    Ok(vault)
+}
+
+/// Obtains the key of a vault from the environment, the agent, or, if `interactive` is set, by
+/// prompting the user. Keys obtained from the environment or the user are verified, and an error
+/// is returned if they are incorrect.
+///
+/// Verified keys [upgrade](Vault::verify_and_upgrade) the hash of the key to the configured cost.
+fn key(vault: &mut Vault, interactive: bool) -> Result<Zeroizing<String>> {
+    fn verify(vault: &mut Vault, key: Zeroizing<String>) -> Result<Zeroizing<String>> {
+        if vault.verify_and_upgrade(&key)? {
+            Ok(key)
+        } else {
+            Err(vault::Error::KeyMismatch(vault.identifier().to_owned()).into())
+        }
+    }
+
+    if let Ok(key) = env::var(KEY_VAR) {
+        return verify(vault, Zeroizing::new(key));
+    }
+
+    #[cfg(unix)]
+    let cache_id = vault.path().to_string_lossy().into_owned();
+
+    // a cached key may be outdated, in which case the user is prompted as usual
+    #[cfg(unix)]
+    if let Some(key) = agent::get(&cache_id) {
+        if vault.verify_and_upgrade(&key)? {
+            return Ok(key);
+        }
+    }
+
+    if !interactive {
+        bail!("No key available. Set the {KEY_VAR} environment variable or add it to the agent.");
+    }
+    let prompt = format!("Key for '{}': ", vault.identifier());
+    let key = verify(vault, read_key(&prompt)?)?;
+
+    #[cfg(unix)]
+    agent::set(&cache_id, &key);
+
+    Ok(key)
+}
+
+/// Reads a key from the terminal without echoing it, or from a single line of stdin if it is not a
+/// terminal.
+fn read_key(prompt: &str) -> Result<Zeroizing<String>> {
+    if io::stdin().is_tty() {
+        Ok(Zeroizing::new(rpassword::prompt_password(prompt)?))
+    } else {
+        let mut line = Zeroizing::new(String::new());
+        io::stdin().lock().read_line(&mut line)?;
+        Ok(Zeroizing::new(line.trim_end_matches(&['\r', '\n'][..]).to_owned()))
+    }
+}
+
+/// Finds a seed by identifier, preferring exact matches over case-insensitive ones. If neither
+/// exists, the best fuzzy match is suggested in the error.
+fn find_seed<'a>(vault: &'a Vault, identifier: &str) -> Result<&'a Seed> {
+    let seeds = vault.seeds();
+
+    seeds
+        .iter()
+        .find(|seed| seed.identifier == identifier)
+        .or_else(|| {
+            let identifier = identifier.to_lowercase();
+            seeds.iter().find(|seed| seed.identifier.to_lowercase() == identifier)
+        })
+        .ok_or_else(|| {
+            let msg = format!("Seed '{identifier}' not found in vault '{}'.", vault.identifier());
+            let suggestion = seeds
+                .iter()
+                .filter_map(|seed| seed.match_score(identifier).map(|score| (score, seed)))
+                .max_by_key(|&(score, _)| score);
+
+            match suggestion {
+                Some((_, seed)) => anyhow!("{msg} Did you mean '{}'?", seed.identifier),
+                None => anyhow!(msg),
+            }
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seed_spec() {
+        let seed = parse_seed_spec("GitHub:12-20:3:UL-N-").unwrap();
+        assert_eq!((seed.identifier.as_str(), seed.gen_tag.as_str()), ("GitHub", "GitHub"));
+        assert_eq!((seed.min_len, seed.max_len, seed.salt), (12, 20, 3));
+        assert_eq!(seed.characters, Characters::UPPER_CASE | Characters::LOWER_CASE | Characters::NUMERICAL);
+
+        let seed = parse_seed_spec("host:8080:16:0:n").unwrap();
+        assert_eq!((seed.identifier.as_str(), seed.min_len), ("host:8080", 16));
+
+        for invalid in ["GitHub:16:0", ":16:0:U", "a:0:0:U", "a:20-12:0:U", "a:256:0:U", "a:16:-1:U", "a:16:0:X", "a:16:0:"] {
+            assert!(parse_seed_spec(invalid).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn ephemeral_variant() {
+        assert_eq!(parse_variant("Argon2d").unwrap(), Argon2Variant::Argon2d);
+        assert!(parse_variant("argon2i").is_err());
+
+        // new vaults normalize text, so their passwords are reproduced given their variant
+        for variant in Argon2Variant::ALL {
+            let mut vault = Vault::scratch("ephemeral".to_owned(), "kéy", variant).unwrap();
+            let seed = parse_seed_spec("GitHub:12-20:0:ULNS").unwrap();
+            vault.push(seed.clone());
+
+            let ephemeral =
+                generate::derive_with("kéy", vault.pepper(), &seed, Normalization::Nfc, variant);
+            assert_eq!(ephemeral.unwrap(), vault.password(&vault.seeds()[0], "kéy").unwrap());
+        }
+    }
+
+    #[test]
+    fn seeds_json() {
+        let variant = Argon2Variant::Argon2id;
+        let mut vault = Vault::scratch("seeds".to_owned(), "key", variant).unwrap();
+        let mut seed = Seed::basic("GitHub".to_owned(), Some("user".to_owned()));
+        seed.url = Some("https://github.com/login".to_owned());
+        vault.push(seed);
+
+        assert_eq!(seeds(&vault, false).unwrap(), "GitHub\tuser\thttps://github.com/login\n");
+        let json: serde_json::Value = serde_json::from_str(&seeds(&vault, true).unwrap()).unwrap();
+        assert_eq!(json[0]["url"], "https://github.com/login");
+    }
 }
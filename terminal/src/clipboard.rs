@@ -0,0 +1,187 @@
+//! Copies text to the clipboard. Since the native clipboard isn't available in every environment
+//! (e.g. some Wayland compositors, or over SSH), the [automatic backend](Backend::Auto) tries a
+//! number of methods in order:
+//! 1. The native clipboard of the platform.
+//! 2. The `wl-copy`, `xclip`, and `xsel` programs, if installed and a display server is running.
+//! 3. OSC 52 escape sequences, which instruct the terminal itself to set the clipboard. These also
+//!    work over SSH, and in tmux if `set-clipboard` is enabled.
+
+use crate::config;
+use anyhow::{bail, Result};
+use arboard::Clipboard;
+use crossterm::tty::IsTty;
+use data_encoding::BASE64;
+use serde::Deserialize;
+use std::{
+    env, fmt,
+    io::{self, Write},
+    process::{Command, Stdio},
+};
+
+/// Lists the external programs tried, their arguments, and the environment variable indicating that
+/// the display server they target is running.
+const PROGRAMS: [(&str, &[&str], &str); 3] = [
+    ("wl-copy", &[], "WAYLAND_DISPLAY"),
+    ("xclip", &["-selection", "clipboard"], "DISPLAY"),
+    ("xsel", &["--clipboard", "--input"], "DISPLAY"),
+];
+
+/// Specifies the largest base64 encoded payload of an OSC 52 sequence. Terminals cap the payload at
+/// different sizes and silently drop larger ones, so the smallest common cap is used.
+const OSC52_MAX_LEN: usize = 74_994;
+
+/// Defines the selectable clipboard backends.
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Default, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Backend {
+    /// Tries each method in order until one succeeds.
+    #[default]
+    Auto,
+    /// Only uses the native clipboard.
+    Native,
+    /// Only uses OSC 52 escape sequences. Requires no display server or external programs, making
+    /// it suitable for headless and remote sessions.
+    Osc52,
+}
+
+/// Defines the ways in which text may be copied.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Method {
+    Native,
+    Program(&'static str),
+    Osc52,
+}
+
+impl fmt::Display for Method {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Method::Native => write!(f, "the native clipboard"),
+            Method::Program(program) => write!(f, "{program}"),
+            Method::Osc52 => write!(f, "an OSC 52 escape sequence"),
+        }
+    }
+}
+
+/// Copies text to the clipboard with the [configured backend](config::Config::clipboard), returning
+/// the method that succeeded. Fails with the reasons of each method if none did.
+///
+/// On Linux, the native clipboard contents are served by the process that set them and vanish once
+/// it exits. If `hold` is set, the contents are therefore kept alive by blocking the current thread
+/// until another application replaces them. This should be used by short-lived processes, and has
+/// no effect on other platforms or methods.
+pub fn copy(text: &str, hold: bool) -> Result<Method> {
+    match config::get().clipboard {
+        Backend::Auto => copy_auto(text, hold),
+        Backend::Native => copy_native(text, hold).map(|_| Method::Native),
+        Backend::Osc52 => copy_osc52(text).map(|_| Method::Osc52),
+    }
+}
+
+fn copy_auto(text: &str, hold: bool) -> Result<Method> {
+    let mut failures = Vec::new();
+
+    match copy_native(text, hold) {
+        Ok(()) => return Ok(Method::Native),
+        Err(e) => failures.push(format!("{}: {e}", Method::Native)),
+    }
+
+    for (program, args, display) in PROGRAMS {
+        if env::var_os(display).is_none() || !is_installed(program) {
+            continue;
+        }
+        match copy_program(text, program, args) {
+            Ok(()) => return Ok(Method::Program(program)),
+            Err(e) => failures.push(format!("{program}: {e}")),
+        }
+    }
+
+    match copy_osc52(text) {
+        Ok(()) => Ok(Method::Osc52),
+        Err(e) => {
+            failures.push(format!("{}: {e}", Method::Osc52));
+            bail!("Failed to copy to the clipboard:\n* {}", failures.join("\n* "))
+        }
+    }
+}
+
+fn copy_native(text: &str, hold: bool) -> Result<()> {
+    let mut clipboard = Clipboard::new()?;
+
+    #[cfg(target_os = "linux")]
+    if hold {
+        use arboard::SetExtLinux;
+        clipboard.set().wait().text(text)?;
+        return Ok(());
+    }
+    #[cfg(not(target_os = "linux"))]
+    let _ = hold;
+
+    clipboard.set_text(text)?;
+    Ok(())
+}
+
+/// Pipes the text to an external program. The programs keep serving the clipboard in the
+/// background after the text is received, so there is no need to hold it.
+fn copy_program(text: &str, program: &str, args: &[&str]) -> Result<()> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+
+    // the pipe is closed when dropped, signalling the end of the text
+    child.stdin.take().unwrap().write_all(text.as_bytes())?;
+    let status = child.wait()?;
+
+    if !status.success() {
+        bail!("exited with {status}");
+    }
+    Ok(())
+}
+
+/// Writes an OSC 52 escape sequence to the terminal. Whether the terminal honours it cannot be
+/// detected, so this only fails if there is no terminal to write to.
+fn copy_osc52(text: &str) -> Result<()> {
+    let mut stdout = io::stdout();
+
+    if !stdout.is_tty() {
+        bail!("stdout is not a terminal");
+    }
+    write!(stdout, "{}", osc52_sequence(text)?)?;
+    stdout.flush()?;
+    Ok(())
+}
+
+/// Creates an OSC 52 sequence setting the clipboard to the text. Fails if the text is too large to
+/// be accepted by all terminals, see [`OSC52_MAX_LEN`].
+fn osc52_sequence(text: &str) -> Result<String> {
+    let payload = BASE64.encode(text.as_bytes());
+
+    if payload.len() > OSC52_MAX_LEN {
+        bail!(
+            "text is too large for OSC 52 ({} bytes encoded, the maximum is {OSC52_MAX_LEN})",
+            payload.len()
+        );
+    }
+    Ok(format!("\x1b]52;c;{payload}\x07"))
+}
+
+/// Returns whether a program is found in any of the directories of `PATH`.
+fn is_installed(program: &str) -> bool {
+    env::var_os("PATH")
+        .map(|path| env::split_paths(&path).any(|dir| dir.join(program).is_file()))
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn osc52() {
+        assert_eq!(osc52_sequence("hunter2").unwrap(), "\x1b]52;c;aHVudGVyMg==\x07");
+        assert!(osc52_sequence(&"a".repeat(OSC52_MAX_LEN / 4 * 3)).is_ok());
+        assert!(osc52_sequence(&"a".repeat(OSC52_MAX_LEN / 4 * 3 + 1)).is_err());
+    }
+}
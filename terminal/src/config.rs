@@ -0,0 +1,121 @@
+//! Contains the user configuration of the terminal front-end, read from `config.json` in the
+//! executable folder. All settings are optional and fall back to their defaults if absent.
+
+use crate::{clipboard, shared, tui::{keymap::Keymap, theme::{Theme, ThemeSpec}}};
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::{collections::BTreeMap, env, fs, path::PathBuf, sync::OnceLock, time::Duration};
+use vault::{audit::AuditThresholds, generate::Argon2Params, Vault};
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Whether moving past the last row of a list wraps around to the first, and vice versa.
+    pub wrap_navigation: bool,
+    /// Colors of the interface, either `"dark"`, `"light"`, or an object of custom colors.
+    pub theme: ThemeSpec,
+    /// Disables all colors and text styles. Also enabled by setting the `NO_COLOR` environment
+    /// variable, see <https://no-color.org>.
+    pub no_color: bool,
+    /// Keys bound to each action of the vault view, overriding the defaults per action. Validated
+    /// for conflicts when loaded.
+    pub keymap: Keymap,
+    /// Whether generated passwords are initially displayed in groups of characters for
+    /// readability. May be toggled while the password is displayed.
+    pub group_passwords: bool,
+    /// Whether generated passwords are initially masked, such that they can't be read over the
+    /// shoulder. Pressing `r` reveals and masks them again. Revealing only while the key is held
+    /// would require key release events, which the terminal backend doesn't report.
+    pub mask_passwords: bool,
+    /// Number of previous versions kept of each vault file, see [`Vault::save`]. Defaults to
+    /// [`vault::DEFAULT_BACKUP_COUNT`].
+    pub backup_count: Option<usize>,
+    /// Number of idle seconds after which the entered key is forgotten, such that it must be
+    /// re-entered to generate passwords. Defaults to 5 minutes, and 0 disables locking.
+    pub lock_after: Option<u64>,
+    /// Cost of the argon2 hash verifying keys, as `{ "mem_cost": KiB, "time_cost": passes,
+    /// "lanes": n }`. Vaults are upgraded to it once their key is next entered. Defaults to the
+    /// parameters of existing vaults.
+    pub auth_params: Option<Argon2Params>,
+    /// Vault folders selectable by name with `--profile <name>` or the `SVALBARD_PROFILE`
+    /// environment variable, e.g. to keep personal and work vaults apart. Relative folders are
+    /// relative to the executable folder.
+    pub profiles: BTreeMap<String, PathBuf>,
+    /// Method of copying to the clipboard, either `"auto"`, `"native"`, or `"osc52"`. The latter
+    /// copies through the terminal itself, e.g. over SSH. Defaults to trying each in order.
+    pub clipboard: clipboard::Backend,
+    /// Number of days after which the audit flags a password as due for rotation if it hasn't
+    /// been generated since. Generation is only recorded by the interface. Defaults to a year, and
+    /// 0 disables the check.
+    pub stale_after: Option<u64>,
+}
+
+impl Config {
+    pub fn theme(&self) -> Theme {
+        let mut theme = self.theme.theme();
+        theme.plain = self.no_color || env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+        theme
+    }
+
+    /// Returns how long the interface may be idle before the vault is locked, or [None] if it is
+    /// never locked.
+    pub fn lock_timeout(&self) -> Option<Duration> {
+        match self.lock_after.unwrap_or(5 * 60) {
+            0 => None,
+            secs => Some(Duration::from_secs(secs)),
+        }
+    }
+
+    /// Returns the thresholds of audits, see [`Config::stale_after`].
+    pub fn audit_thresholds(&self) -> AuditThresholds {
+        let mut thresholds = AuditThresholds::default();
+
+        if let Some(days) = self.stale_after {
+            thresholds.max_age = match days {
+                0 => None,
+                days => Some(Duration::from_secs(days * 24 * 60 * 60)),
+            };
+        }
+        thresholds
+    }
+
+    /// Returns the vault folder of a profile, or [None] if no such profile is configured.
+    pub fn profile_folder(&self, name: &str) -> Option<PathBuf> {
+        self.profiles.get(name).map(|folder| shared::exe_folder().join(folder))
+    }
+
+    /// Applies the settings concerning a loaded vault.
+    pub fn configure(&self, vault: &mut Vault) {
+        if let Some(count) = self.backup_count {
+            vault.set_backup_count(count);
+        }
+        if let Some(params) = self.auth_params {
+            vault.set_auth_params(params);
+        }
+    }
+}
+
+/// Loads the configuration from disk. Must be called before [`get`] to take effect.
+///
+/// # Errors
+/// If the configuration file exists but cannot be read or parsed.
+pub fn init() -> Result<()> {
+    let path = shared::exe_folder().join("config.json");
+    let config = if path.exists() {
+        let string = fs::read_to_string(&path)
+            .with_context(|| format!("Could not read config {}", path.display()))?;
+        serde_json::from_str(&string)
+            .with_context(|| format!("Could not parse config {}", path.display()))?
+    } else {
+        Config::default()
+    };
+    let _ = CONFIG.set(config);
+    Ok(())
+}
+
+/// Returns the loaded configuration, or the default configuration if none has been loaded.
+pub fn get() -> &'static Config {
+    CONFIG.get_or_init(Config::default)
+}
@@ -2,17 +2,40 @@
 //! * TUI: the interface is drawn onto the terminal window with ASCII graphics and navigated with keyboard input.
 //! * CLI: the program accepts, interprets, and executes command-line arguments.
 
+#[cfg(unix)]
+mod agent;
 mod cli;
+mod clipboard;
+mod config;
+mod qr;
 mod shared;
 mod tui;
 
 use anyhow::Result;
-use std::env;
+use std::{env, process};
 
 fn main() -> Result<()> {
-    if env::args().len() > 2 {
-        cli::launch()
-    } else {
-        tui::launch()
+    config::init()?;
+
+    let args: Vec<String> = env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+
+    // the profile applies to both front-ends, so it is selected before either is launched
+    let (profile, args) = match args.as_slice() {
+        ["--profile", profile, args @ ..] => (Some(*profile), args),
+        args => (None, args),
+    };
+    shared::select_profile(profile)?;
+
+    match args {
+        [] => tui::launch(),
+        ["scratch"] => tui::launch_scratch(),
+        args => {
+            if let Err(e) = cli::launch(args) {
+                eprintln!("Error: {e:?}");
+                process::exit(cli::exit_code(&e).into());
+            }
+            Ok(())
+        }
     }
 }
@@ -0,0 +1,69 @@
+//! Renders text as QR codes in the terminal, e.g. to transfer it to a phone without a network.
+
+use anyhow::Result;
+use qrcode::{render::unicode::Dense1x2, EcLevel, QrCode};
+
+/// Specifies the largest number of bytes encoded per QR code by [`chunk`]. Larger codes become too
+/// dense to be scanned from a terminal reliably.
+pub const MAX_CHUNK_LEN: usize = 400;
+
+/// Renders text as a QR code of unicode half blocks, two modules per character. The code is drawn
+/// for light text on a dark background, as used by most terminals.
+///
+/// # Errors
+/// If the text is too long to fit a QR code.
+pub fn render(text: &str) -> Result<String> {
+    let code = QrCode::with_error_correction_level(text, EcLevel::M)?;
+    let rendered = code
+        .render::<Dense1x2>()
+        .dark_color(Dense1x2::Light)
+        .light_color(Dense1x2::Dark)
+        .build();
+    Ok(rendered)
+}
+
+/// Splits text into chunks of at most `max_len` bytes, each of which may then be rendered as a
+/// separate QR code. Chunks are split between lines where possible, such that each line of the
+/// text is read whole from a single code.
+pub fn chunk(text: &str, max_len: usize) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+
+    for line in text.split_inclusive('\n') {
+        if current.len() + line.len() > max_len && !current.is_empty() {
+            chunks.push(std::mem::take(&mut current));
+        }
+        // lines longer than a chunk are split wherever they must be
+        let mut rest = line;
+        while rest.len() > max_len {
+            let mut end = max_len;
+            while !rest.is_char_boundary(end) {
+                end -= 1;
+            }
+            chunks.push(rest[..end].to_owned());
+            rest = &rest[end..];
+        }
+        current.push_str(rest);
+    }
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chunk_lines() {
+        let text = "aaaa\nbbbb\ncc\n";
+        assert_eq!(chunk(text, 10), ["aaaa\nbbbb\n", "cc\n"]);
+        assert_eq!(chunk(text, 4), ["aaaa", "\n", "bbbb", "\ncc\n"]);
+        assert_eq!(chunk("ééé", 3), ["é", "é", "é"]);
+        assert!(chunk("", 10).is_empty());
+
+        let long = "line\n".repeat(100);
+        assert_eq!(chunk(&long, MAX_CHUNK_LEN).concat(), long);
+    }
+}
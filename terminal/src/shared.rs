@@ -1,4 +1,9 @@
-use std::{env, path::PathBuf};
+use crate::config;
+use anyhow::{bail, Result};
+use std::{env, path::PathBuf, sync::OnceLock};
+
+/// Name of the active profile, if one was selected. See [`select_profile`].
+static PROFILE: OnceLock<String> = OnceLock::new();
 
 pub fn exe_folder() -> PathBuf {
     env::current_exe()
@@ -7,8 +12,59 @@ pub fn exe_folder() -> PathBuf {
         .unwrap()
 }
 
+/// Returns the folder containing the vaults of the active profile, or the default folder if no
+/// profile is active.
 pub fn vault_folder() -> PathBuf {
+    match profile() {
+        Some(name) => config::get().profile_folder(name).unwrap(),
+        None => default_vault_folder(),
+    }
+}
+
+/// Returns the folder containing the vaults when no profile is active.
+pub fn default_vault_folder() -> PathBuf {
     let mut container = exe_folder();
     container.push("vaults");
     container
 }
+
+/// Selects the active profile by name, falling back to the `SVALBARD_PROFILE` environment variable
+/// if none is given. Must be called before [`vault_folder`] to take effect.
+///
+/// # Errors
+/// If the profile is not configured.
+pub fn select_profile(name: Option<&str>) -> Result<()> {
+    let name = match name {
+        Some(name) => name.to_owned(),
+        None => match env::var("SVALBARD_PROFILE") {
+            Ok(name) if !name.is_empty() => name,
+            _ => return Ok(()),
+        },
+    };
+    if config::get().profile_folder(&name).is_none() {
+        bail!("Profile '{name}' is not configured. Profiles are listed with `svalbard profiles`.");
+    }
+    let _ = PROFILE.set(name);
+    Ok(())
+}
+
+/// Returns the name of the active profile, if any.
+pub fn profile() -> Option<&'static str> {
+    PROFILE.get().map(String::as_str)
+}
+
+/// Normalizes a user-entered URL such that it may be opened in a browser, prepending `https://` if
+/// no scheme is specified.
+///
+/// Returns [None] if the URL is empty or contains whitespace.
+pub fn normalize_url(url: &str) -> Option<String> {
+    let url = url.trim();
+
+    if url.is_empty() || url.contains(char::is_whitespace) {
+        None
+    } else if url.contains("://") {
+        Some(url.to_owned())
+    } else {
+        Some(format!("https://{url}"))
+    }
+}
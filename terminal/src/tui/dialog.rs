@@ -1,17 +1,44 @@
 use super::{
     input::{self, Form, FormWidget},
     state::{State, Status},
-    utility::{Center, WrappedString},
+    utility::{self, Center, WrappedString},
     Frame, Terminal,
 };
+use crate::{clipboard, config};
 use anyhow::Result;
 use crossterm::event::{KeyCode, KeyModifiers};
+use std::{
+    iter,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver},
+    },
+    thread,
+    time::{Duration, Instant},
+};
 use tui::{
-    layout::{Constraint, Layout},
-    style::{Color, Modifier, Style},
-    widgets::{Block, BorderType, Borders, Clear},
+    layout::{Constraint, Layout, Rect},
+    style::{Modifier, Style},
+    widgets::{Block, BorderType, Borders, Clear, Gauge, Paragraph, Wrap},
 };
 
+/// Specifies how long the user must be idle after changing a form before its preview is
+/// regenerated.
+const PREVIEW_DELAY: Duration = Duration::from_millis(500);
+
+/// Specifies the least width of the content of a dialog. Narrower dialogs are replaced by
+/// [`too_small`].
+const MIN_CONTENT_WIDTH: u16 = 12;
+
+/// Contains the hint displayed by progress dialogs.
+const PROGRESS_HINT: &str = "Press (esc) to cancel...";
+
+/// Specifies the number of characters per group when a password is displayed grouped.
+const PASSWORD_GROUP_SIZE: usize = 4;
+
+/// Replaces each character of a password while it is masked.
+const PASSWORD_MASK: char = '•';
+
 /// Displays a warning and returns whether the user confirmed.
 pub fn confirm<S>(term: &mut Terminal, bg: Option<&dyn State>, msg: S) -> Result<bool>
 where
@@ -45,6 +72,33 @@ where
     notice(term, bg, NoticeLevel::Error, msg)
 }
 
+/// Displays a dialog with a message which may be scrolled through, until closed by the user. Used
+/// for messages which may not fit the dialog.
+pub fn scrollable<S>(term: &mut Terminal, bg: Option<&dyn State>, title: &str, msg: S) -> Result<()>
+where
+    S: Into<String>,
+{
+    let content = DialogContent::Scrollable {
+        title: title.to_owned(),
+        msg: msg.into(),
+        offset: 0,
+    };
+    dialog(term, bg, content).map(|_| ())
+}
+
+/// Displays a generated password until closed by the user. The password may be displayed in groups
+/// of characters for readability, and may be masked, both of which are toggled by the user. These
+/// only affect the display; the password is always copied as is.
+pub fn password(term: &mut Terminal, bg: Option<&dyn State>, identifier: &str, password: &str) -> Result<()> {
+    let content = DialogContent::Password {
+        identifier: identifier.to_owned(),
+        password: password.to_owned(),
+        grouped: config::get().group_passwords,
+        revealed: !config::get().mask_passwords,
+    };
+    dialog(term, bg, content).map(|_| ())
+}
+
 /// Displays a fatal error dialog until a key is pressed.
 pub fn fatal<S>(term: &mut Terminal, msg: S) -> Result<()>
 where
@@ -53,6 +107,23 @@ where
     notice(term, None, NoticeLevel::Fatal, msg)
 }
 
+/// Displays a dialog listing options to pick from, initially selecting the one at index `selected`.
+/// Returns the index of the picked option, or [None] if the user cancels.
+pub fn pick(
+    term: &mut Terminal,
+    bg: Option<&dyn State>,
+    title: &str,
+    options: Vec<String>,
+    selected: usize,
+) -> Result<Option<usize>> {
+    let content = DialogContent::Pick { title: title.to_owned(), options, selected };
+
+    match dialog(term, bg, content)? {
+        Some(DialogContent::Pick { selected, .. }) => Ok(Some(selected)),
+        _ => Ok(None),
+    }
+}
+
 /// Displays a dialog with an input form. Depending on how the user exits the dialog, the form is
 /// returned for inspection.
 pub fn form(term: &mut Terminal, bg: Option<&dyn State>, form: Form) -> Result<Option<Form>> {
@@ -62,6 +133,78 @@ pub fn form(term: &mut Terminal, bg: Option<&dyn State>, form: Form) -> Result<O
     }
 }
 
+/// Displays a dialog with an input form and a preview line generated from the form contents.
+///
+/// Since the preview may be expensive to generate, it is regenerated only once the user has been
+/// idle for a short while after changing the form. If the generator returns [None], the form
+/// contents are deemed invalid and no preview is shown.
+pub fn form_with_preview(
+    term: &mut Terminal,
+    bg: Option<&dyn State>,
+    form: Form,
+    generator: &dyn Fn(&Form) -> Option<String>,
+) -> Result<Option<Form>> {
+    let preview = Preview {
+        generator,
+        value: None,
+        changed: Some(Instant::now()),
+    };
+    let state = Dialog {
+        content: DialogContent::Form(form),
+        bg,
+        preview: Some(preview),
+        error: None,
+    }.exec(term)?;
+
+    match state.map(|d| d.content) {
+        Some(DialogContent::Form(form)) => Ok(Some(form)),
+        _ => Ok(None),
+    }
+}
+
+/// Displays a progress bar while `task` is performed for each index in `0..total` on a worker
+/// thread, keeping the interface responsive during slow operations. Returns the results in order,
+/// or [None] if the user cancels.
+///
+/// Cancelling takes effect once the task currently being performed finishes.
+pub fn progress<T, F>(
+    term: &mut Terminal,
+    bg: Option<&dyn State>,
+    title: &str,
+    total: usize,
+    task: F,
+) -> Result<Option<Vec<T>>>
+where
+    T: Send,
+    F: Fn(usize) -> T + Sync,
+{
+    let cancelled = AtomicBool::new(false);
+    let (sender, receiver) = mpsc::channel();
+
+    thread::scope(|scope| {
+        let (cancelled, task) = (&cancelled, &task);
+
+        scope.spawn(move || {
+            for i in 0..total {
+                if cancelled.load(Ordering::Relaxed) || sender.send(task(i)).is_err() {
+                    break;
+                }
+            }
+        });
+        let state = Progress {
+            title,
+            total,
+            results: Vec::with_capacity(total),
+            receiver,
+            bg,
+        }.exec(term);
+
+        // stop the worker if the user cancelled or an error occurred
+        cancelled.store(true, Ordering::Relaxed);
+        Ok(state?.map(|progress| progress.results))
+    })
+}
+
 /// Displays a dialog with a message of a certain priority level specified by [`NoticeLevel`].
 fn notice<S>(term: &mut Terminal, bg: Option<&dyn State>, level: NoticeLevel, msg: S) -> Result<()>
 where
@@ -73,7 +216,7 @@ where
 /// Displays a dialog with specified contents. Depending on how the user exits the dialog, the
 /// content is returned for inspection.
 fn dialog(term: &mut Terminal, bg: Option<&dyn State>, content: DialogContent) -> Result<Option<DialogContent>> {
-    let state = Dialog { content, bg }.exec(term)?;
+    let state = Dialog { content, bg, preview: None, error: None }.exec(term)?;
     Ok(state.map(|d| d.content))
 }
 
@@ -90,6 +233,23 @@ enum DialogContent {
     Confirm(String),
     Form(input::Form),
     Notice(NoticeLevel, String),
+    /// Contains a message scrolled down by `offset` lines.
+    Scrollable { title: String, msg: String, offset: u16 },
+    /// Contains a generated password, displayed in groups of [`PASSWORD_GROUP_SIZE`] characters if
+    /// `grouped` is set, and masked with [`PASSWORD_MASK`] unless `revealed` is set.
+    Password { identifier: String, password: String, grouped: bool, revealed: bool },
+    /// Contains options to pick from, of which the one at index `selected` is selected.
+    Pick { title: String, options: Vec<String>, selected: usize },
+}
+
+/// Contains a preview line displayed below a form.
+struct Preview<'a> {
+    /// Generates the preview from the form contents.
+    generator: &'a dyn Fn(&Form) -> Option<String>,
+    /// Contains the most recently generated preview.
+    value: Option<String>,
+    /// Contains the time of the most recent form change not yet reflected in the preview.
+    changed: Option<Instant>,
 }
 
 struct Dialog<'a> {
@@ -97,6 +257,10 @@ struct Dialog<'a> {
     content: DialogContent,
     /// Drawn before the dialog, such that the dialog lays on top.
     bg: Option<&'a dyn State>,
+    /// Displayed below the content if it is a form.
+    preview: Option<Preview<'a>>,
+    /// Contains the validation error of the most recent submission of a form.
+    error: Option<String>,
 }
 
 impl<'a> State for Dialog<'a> {
@@ -112,29 +276,92 @@ impl<'a> State for Dialog<'a> {
             },
             DialogContent::Form(form) => match key {
                 KeyCode::Esc => Status::Cancelled,
-                KeyCode::Enter => Status::Done,
+                KeyCode::Enter if !form.multiline_selected() => {
+                    self.error = form.validate();
+
+                    match self.error {
+                        Some(_) => Status::Running,
+                        None => Status::Done,
+                    }
+                }
                 _ => {
-                    form.key_down(key, modifiers);
+                    if form.key_down(key, modifiers) {
+                        if let Some(preview) = &mut self.preview {
+                            preview.changed = Some(Instant::now());
+                        }
+                    }
                     Status::Running
                 }
             },
             DialogContent::Notice(..) => Status::Done,
+            DialogContent::Scrollable { msg, offset, .. } => {
+                let max_offset = msg.lines().count().saturating_sub(1) as u16;
+
+                match key {
+                    KeyCode::Up       => *offset = offset.saturating_sub(1),
+                    KeyCode::Down     => *offset = (*offset + 1).min(max_offset),
+                    KeyCode::PageUp   => *offset = offset.saturating_sub(10),
+                    KeyCode::PageDown => *offset = (*offset + 10).min(max_offset),
+                    KeyCode::Home     => *offset = 0,
+                    KeyCode::End      => *offset = max_offset,
+                    _ => return Ok(Status::Done),
+                }
+                Status::Running
+            }
+            DialogContent::Password { password, grouped, revealed, .. } => match key {
+                KeyCode::Char('g') => {
+                    *grouped = !*grouped;
+                    Status::Running
+                }
+                KeyCode::Char('r') => {
+                    *revealed = !*revealed;
+                    Status::Running
+                }
+                KeyCode::Char('c') => {
+                    clipboard::copy(password, false)?;
+                    Status::Done
+                }
+                _ => Status::Done,
+            },
+            DialogContent::Pick { options, selected, .. } => {
+                let wrap = config::get().wrap_navigation;
+
+                match key {
+                    KeyCode::Up    => *selected = utility::step_index(*selected, -1, options.len(), wrap),
+                    KeyCode::Down  => *selected = utility::step_index(*selected, 1, options.len(), wrap),
+                    KeyCode::Enter => return Ok(Status::Done),
+                    KeyCode::Esc   => return Ok(Status::Cancelled),
+                    _ => (),
+                }
+                Status::Running
+            }
         };
         Ok(status)
     }
 
+    fn tick(&mut self) -> Result<Status> {
+        if let (DialogContent::Form(form), Some(preview)) = (&self.content, &mut self.preview) {
+            if preview.changed.is_some_and(|changed| changed.elapsed() >= PREVIEW_DELAY) {
+                preview.value = (preview.generator)(form);
+                preview.changed = None;
+            }
+        }
+        Ok(Status::Running)
+    }
+
     fn draw(&self, frame: &mut Frame) {
         if let Some(bg) = &self.bg {
             bg.draw(frame);
         }
 
+        let theme = config::get().theme();
         let (title, style, hint) = match &self.content {
             DialogContent::Notice(level, _) => {
                 let (title, color) = match level {
-                    NoticeLevel::Info    => ("Info",        Color::Cyan),
-                    NoticeLevel::Warning => ("Warning",     Color::Yellow),
-                    NoticeLevel::Error   => ("Error",       Color::Red),
-                    NoticeLevel::Fatal   => ("Fatal Error", Color::Red),
+                    NoticeLevel::Info    => ("Info",        theme.info),
+                    NoticeLevel::Warning => ("Warning",     theme.warning),
+                    NoticeLevel::Error   => ("Error",       theme.error),
+                    NoticeLevel::Fatal   => ("Fatal Error", theme.error),
                 };
                 (
                     title,
@@ -145,27 +372,45 @@ impl<'a> State for Dialog<'a> {
             DialogContent::Form(form) => (
                 form.title(),
                 Style::default(),
-                "Press (enter) to submit, (esc) to cancel...",
+                if form.multiline_selected() {
+                    "Press (enter) for a new line, (↑/↓) past the first or last line to leave..."
+                } else {
+                    "Press (enter) to submit, (esc) to cancel..."
+                },
             ),
             DialogContent::Confirm(_) => (
                 "Confirm",
-                Style::default().fg(Color::Yellow),
+                Style::default().fg(theme.warning),
                 "Press (y) to confirm, (n) or (esc) to cancel...",
             ),
+            DialogContent::Scrollable { title, .. } => (
+                title.as_str(),
+                Style::default().fg(theme.info),
+                "Press (↑/↓) to scroll, any other key to close...",
+            ),
+            DialogContent::Password { .. } => (
+                "Password",
+                Style::default().fg(theme.info),
+                "Press (g) to toggle grouping, (r) to reveal or mask, (c) to copy, any other key to \
+                 close...",
+            ),
+            DialogContent::Pick { title, .. } => (
+                title.as_str(),
+                Style::default().fg(theme.info),
+                "Press (↑/↓) to select, (enter) to pick, (esc) to cancel...",
+            ),
         };
 
-        let width = (frame.size().width as f32 * 0.6) as u16;
-        let dialog_area = Layout::default()
-            .horizontal_margin((frame.size().width - width) / 2)
-            .constraints([
-                Constraint::Percentage(25),
-                Constraint::Percentage(50),
-                Constraint::Percentage(25),
-            ])
-            .split(frame.size())[1];
+        let dialog_area = dialog_area(frame.size());
+
+        if !fits(dialog_area, 1, hint) {
+            frame.render_widget(Clear, frame.size());
+            frame.render_widget(too_small(title, theme.style(style)), frame.size());
+            return;
+        }
         let block = Block::default()
             .borders(Borders::ALL)
-            .style(style)
+            .style(theme.style(style))
             .title(format!(" {} ", title.to_uppercase()))
             .border_type(BorderType::Thick);
         let client_area = block.inner(dialog_area);
@@ -173,7 +418,7 @@ impl<'a> State for Dialog<'a> {
         frame.render_widget(block, dialog_area);
 
         let hint_widget = WrappedString::new(hint, client_area.width)
-            .style(Style::default().add_modifier(Modifier::ITALIC));
+            .style(theme.style(Style::default().add_modifier(Modifier::ITALIC)));
 
         let (content_area, hint_area) = {
             let layout = Layout::default()
@@ -190,10 +435,230 @@ impl<'a> State for Dialog<'a> {
                 let msg_widget = WrappedString::new(&msg, content_area.width).center();
                 frame.render_widget(msg_widget, content_area);
             }
-            DialogContent::Form(form) => {
-                let widget = FormWidget(form).center();
+            DialogContent::Scrollable { msg, offset, .. } => {
+                let widget = Paragraph::new(msg.as_str()).scroll((*offset, 0));
                 frame.render_widget(widget, content_area);
             }
+            DialogContent::Password { identifier, password, grouped, revealed } => {
+                // masking keeps the length visible, which is also shown by the seed table
+                let masked;
+                let password = if *revealed {
+                    password
+                } else {
+                    masked = PASSWORD_MASK.to_string().repeat(password.chars().count());
+                    &masked
+                };
+                let password = if *grouped {
+                    group(password, PASSWORD_GROUP_SIZE)
+                } else {
+                    password.clone()
+                };
+                let msg = format!("Password for '{identifier}':\n\n{password}");
+                let msg_widget = WrappedString::new(&msg, content_area.width).center();
+                frame.render_widget(msg_widget, content_area);
+            }
+            DialogContent::Pick { options, selected, .. } => {
+                // the selected option is marked with text, such that it stands out without styles
+                let msg = options
+                    .iter()
+                    .enumerate()
+                    .map(|(i, option)| format!("{} {option}", if i == *selected { '>' } else { ' ' }))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                let msg_widget = WrappedString::new(&msg, content_area.width).center();
+                frame.render_widget(msg_widget, content_area);
+            }
+            DialogContent::Form(form) => {
+                // lines displayed below the form
+                let preview_str = self.preview.as_ref().map(|preview| {
+                    match (preview.changed, &preview.value) {
+                        (Some(_), _)        => "Preview: ...".to_owned(),
+                        (None, Some(value)) => format!("Preview: {value}"),
+                        (None, None)        => "Preview: -".to_owned(),
+                    }
+                });
+                let lines: Vec<Paragraph> = [
+                    preview_str.map(|preview| (preview, Style::default())),
+                    self.error.clone().map(|error| (error, Style::default().fg(theme.error))),
+                ]
+                .into_iter()
+                .flatten()
+                .map(|(line, style)| Paragraph::new(line).style(theme.style(style)))
+                .collect();
+
+                let layout = Layout::default()
+                    .constraints(
+                        iter::once(Constraint::Min(1))
+                            .chain(lines.iter().map(|_| Constraint::Length(1)))
+                            .collect::<Vec<_>>(),
+                    )
+                    .split(content_area);
+                frame.render_widget(FormWidget(form).center(), layout[0]);
+
+                for (line, area) in lines.into_iter().zip(&layout[1..]) {
+                    frame.render_widget(line, *area);
+                }
+            }
+        }
+    }
+}
+
+/// Displays the progress of a task performed on a worker thread. See [`progress`].
+struct Progress<'a, T> {
+    title: &'a str,
+    /// Number of results expected from the worker.
+    total: usize,
+    /// Contains the results received from the worker so far.
+    results: Vec<T>,
+    receiver: Receiver<T>,
+    /// Drawn before the dialog, such that the dialog lays on top.
+    bg: Option<&'a dyn State>,
+}
+
+impl<'a, T> State for Progress<'a, T> {
+    fn update(&mut self, _: &mut Terminal, key: KeyCode, _: KeyModifiers) -> Result<Status> {
+        match key {
+            KeyCode::Esc => Ok(Status::Cancelled),
+            _ => Ok(Status::Running),
+        }
+    }
+
+    fn tick(&mut self) -> Result<Status> {
+        self.results.extend(self.receiver.try_iter());
+
+        if self.results.len() == self.total {
+            Ok(Status::Done)
+        } else {
+            Ok(Status::Running)
+        }
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        if let Some(bg) = &self.bg {
+            bg.draw(frame);
+        }
+
+        let theme = config::get().theme();
+        let dialog_area = dialog_area(frame.size());
+
+        if !fits(dialog_area, 3, PROGRESS_HINT) {
+            frame.render_widget(Clear, frame.size());
+            frame.render_widget(too_small(self.title, Style::default()), frame.size());
+            return;
         }
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(format!(" {} ", self.title.to_uppercase()))
+            .border_type(BorderType::Thick);
+        let client_area = block.inner(dialog_area);
+        frame.render_widget(Clear, dialog_area);
+        frame.render_widget(block, dialog_area);
+
+        let layout = Layout::default()
+            .horizontal_margin(3)
+            .vertical_margin(1)
+            .constraints([Constraint::Min(1), Constraint::Length(1), Constraint::Min(1), Constraint::Length(1)])
+            .split(client_area);
+        let ratio = if self.total == 0 {
+            1.0
+        } else {
+            self.results.len() as f64 / self.total as f64
+        };
+        let gauge = Gauge::default()
+            .gauge_style(theme.style(Style::default().fg(theme.highlight)))
+            .ratio(ratio)
+            .label(format!("{}/{}", self.results.len(), self.total));
+        let hint = Paragraph::new(PROGRESS_HINT)
+            .style(theme.style(Style::default().add_modifier(Modifier::ITALIC)));
+        frame.render_widget(gauge, layout[1]);
+        frame.render_widget(hint, layout[3]);
+    }
+}
+
+/// Returns whether a dialog fits within its area, leaving at least `content_height` lines for its
+/// content besides its borders, margins, and the wrapped hint. Dialogs which don't are replaced by
+/// [`too_small`], since their areas may otherwise be empty and their contents invisible.
+fn fits(dialog_area: Rect, content_height: u16, hint: &str) -> bool {
+    // the borders take one line or column on each side, and the margins one line and three columns
+    let client_width = dialog_area.width.saturating_sub(2);
+    let client_height = dialog_area.height.saturating_sub(2);
+    let content_width = client_width.saturating_sub(6);
+
+    content_width >= MIN_CONTENT_WIDTH
+        && client_height >= 2 + content_height + WrappedString::new(hint, client_width).height()
+}
+
+/// Creates a message filling the terminal in place of a dialog which doesn't fit, naming the
+/// dialog such that the user knows what is waiting once the terminal is enlarged.
+fn too_small(title: &str, style: Style) -> Paragraph<'static> {
+    let msg = format!("{}\nEnlarge the terminal to show this dialog.", title.to_uppercase());
+    Paragraph::new(msg).style(style).wrap(Wrap { trim: true })
+}
+
+/// Separates a string into space-separated groups of `size` characters.
+fn group(string: &str, size: usize) -> String {
+    let chars: Vec<char> = string.chars().collect();
+
+    chars
+        .chunks(size)
+        .map(|chunk| chunk.iter().collect::<String>())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Calculates the area of a dialog centered within the given frame area. Since this is recalculated
+/// on every draw, the dialog stays centered when the terminal is resized.
+fn dialog_area(frame: Rect) -> Rect {
+    let width = (frame.width as f32 * 0.6) as u16;
+
+    Layout::default()
+        .horizontal_margin((frame.width - width) / 2)
+        .constraints([
+            Constraint::Percentage(25),
+            Constraint::Percentage(50),
+            Constraint::Percentage(25),
+        ])
+        .split(frame)[1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tui::{buffer::Buffer, widgets::Widget};
+
+    #[test]
+    fn dialog_area_centered() {
+        for (width, height) in [(80, 24), (81, 25), (120, 40), (200, 60)] {
+            let frame = Rect::new(0, 0, width, height);
+            let area = dialog_area(frame);
+            let left = area.x;
+            let right = width - area.right();
+            let top = area.y;
+            let bottom = height - area.bottom();
+
+            assert!(left.abs_diff(right) <= 1, "{width}x{height}: {area:?}");
+            assert!(top.abs_diff(bottom) <= 1, "{width}x{height}: {area:?}");
+        }
+    }
+
+    #[test]
+    fn tiny_terminal() {
+        let frame = Rect::new(0, 0, 10, 3);
+        assert!(!fits(dialog_area(frame), 1, "Press any key to close..."));
+
+        let mut buf = Buffer::empty(frame);
+        too_small("Confirm", Style::default()).render(frame, &mut buf);
+        let text: String = buf.content().iter().map(|cell| cell.symbol.as_str()).collect();
+        assert!(text.starts_with("CONFIRM"));
+
+        assert!(fits(dialog_area(Rect::new(0, 0, 80, 24)), 1, "Press any key to close..."));
+    }
+
+    #[test]
+    fn group_password() {
+        assert_eq!(group("abcdefghij", 4), "abcd efgh ij");
+        assert_eq!(group("abcd", 4), "abcd");
+        assert_eq!(group("åäö€", 2), "åä ö€");
+        assert_eq!(group("", 4), "");
     }
 }
@@ -1,21 +1,32 @@
 use crossterm::event::{KeyCode, KeyModifiers};
-use std::{collections::HashMap, iter, cell::RefCell};
+use std::{collections::HashMap, iter, cell::{Cell, RefCell}};
 use tui::{
     buffer::Buffer,
     layout::Rect,
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
     text::{Span, Spans},
     widgets::{List, ListItem, ListState, StatefulWidget, Widget},
 };
 
-use super::utility::Number;
+use super::utility::{self, Number};
+use crate::config;
+use vault::seed::Characters;
 
 pub trait Input {
     /// Returns whether value changed.
     fn key_down(&mut self, key: KeyCode, modifiers: KeyModifiers) -> bool;
     fn format(&self, selected: bool) -> Spans;
+
+    /// Formats the input across multiple rows. Single-line inputs need not implement this.
+    fn format_lines(&self, selected: bool) -> Vec<Spans> {
+        vec![self.format(selected)]
+    }
 }
 
+/// Displayed in empty hidden inputs while they are selected, since they would otherwise be
+/// indistinguishable from empty unselected ones.
+const HIDDEN_PLACEHOLDER: &str = "enter key…";
+
 /// Utility to handle string input.
 pub struct StringInput {
     /// Contains the entered text. Not defined as [`String`] to allow easier index-based operations.
@@ -38,7 +49,7 @@ impl StringInput {
 
     pub fn set_value(&mut self, value: String) {
         self.value = value.chars().collect();
-        self.caret = value.len();
+        self.caret = self.value.len();
     }
 
     /// Determines the index to jump to in specified direction e.g. when `CTRL + ←/→` is pressed.
@@ -157,7 +168,18 @@ impl Input for StringInput {
             self.value.iter().cloned().collect()
         };
         
-        let spans = if with_caret {
+        // the placeholder is only displayed, and never part of the value
+        let placeholder = if with_caret && self.hidden && self.value.is_empty() {
+            Some(HIDDEN_PLACEHOLDER)
+        } else {
+            None
+        };
+
+        let mut spans = if with_caret && config::get().theme().plain {
+            // without styles, the caret cannot be drawn over a character and is inserted instead
+            content.insert(self.caret, '|');
+            vec![Span::raw(String::from_iter(content))]
+        } else if with_caret {
             content.push(' ');
 
             let [pre, caret, post] = {
@@ -173,10 +195,257 @@ impl Input for StringInput {
         } else {
             vec![Span::raw(String::from_iter(content))]
         };
+        if let Some(placeholder) = placeholder {
+            spans.push(Span::styled(placeholder, Style::default().add_modifier(Modifier::DIM)));
+        }
         Spans::from(spans)
     }
 }
 
+/// Utility to handle multi-line string input. Lines are broken by line breaks in the value, and
+/// lines longer than the [width](TextArea::set_width) are wrapped at word boundaries. Wrapped lines
+/// are navigated and displayed as separate rows.
+#[derive(Default)]
+pub struct TextArea {
+    /// Handles all editing within a line, with line breaks contained in the value.
+    input: StringInput,
+    /// Specifies the number of characters per row, set when rendered. Zero disables wrapping.
+    width: Cell<usize>,
+}
+
+/// A row of a [TextArea], spanning `start..end` of its value.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+struct Row {
+    start: usize,
+    end: usize,
+    /// Whether the row was wrapped, i.e. continues on the next row without a line break.
+    wrapped: bool,
+}
+
+impl Row {
+    /// Returns the last position of the caret within the row. The end of a wrapped row is the
+    /// start of the next, where the caret is displayed instead.
+    fn caret_end(&self) -> usize {
+        if self.wrapped {
+            self.end - 1
+        } else {
+            self.end
+        }
+    }
+}
+
+impl TextArea {
+    pub fn value(&self) -> String {
+        self.input.value()
+    }
+
+    pub fn set_value(&mut self, value: String) {
+        self.input.set_value(value);
+    }
+
+    /// Sets the number of characters per row, wrapping longer lines. Zero disables wrapping.
+    pub fn set_width(&self, width: usize) {
+        self.width.set(width);
+    }
+
+    /// Returns the number of rows.
+    pub fn height(&self) -> usize {
+        self.rows().len()
+    }
+
+    /// Splits the value into rows at line breaks, and wraps lines longer than the width after the
+    /// last space within it. Words longer than the width are broken.
+    fn rows(&self) -> Vec<Row> {
+        let value = &self.input.value;
+        let width = self.width.get();
+        let mut rows = Vec::new();
+        let mut start = 0;
+
+        for end in value
+            .iter()
+            .enumerate()
+            .filter(|(_, &c)| c == '\n')
+            .map(|(i, _)| i)
+            .chain(iter::once(value.len()))
+        {
+            while width > 0 && end - start > width {
+                let limit = start + width;
+                let wrap = (start + 1..=limit)
+                    .rev()
+                    .find(|&i| value[i - 1] == ' ')
+                    .unwrap_or(limit);
+                rows.push(Row { start, end: wrap, wrapped: true });
+                start = wrap;
+            }
+            rows.push(Row { start, end, wrapped: false });
+            start = end + 1;
+        }
+        rows
+    }
+
+    /// Moves the caret to the same column of an adjacent row, or to its end if the row is
+    /// shorter. Returns whether there was a row to move to.
+    fn move_line(&mut self, delta: isize) -> bool {
+        let rows = self.rows();
+        let row = rows.iter().rposition(|row| row.start <= self.input.caret).unwrap();
+        let column = self.input.caret - rows[row].start;
+        let target = row as isize + delta;
+
+        if target < 0 || target as usize >= rows.len() {
+            return false;
+        }
+        let target = rows[target as usize];
+        self.input.caret = (target.start + column).min(target.caret_end());
+        true
+    }
+}
+
+impl Input for TextArea {
+    fn key_down(&mut self, key: KeyCode, modifiers: KeyModifiers) -> bool {
+        match key {
+            KeyCode::Up => {
+                self.move_line(-1);
+                false
+            }
+            KeyCode::Down => {
+                self.move_line(1);
+                false
+            }
+            KeyCode::Enter => self.input.key_down(KeyCode::Char('\n'), KeyModifiers::NONE),
+            _ => self.input.key_down(key, modifiers),
+        }
+    }
+
+    fn format(&self, with_caret: bool) -> Spans {
+        self.format_lines(with_caret).swap_remove(0)
+    }
+
+    fn format_lines(&self, with_caret: bool) -> Vec<Spans> {
+        let value = &self.input.value;
+        let caret = self.input.caret;
+        let rows = self.rows();
+        let caret_row = rows.iter().rposition(|row| row.start <= caret).unwrap();
+        let plain = config::get().theme().plain;
+
+        rows
+            .iter()
+            .enumerate()
+            .map(|(i, row)| {
+                let line = &value[row.start..row.end];
+
+                if !with_caret || i != caret_row {
+                    return Spans::from(String::from_iter(line));
+                }
+                // as in [StringInput], the caret is inserted if it cannot be drawn with styles
+                let (pre, post) = line.split_at(caret - row.start);
+
+                if plain {
+                    Spans::from(format!("{}|{}", String::from_iter(pre), String::from_iter(post)))
+                } else {
+                    let (caret, post) = match post.split_first() {
+                        Some((caret, post)) => (caret.to_string(), post),
+                        None => (" ".to_owned(), post),
+                    };
+                    Spans::from(vec![
+                        Span::raw(String::from_iter(pre)),
+                        Span::styled(caret, Style::default().add_modifier(Modifier::REVERSED)),
+                        Span::raw(String::from_iter(post)),
+                    ])
+                }
+            })
+            .collect()
+    }
+}
+
+/// Selects any combination of character sets, displayed as one checkbox per set across multiple
+/// rows. The arrow keys move between the checkboxes and space toggles the one under the cursor.
+pub struct SetSelector {
+    value: Characters,
+    /// Index of the checkbox under the cursor, in the order of [`Characters::descriptors`].
+    cursor: usize,
+}
+
+impl SetSelector {
+    pub fn new(value: Characters) -> Self {
+        SetSelector { value, cursor: 0 }
+    }
+
+    pub fn value(&self) -> Characters {
+        self.value
+    }
+
+    /// Returns the number of checkboxes, each displayed on its own row.
+    pub fn height(&self) -> usize {
+        Characters::descriptors().len()
+    }
+
+    /// Moves the cursor to an adjacent checkbox. Returns whether there was a checkbox to move to.
+    fn move_cursor(&mut self, delta: isize) -> bool {
+        let target = self.cursor as isize + delta;
+
+        if target < 0 || target as usize >= self.height() {
+            return false;
+        }
+        self.cursor = target as usize;
+        true
+    }
+}
+
+impl Input for SetSelector {
+    fn key_down(&mut self, key: KeyCode, _modifiers: KeyModifiers) -> bool {
+        match key {
+            KeyCode::Up | KeyCode::Left => {
+                self.move_cursor(-1);
+                false
+            }
+            KeyCode::Down | KeyCode::Right => {
+                self.move_cursor(1);
+                false
+            }
+            KeyCode::Char(' ') => {
+                let (flag, ..) = Characters::descriptors()[self.cursor];
+                self.value.toggle(flag);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn format(&self, selected: bool) -> Spans {
+        self.format_lines(selected).swap_remove(0)
+    }
+
+    fn format_lines(&self, selected: bool) -> Vec<Spans> {
+        let plain = config::get().theme().plain;
+
+        Characters::descriptors()
+            .iter()
+            .enumerate()
+            .map(|(i, &(flag, label, _))| {
+                let checkbox = if self.value.contains(flag) { "[x]" } else { "[ ]" };
+
+                // as in [StringInput], the cursor is marked with text if it cannot be styled
+                match (selected && i == self.cursor, plain) {
+                    (false, _) => Spans::from(format!("{checkbox} {label}")),
+                    (true, true) => Spans::from(format!("{checkbox} {label} <")),
+                    (true, false) => Spans::from(vec![
+                        Span::styled(checkbox, Style::default().add_modifier(Modifier::REVERSED)),
+                        Span::raw(format!(" {label}")),
+                    ]),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Specifies how the value of a [NumericalInput] is displayed.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum DisplayMode {
+    Decimal,
+    /// Upper case hexadecimal prefixed with `0x`. Compact for large values such as salts.
+    Hex,
+}
+
 /// Utility to handle integral input.
 pub struct NumericalInput<T: Number> {
     default: T,
@@ -184,6 +453,7 @@ pub struct NumericalInput<T: Number> {
     min: T,
     max: T,
     step: T,
+    display: DisplayMode,
 }
 
 impl<T: Number> NumericalInput<T> {
@@ -196,9 +466,15 @@ impl<T: Number> NumericalInput<T> {
             min,
             max,
             step,
+            display: DisplayMode::Decimal,
         }
     }
 
+    pub fn display(mut self, display: DisplayMode) -> Self {
+        self.display = display;
+        self
+    }
+
     pub fn set_value(&mut self, value: T) {
         debug_assert!(value >= self.min && value <= self.max);
         self.value = value;
@@ -249,13 +525,19 @@ impl<T: Number> Input for NumericalInput<T> {
     }
 
     fn format(&self, _selected: bool) -> Spans {
-        Spans::from(vec![Span::from(format!("<{}>", self.value))])
+        let value = match self.display {
+            DisplayMode::Decimal => self.value.to_string(),
+            DisplayMode::Hex => format!("0x{:X}", self.value),
+        };
+        Spans::from(vec![Span::from(format!("<{value}>"))])
     }
 }
 
 enum InputType {
     String(StringInput),
+    Text(TextArea),
     Integer(NumericalInput<u64>),
+    Sets(SetSelector),
 }
 
 pub struct Field {
@@ -269,26 +551,44 @@ impl Field {
         &self.title
     }
 
-    fn format_input(&self, as_selected: bool) -> Spans {
+    fn format_input(&self, as_selected: bool) -> Vec<Spans> {
         match &self.input_type {
-            InputType::String(input) => input.format(as_selected),
-            InputType::Integer(input) => input.format(as_selected),
+            InputType::String(input) => input.format_lines(as_selected),
+            InputType::Text(input) => input.format_lines(as_selected),
+            InputType::Integer(input) => input.format_lines(as_selected),
+            InputType::Sets(input) => input.format_lines(as_selected),
         }
     }
 
-    fn key_down(&mut self, key: KeyCode, modifiers: KeyModifiers) {
+    /// Returns the number of rows the field is displayed across.
+    pub fn height(&self) -> usize {
+        match &self.input_type {
+            InputType::Text(input) => input.height(),
+            InputType::Sets(input) => input.height(),
+            _ => 1,
+        }
+    }
+
+    /// Returns whether value changed.
+    fn key_down(&mut self, key: KeyCode, modifiers: KeyModifiers) -> bool {
         match &mut self.input_type {
             InputType::String(input) => input.key_down(key, modifiers),
+            InputType::Text(input) => input.key_down(key, modifiers),
             InputType::Integer(input) => input.key_down(key, modifiers),
-        };
+            InputType::Sets(input) => input.key_down(key, modifiers),
+        }
     }
 }
 
+/// Validates the contents of a [Form], returning an error message if they are invalid.
+pub type Validator = Box<dyn Fn(&Form) -> Option<String>>;
+
 pub struct Form {
     title: String,
     fields: Vec<Field>,
     list_state: RefCell<ListState>,
     fields_lut: HashMap<&'static str, usize>,
+    validator: Option<Validator>,
 }
 
 impl Form {
@@ -298,6 +598,7 @@ impl Form {
             fields: Vec::new(),
             list_state: RefCell::default(),
             fields_lut: HashMap::new(),
+            validator: None,
         }
     }
 
@@ -305,23 +606,74 @@ impl Form {
         &self.title
     }
 
-    pub fn key_down(&mut self, key: KeyCode, modifiers: KeyModifiers) {
+    /// Sets a validator which is run when the form is submitted. Forms with invalid contents are
+    /// not submitted, and the error is shown to the user instead.
+    pub fn validator<F>(mut self, validator: F) -> Self
+    where
+        F: Fn(&Form) -> Option<String> + 'static,
+    {
+        self.validator = Some(Box::new(validator));
+        self
+    }
+
+    /// Returns an error message if the contents of the form are invalid.
+    pub fn validate(&self) -> Option<String> {
+        self.validator.as_ref().and_then(|validator| validator(self))
+    }
+
+    /// Returns whether the value of any field changed.
+    pub fn key_down(&mut self, key: KeyCode, modifiers: KeyModifiers) -> bool {
+        let selected = self.list_state.borrow().selected();
+
         match key {
-            KeyCode::Up => self.move_selected(-1),
-            KeyCode::Down => self.move_selected(1),
-            _ => {
-                if let Some(selected) = self.list_state.borrow().selected() {
-                    self.fields[selected].key_down(key, modifiers)
+            KeyCode::Up | KeyCode::Down => {
+                let delta = if key == KeyCode::Up { -1 } else { 1 };
+
+                // multi-line fields move between their lines before moving to the adjacent field
+                let moved_line = match selected.map(|i| &mut self.fields[i].input_type) {
+                    Some(InputType::Text(input)) => input.move_line(delta),
+                    Some(InputType::Sets(input)) => input.move_cursor(delta),
+                    _ => false,
+                };
+                if !moved_line {
+                    self.move_selected(delta);
                 }
+                false
             }
+            _ => match selected {
+                Some(selected) => self.fields[selected].key_down(key, modifiers),
+                None => false,
+            },
         }
     }
 
-    pub fn textbox<S: Into<String>>(self, key: &'static str, title: S) -> Self {
+    /// Returns whether the selected field is multi-line, in which case it consumes Enter to insert
+    /// line breaks rather than the form being submitted.
+    pub fn multiline_selected(&self) -> bool {
+        let selected = self.list_state.borrow().selected();
+        matches!(selected.map(|i| &self.fields[i].input_type), Some(InputType::Text(_)))
+    }
+
+    pub fn textbox<S: Into<String>>(self, key: &'static str, title: S, value: String) -> Self {
+        let mut input = StringInput::default();
+        input.set_value(value);
+
+        self.add(Field {
+            key,
+            title: title.into(),
+            input_type: InputType::String(input),
+        })
+    }
+
+    /// Adds a multi-line textbox. See [TextArea].
+    pub fn textarea<S: Into<String>>(self, key: &'static str, title: S, value: String) -> Self {
+        let mut input = TextArea::default();
+        input.set_value(value);
+
         self.add(Field {
             key,
             title: title.into(),
-            input_type: InputType::String(StringInput::default()),
+            input_type: InputType::Text(input),
         })
     }
 
@@ -341,10 +693,30 @@ impl Form {
         })
     }
 
+    /// Adds a slider displaying its value in hexadecimal. See [`DisplayMode::Hex`].
+    pub fn hex_slider<S: Into<String>>(self, key: &'static str, title: S, default: u64, min: u64, max: u64, step: u64) -> Self {
+        let input = NumericalInput::new(default, min, max, step).display(DisplayMode::Hex);
+
+        self.add(Field {
+            key,
+            title: title.into(),
+            input_type: InputType::Integer(input),
+        })
+    }
+
     pub fn checkbox<S: Into<String>>(self, key: &'static str, title: S, default: bool) -> Self {
         self.slider(key, title, default as u64, 0, 1, 1)
     }
 
+    /// Adds a checkbox per character set. See [SetSelector].
+    pub fn set_selector<S: Into<String>>(self, key: &'static str, title: S, value: Characters) -> Self {
+        self.add(Field {
+            key,
+            title: title.into(),
+            input_type: InputType::Sets(SetSelector::new(value)),
+        })
+    }
+
     pub fn fields(&self) -> &[Field] {
         &self.fields
     }
@@ -367,12 +739,26 @@ impl Form {
         self.get_integer(key).map(|value| value != 0)
     }
 
+    pub fn get_characters(&self, key: &str) -> Option<Characters> {
+        match self.get_field(key) {
+            Some(Field {
+                input_type: InputType::Sets(input),
+                ..
+            }) => Some(input.value()),
+            _ => None,
+        }
+    }
+
     pub fn get_string(&self, key: &str) -> Option<String> {
         match self.get_field(key) {
             Some(Field {
                 input_type: InputType::String(input),
                 ..
             }) => Some(input.value()),
+            Some(Field {
+                input_type: InputType::Text(input),
+                ..
+            }) => Some(input.value()),
             _ => None,
         }
     }
@@ -394,7 +780,8 @@ impl Form {
         let mut list_state = self.list_state.borrow_mut();
         
         if let Some(prev) = list_state.selected() {
-            let new = (prev as isize + delta).clamp(0, (self.fields.len() - 1) as isize) as usize;
+            let wrap = config::get().wrap_navigation;
+            let new = utility::step_index(prev, delta, self.fields.len(), wrap);
             list_state.select(Some(new));
         }
     }
@@ -405,12 +792,23 @@ pub struct FormWidget<'a>(pub &'a Form);
 impl<'a> Widget for FormWidget<'a> {
     fn render(self, area: Rect, buf: &mut Buffer) {
         let form = self.0;
+        let theme = config::get().theme();
         let max_title_len = form
             .fields
             .iter()
             .map(|field| field.title().len())
             .max()
             .unwrap_or(0);
+
+        // multi-line fields wrap within the space right of the titles, leaving room for the caret
+        let value_width = (area.width as usize)
+            .saturating_sub(theme.marker().chars().count() + max_title_len + 2 + 1)
+            .max(1);
+        for field in &form.fields {
+            if let InputType::Text(input) = &field.input_type {
+                input.set_width(value_width);
+            }
+        }
         let list_widget = List::new(
             form.fields
                 .iter()
@@ -426,19 +824,118 @@ impl<'a> Widget for FormWidget<'a> {
                     let padding_str: String = iter::repeat(' ')
                         .take(max_title_len - title.len())
                         .collect();
-                    let input_spans = field.format_input(is_selected).0;
-                    let mut spans = Vec::with_capacity(input_spans.len() + 1);
-                    spans.extend([Span::raw(format!("{padding_str}{title}: "))]);
-                    spans.extend(input_spans.into_iter());
-                    ListItem::new(Spans::from(spans))
+                    // continuation lines of multi-line fields are indented to align with the first
+                    let lines = field
+                        .format_input(is_selected)
+                        .into_iter()
+                        .enumerate()
+                        .map(|(i, line)| {
+                            let prefix = if i == 0 {
+                                format!("{padding_str}{title}: ")
+                            } else {
+                                " ".repeat(max_title_len + 2)
+                            };
+                            let mut spans = Vec::with_capacity(line.0.len() + 1);
+                            spans.push(Span::raw(prefix));
+                            spans.extend(line.0);
+                            Spans::from(spans)
+                        })
+                        .collect::<Vec<Spans>>();
+                    ListItem::new(lines)
                 })
                 .collect::<Vec<ListItem>>(),
         )
-        .highlight_style(
+        .highlight_style(theme.style(
             Style::default()
-                .fg(Color::Cyan)
+                .fg(theme.highlight)
                 .add_modifier(Modifier::BOLD),
-        );
+        ))
+        .highlight_symbol(theme.marker());
         StatefulWidget::render(list_widget, area, buf, &mut form.list_state.borrow_mut());
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn textarea_move_line() {
+        let mut input = TextArea::default();
+        input.set_value("abc\nd\nefgh".to_owned());
+
+        assert!(input.move_line(-1));
+        assert_eq!(input.input.caret, 5);
+        assert!(input.move_line(-1));
+        assert_eq!(input.input.caret, 1);
+        assert!(!input.move_line(-1));
+        assert!(input.move_line(2));
+        assert_eq!(input.input.caret, 7);
+
+        assert!(input.key_down(KeyCode::Enter, KeyModifiers::NONE));
+        assert_eq!(input.value(), "abc\nd\ne\nfgh");
+        assert_eq!(input.height(), 4);
+    }
+
+    #[test]
+    fn textarea_wrap() {
+        let mut input = TextArea::default();
+        input.set_value("aaa bbb cccccc\nd".to_owned());
+        assert_eq!(input.height(), 2);
+
+        input.set_width(5);
+        let rows: Vec<String> = input
+            .format_lines(false)
+            .iter()
+            .map(|row| row.0[0].content.to_string())
+            .collect();
+        assert_eq!(rows, ["aaa ", "bbb ", "ccccc", "c", "d"]);
+
+        // the caret moves between wrapped rows, and is displayed at the start of the next row
+        // rather than the end of a wrapped one
+        input.input.caret = 1;
+        assert!(input.move_line(1));
+        assert_eq!(input.input.caret, 5);
+        assert!(input.move_line(1));
+        assert_eq!(input.input.caret, 9);
+        assert!(input.move_line(1));
+        assert_eq!(input.input.caret, 14);
+        assert!(input.move_line(1));
+        assert_eq!(input.input.caret, 16);
+        assert!(!input.move_line(1));
+    }
+
+    #[test]
+    fn hidden_placeholder() {
+        let mut input = StringInput::default().hide(true);
+        let text = |input: &StringInput, selected| -> String {
+            input.format(selected).0.iter().map(|span| span.content.as_ref()).collect()
+        };
+
+        assert!(text(&input, true).contains(HIDDEN_PLACEHOLDER));
+        assert!(!text(&input, false).contains(HIDDEN_PLACEHOLDER));
+
+        input.key_down(KeyCode::Char('a'), KeyModifiers::NONE);
+        assert!(!text(&input, true).contains(HIDDEN_PLACEHOLDER));
+        assert_eq!(input.value(), "a");
+    }
+
+    #[test]
+    fn set_selector_toggle() {
+        let form = Form::new("").set_selector("sets", "Sets", Characters::UPPER_CASE);
+        let mut form = form.textbox("other", "Other", String::new());
+
+        assert!(form.key_down(KeyCode::Char(' '), KeyModifiers::NONE));
+        form.key_down(KeyCode::Down, KeyModifiers::NONE);
+        assert!(form.key_down(KeyCode::Char(' '), KeyModifiers::NONE));
+        assert_eq!(form.get_characters("sets"), Some(Characters::LOWER_CASE));
+
+        // leaving the last checkbox moves to the next field
+        for _ in 0..4 {
+            form.key_down(KeyCode::Down, KeyModifiers::NONE);
+        }
+        assert!(form.key_down(KeyCode::Char('a'), KeyModifiers::NONE));
+        assert_eq!(form.get_string("other").as_deref(), Some("a"));
+        assert_eq!(form.get_characters("sets"), Some(Characters::LOWER_CASE));
+    }
+}
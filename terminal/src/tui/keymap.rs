@@ -0,0 +1,293 @@
+//! Maps key combinations to the actions of the vault view. The default bindings may be overridden
+//! per action in the configuration, e.g. `"keymap": { "move_up": ["up", "k"] }`.
+
+use super::utility;
+use anyhow::{anyhow, bail, Error, Result};
+use crossterm::event::{KeyCode, KeyModifiers};
+use serde::Deserialize;
+use std::{collections::HashMap, fmt, str::FromStr};
+
+/// Defines all actions which may be bound to keys.
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    MoveUp,
+    MoveDown,
+    MoveSeedUp,
+    MoveSeedDown,
+    Generate,
+    GenerateAll,
+    GenerateGroup,
+    PickVariant,
+    Add,
+    Edit,
+    Rename,
+    Duplicate,
+    Remove,
+    Import,
+    RestoreBackup,
+    Sort,
+    Settings,
+    SaveAs,
+    OpenUrl,
+    Stats,
+    Audit,
+    Help,
+    Filter,
+    Yank,
+    Quit,
+}
+
+impl Action {
+    /// Returns the keys bound to the action unless overridden.
+    fn default_keys(self) -> &'static [&'static str] {
+        match self {
+            Action::MoveUp        => &["up"],
+            Action::MoveDown      => &["down"],
+            Action::MoveSeedUp    => &["alt+up"],
+            Action::MoveSeedDown  => &["alt+down"],
+            Action::Generate      => &["enter"],
+            Action::GenerateAll   => &["ctrl+g"],
+            Action::GenerateGroup => &["ctrl+u"],
+            Action::PickVariant   => &["ctrl+p"],
+            Action::Add           => &["ctrl+a"],
+            Action::Edit          => &["ctrl+e"],
+            Action::Rename        => &["ctrl+n"],
+            Action::Duplicate     => &["ctrl+d"],
+            Action::Remove        => &["ctrl+r"],
+            Action::Import        => &["ctrl+l"],
+            Action::RestoreBackup => &["ctrl+b"],
+            Action::Sort          => &["ctrl+s"],
+            Action::Settings      => &["ctrl+v"],
+            Action::SaveAs        => &["ctrl+x"],
+            Action::OpenUrl       => &["ctrl+o"],
+            Action::Stats         => &["ctrl+t"],
+            Action::Audit         => &["ctrl+w"],
+            Action::Help          => &["ctrl+h"],
+            Action::Filter        => &["ctrl+f"],
+            Action::Yank          => &["ctrl+y"],
+            Action::Quit          => &["ctrl+q"],
+        }
+    }
+
+    const ALL: [Action; 25] = [
+        Action::MoveUp,
+        Action::MoveDown,
+        Action::MoveSeedUp,
+        Action::MoveSeedDown,
+        Action::Generate,
+        Action::GenerateAll,
+        Action::GenerateGroup,
+        Action::PickVariant,
+        Action::Add,
+        Action::Edit,
+        Action::Rename,
+        Action::Duplicate,
+        Action::Remove,
+        Action::Import,
+        Action::RestoreBackup,
+        Action::Sort,
+        Action::Settings,
+        Action::SaveAs,
+        Action::OpenUrl,
+        Action::Stats,
+        Action::Audit,
+        Action::Help,
+        Action::Filter,
+        Action::Yank,
+        Action::Quit,
+    ];
+}
+
+/// A key combination, written as modifiers and a key joined by `+`, e.g. `ctrl+a` or `alt+up`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Key {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl Key {
+    /// Creates a key from a key event. The shift modifier of characters is folded into the
+    /// character itself, since terminals report shifted characters with or without it. Thereby
+    /// `shift+k` and `K` are the same key.
+    pub fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        let code = match code {
+            KeyCode::Char(c) if modifiers.contains(KeyModifiers::SHIFT) => {
+                KeyCode::Char(shifted(c))
+            }
+            code => code,
+        };
+        let modifiers = match code {
+            KeyCode::Char(_) => modifiers - KeyModifiers::SHIFT,
+            _ => modifiers,
+        };
+        Key { code, modifiers }
+    }
+}
+
+/// Returns the upper case of a character if it has a single one, and otherwise the character.
+fn shifted(c: char) -> char {
+    let mut upper = c.to_uppercase();
+
+    match (upper.next(), upper.next()) {
+        (Some(upper), None) => upper,
+        _ => c,
+    }
+}
+
+impl FromStr for Key {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut parts: Vec<&str> = s.split('+').map(str::trim).collect();
+        let code = parts.pop().unwrap_or_default();
+        let mut modifiers = KeyModifiers::NONE;
+
+        for modifier in parts {
+            modifiers |= match modifier.to_lowercase().as_str() {
+                "ctrl"  => KeyModifiers::CONTROL,
+                "alt"   => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => bail!("Invalid modifier '{modifier}' in key '{s}'."),
+            };
+        }
+
+        let mut chars = code.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match code.to_lowercase().as_str() {
+                "space"     => KeyCode::Char(' '),
+                "up"        => KeyCode::Up,
+                "down"      => KeyCode::Down,
+                "left"      => KeyCode::Left,
+                "right"     => KeyCode::Right,
+                "enter"     => KeyCode::Enter,
+                "esc"       => KeyCode::Esc,
+                "tab"       => KeyCode::Tab,
+                "backspace" => KeyCode::Backspace,
+                "delete"    => KeyCode::Delete,
+                "home"      => KeyCode::Home,
+                "end"       => KeyCode::End,
+                "pageup"    => KeyCode::PageUp,
+                "pagedown"  => KeyCode::PageDown,
+                f => f
+                    .strip_prefix('f')
+                    .and_then(|n| n.parse().ok())
+                    .map(KeyCode::F)
+                    .ok_or_else(|| anyhow!("Invalid key '{s}'."))?,
+            },
+        };
+        Ok(Key::new(code, modifiers))
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", utility::format_key(self.code, self.modifiers))
+    }
+}
+
+/// Contains the keys bound to each [Action].
+///
+/// Deserialized from a map of actions to lists of keys, replacing the default keys of each listed
+/// action. Fails if a key is bound to more than one action.
+#[derive(Deserialize, Debug)]
+#[serde(try_from = "HashMap<Action, Vec<String>>")]
+pub struct Keymap(HashMap<Action, Vec<Key>>);
+
+impl Keymap {
+    /// Returns the action bound to a key event, if any.
+    pub fn action(&self, code: KeyCode, modifiers: KeyModifiers) -> Option<Action> {
+        let key = Key::new(code, modifiers);
+
+        self.0
+            .iter()
+            .find(|(_, keys)| keys.contains(&key))
+            .map(|(&action, _)| action)
+    }
+
+    /// Returns the keys bound to an action.
+    pub fn keys(&self, action: Action) -> &[Key] {
+        self.0.get(&action).map_or(&[], Vec::as_slice)
+    }
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Keymap::try_from(HashMap::new()).unwrap()
+    }
+}
+
+impl TryFrom<HashMap<Action, Vec<String>>> for Keymap {
+    type Error = Error;
+
+    fn try_from(overrides: HashMap<Action, Vec<String>>) -> Result<Self> {
+        let mut bindings = HashMap::new();
+        let mut bound: HashMap<Key, Action> = HashMap::new();
+
+        for action in Action::ALL {
+            let keys = match overrides.get(&action) {
+                Some(keys) => keys.iter().map(|key| key.parse()).collect::<Result<Vec<Key>>>()?,
+                None => action.default_keys().iter().map(|key| key.parse().unwrap()).collect(),
+            };
+
+            for &key in &keys {
+                if let Some(other) = bound.insert(key, action) {
+                    bail!("Key '{key}' is bound to both {other:?} and {action:?}.");
+                }
+            }
+            bindings.insert(action, keys);
+        }
+        Ok(Keymap(bindings))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_from_str() {
+        let data = [
+            ("k",        KeyCode::Char('k'), KeyModifiers::NONE),
+            ("K",        KeyCode::Char('K'), KeyModifiers::NONE),
+            ("shift+k",  KeyCode::Char('K'), KeyModifiers::NONE),
+            ("ctrl+a",   KeyCode::Char('a'), KeyModifiers::CONTROL),
+            ("alt + up", KeyCode::Up,        KeyModifiers::ALT),
+            ("Enter",    KeyCode::Enter,     KeyModifiers::NONE),
+            ("f5",       KeyCode::F(5),      KeyModifiers::NONE),
+        ];
+
+        for (str, code, modifiers) in data {
+            assert_eq!(str.parse::<Key>().unwrap(), Key::new(code, modifiers));
+        }
+        assert!("hyper+a".parse::<Key>().is_err());
+        assert!("nope".parse::<Key>().is_err());
+    }
+
+    #[test]
+    fn keymap_overrides() {
+        let keymap: Keymap = serde_json::from_str(r#"{ "move_up": ["up", "k"] }"#).unwrap();
+
+        assert_eq!(keymap.action(KeyCode::Char('k'), KeyModifiers::NONE), Some(Action::MoveUp));
+        assert_eq!(keymap.action(KeyCode::Down, KeyModifiers::NONE), Some(Action::MoveDown));
+        assert_eq!(keymap.action(KeyCode::Char('k'), KeyModifiers::CONTROL), None);
+    }
+
+    #[test]
+    fn keymap_shift() {
+        let keymap: Keymap = serde_json::from_str(r#"{ "move_up": ["shift+k"] }"#).unwrap();
+
+        // terminals report shifted characters with or without the shift modifier
+        assert_eq!(keymap.action(KeyCode::Char('K'), KeyModifiers::NONE), Some(Action::MoveUp));
+        assert_eq!(keymap.action(KeyCode::Char('K'), KeyModifiers::SHIFT), Some(Action::MoveUp));
+        assert_eq!(keymap.action(KeyCode::Char('k'), KeyModifiers::SHIFT), Some(Action::MoveUp));
+        assert_eq!(keymap.action(KeyCode::Char('k'), KeyModifiers::NONE), None);
+    }
+
+    #[test]
+    fn keymap_conflict() {
+        let result = serde_json::from_str::<Keymap>(r#"{ "quit": ["ctrl+a"] }"#);
+        assert!(result.is_err());
+    }
+}
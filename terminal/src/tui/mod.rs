@@ -1,24 +1,38 @@
 mod dialog;
 mod input;
+pub mod keymap;
+mod seed_editor;
 mod state;
+pub mod theme;
 mod utility;
 mod vault_view;
 
 mod form; // TMP
 
-use self::state::ExitSignal;
-use crate::shared;
-use anyhow::Result;
+use self::{input::Form, state::ExitSignal};
+use crate::{config, shared};
+use anyhow::{Context, Result};
 use crossterm::terminal::{self, EnterAlternateScreen, LeaveAlternateScreen};
-use std::io;
+use std::{env, io, path::Path, process::Command};
 use tui::backend::CrosstermBackend;
-use vault::Vault;
+use vault::{generate::Argon2Variant, Vault};
+use zeroize::Zeroizing;
 
 type Backend = tui::backend::CrosstermBackend<io::Stdout>;
 type Terminal = tui::Terminal<Backend>;
 type Frame<'a> = tui::Frame<'a, Backend>;
 
+/// Launches the interface on the vault of the selected profile.
 pub fn launch() -> Result<()> {
+    run(ui)
+}
+
+/// Launches the interface on a [scratch](Vault::scratch) vault, kept in memory only until saved.
+pub fn launch_scratch() -> Result<()> {
+    run(scratch_ui)
+}
+
+fn run(ui: fn(&mut Terminal) -> Result<()>) -> Result<()> {
     // setup terminal environment
     let mut stdout = io::stdout();
     terminal::enable_raw_mode()?;
@@ -47,8 +61,73 @@ pub fn launch() -> Result<()> {
 }
 
 fn ui(term: &mut Terminal) -> Result<()> {
-    let vault = Vault::load(&shared::vault_folder(), "😍".to_owned())?;
+    let vault = load_vault(term, "😍")?;
     vault_view::vault_view(term, vault, None)?;
 
     Ok(())
 }
+
+fn scratch_ui(term: &mut Terminal) -> Result<()> {
+    // the variant cannot be changed once passwords are in use, so it is chosen on creation
+    let form = Form::new("Scratch vault")
+        .password("key", "Key")
+        .checkbox("argon2id", "Hash passwords with argon2id (recommended)", true);
+
+    if let Some(form) = dialog::form(term, None, form)? {
+        let key = Zeroizing::new(form.get_string("key").unwrap());
+        let variant = if form.get_bool("argon2id").unwrap() {
+            Argon2Variant::Argon2id
+        } else {
+            Argon2Variant::Argon2d
+        };
+        let mut vault = Vault::scratch("scratch".to_owned(), &key, variant)?;
+        config::get().configure(&mut vault);
+        vault_view::vault_view(term, vault, Some(key))?;
+    }
+    Ok(())
+}
+
+/// Loads a vault. If its file is corrupted, the user is offered to fix it in their editor, after
+/// which loading is retried.
+fn load_vault(term: &mut Terminal, identifier: &str) -> Result<Vault> {
+    loop {
+        match Vault::load(&shared::vault_folder(), identifier.to_owned()) {
+            Err(vault::Error::JSON(e, path, offset)) => {
+                let editor = env::var("VISUAL").or_else(|_| env::var("EDITOR")).unwrap_or_default();
+                let msg = format!(
+                    "Vault file {} is corrupted at byte {offset}: {e}\n\nOpen it in '{editor}' to \
+                     fix it?",
+                    path.display()
+                );
+
+                // without an editor, the user may only fix the file after exiting
+                if editor.trim().is_empty() || !dialog::confirm(term, None, msg)? {
+                    return Err(vault::Error::JSON(e, path, offset).into());
+                }
+                edit_externally(term, &editor, &path)?;
+            }
+            result => {
+                let mut vault = result?;
+                config::get().configure(&mut vault);
+                return Ok(vault);
+            }
+        }
+    }
+}
+
+/// Opens a file in an external editor, suspending the interface until the editor exits. The editor
+/// command may include arguments, e.g. `code --wait`.
+fn edit_externally(term: &mut Terminal, editor: &str, path: &Path) -> Result<()> {
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().unwrap_or_default();
+
+    terminal::disable_raw_mode()?;
+    crossterm::execute!(term.backend_mut(), LeaveAlternateScreen)?;
+    let status = Command::new(program).args(parts).arg(path).status();
+    terminal::enable_raw_mode()?;
+    crossterm::execute!(term.backend_mut(), EnterAlternateScreen)?;
+    term.clear()?;
+
+    status.with_context(|| format!("Could not launch editor '{editor}'"))?;
+    Ok(())
+}
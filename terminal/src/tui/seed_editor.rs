@@ -0,0 +1,217 @@
+use super::{
+    dialog,
+    input::Form,
+    state::State,
+    Terminal,
+};
+use anyhow::{anyhow, bail, Result};
+use zeroize::Zeroizing;
+use vault::{
+    generate,
+    seed::{Encoding, Seed, SeedDefaults, SeedKind},
+};
+
+/// Generates the password previewed by the [seed editor](seed_editor).
+pub type Preview<'a> = dyn Fn(&Seed) -> vault::Result<Zeroizing<String>> + 'a;
+
+/// Displays a form to edit a [Seed] and returns the edited seed once submitted, or [None] if the
+/// user cancels.
+///
+/// The estimated [strength](generate::entropy) of the in-progress seed is shown below the form and
+/// updated as the user edits it. If a `preview` generator is specified, a password generated from
+/// the seed is shown alongside. Nothing is shown while the seed cannot produce a password.
+pub fn seed_editor(
+    term: &mut Terminal,
+    bg: Option<&dyn State>,
+    title: &str,
+    seed: &Seed,
+    preview: Option<&Preview>,
+) -> Result<Option<Seed>> {
+    let form = seed_form(title, seed);
+    let generator = |form: &Form| {
+        let seed = parse_seed(form, seed).ok()?;
+        let strength = format!("~{:.0} bits of entropy", generate::entropy(&seed));
+
+        match preview {
+            Some(preview) => preview(&seed).ok().map(|password| format!("{}  ({strength})", password.as_str())),
+            None => Some(strength),
+        }
+    };
+    let submitted = dialog::form_with_preview(term, bg, form, &generator)?;
+
+    // the form has already been validated on submission
+    submitted.map(|form| parse_seed(&form, seed)).transpose()
+}
+
+/// Creates a form with fields pre-filled from the given [Seed]. The form is validated by
+/// [`parse_seed`] on submission.
+fn seed_form(title: &str, seed: &Seed) -> Form {
+    let base = seed.clone();
+    let site_len = seed.max_site_length.unwrap_or(0) as u64;
+    let (words, separator, hybrid) = match &seed.kind {
+        SeedKind::Password => (0, "-".to_owned(), false),
+        SeedKind::Passphrase { words, separator, hybrid } => {
+            (*words as u64, separator.clone(), *hybrid)
+        }
+    };
+    Form::new(title)
+        .validator(move |form| parse_seed(form, &base).err().map(|e| e.to_string()))
+        .textbox("id", "Identifier", seed.identifier.clone())
+        .textbox("name", "Username", seed.username.clone().unwrap_or_default())
+        .textbox("url", "URL", seed.url.clone().unwrap_or_default())
+        .textarea("notes", "Notes", seed.notes.clone().unwrap_or_default())
+        .textbox("group", "Group", seed.group.clone().unwrap_or_default())
+        .slider("min_len", "Min length", seed.min_len as u64, 1, u8::MAX as u64, 1)
+        .slider("max_len", "Max length", seed.max_len as u64, 1, u8::MAX as u64, 1)
+        .slider("site_len", "Max site length (0 if unknown)", site_len, 0, u8::MAX as u64, 1)
+        .hex_slider("salt", "Salt", seed.salt, 0, u64::MAX, 1)
+        .textbox("encoding", encoding_title(), seed.encoding.name().to_owned())
+        .set_selector("sets", "Character sets", seed.characters)
+        .textbox("custom", "Custom set (optional)", seed.custom_set.clone().unwrap_or_default())
+        .checkbox("no_space", "Rare without space", seed.exclude_space)
+        .textbox("exclude", "Excluded characters", seed.exclude.clone())
+        .slider("words", "Passphrase words (0 for a password)", words, 0, MAX_WORDS, 1)
+        .textbox("separator", "Passphrase separator", separator)
+        .checkbox("hybrid", "Passphrase uses character sets", hybrid)
+}
+
+/// Maximum number of words of passphrases offered by the [seed editor](seed_editor).
+const MAX_WORDS: u64 = 20;
+
+/// Returns the title of the encoding field, listing the names of all encodings.
+fn encoding_title() -> String {
+    let names: Vec<&str> = Encoding::ALL.iter().map(|encoding| encoding.name()).collect();
+    format!("Encoding ({})", names.join("/"))
+}
+
+/// Parses and validates the contents of a form created by [`seed_form`]. Parameters without a
+/// corresponding field are copied from `base`.
+fn parse_seed(form: &Form, base: &Seed) -> Result<Seed> {
+    let identifier = form.get_string("id").unwrap();
+    let username = form.get_string("name").unwrap();
+    let url = form.get_string("url").unwrap();
+    let notes = form.get_string("notes").unwrap();
+    let group = form.get_string("group").unwrap().trim().to_owned();
+    let min_len = to_length(form.get_integer("min_len").unwrap(), "Min length")?;
+    let max_len = to_length(form.get_integer("max_len").unwrap(), "Max length")?;
+    let max_site_length = to_length(form.get_integer("site_len").unwrap(), "Max site length")?;
+    let salt = form.get_integer("salt").unwrap();
+    let encoding = form.get_string("encoding").unwrap().trim().to_lowercase();
+    let characters = form.get_characters("sets").unwrap();
+    let custom_set = form.get_string("custom").unwrap();
+    let words = form.get_integer("words").unwrap() as u8;
+    let separator = form.get_string("separator").unwrap();
+    let hybrid = form.get_bool("hybrid").unwrap();
+
+    if identifier.is_empty() {
+        bail!("Identifier must not be empty.");
+    }
+    if min_len > max_len {
+        bail!("Min length must not exceed max length.");
+    }
+    let encoding = match Encoding::ALL.into_iter().find(|e| e.name() == encoding) {
+        Some(encoding) => encoding,
+        None => bail!("{} must be one of the listed encodings.", encoding_title()),
+    };
+    // the character sets are ignored by encodings with a fixed alphabet, and are optional for
+    // passphrases
+    let uses_sets = words == 0 && encoding == Encoding::CharsetTable;
+    if uses_sets && characters.is_empty() && custom_set.is_empty() {
+        bail!("Select at least one character set.");
+    }
+    if words > 0 && separator.chars().any(char::is_alphabetic) {
+        bail!("Passphrase separator must not contain letters.");
+    }
+
+    let non_empty = |s: String| if s.is_empty() { None } else { Some(s) };
+    let mut seed = base.clone();
+    seed.identifier = identifier;
+    seed.min_len = min_len;
+    seed.max_len = max_len;
+    seed.max_site_length = if max_site_length == 0 { None } else { Some(max_site_length) };
+    seed.salt = salt;
+    seed.characters = characters;
+    seed.custom_set = non_empty(custom_set);
+    seed.username = non_empty(username);
+    seed.url = non_empty(url);
+    seed.notes = non_empty(notes);
+    seed.group = non_empty(group);
+    seed.exclude_space = form.get_bool("no_space").unwrap();
+    seed.exclude = form.get_string("exclude").unwrap();
+    seed.encoding = encoding;
+    seed.kind = match words {
+        0 => SeedKind::Password,
+        words => SeedKind::Passphrase { words, separator, hybrid },
+    };
+    if seed.kind == SeedKind::Password && seed.sets().is_empty() {
+        bail!("Excluded characters must leave at least one character.");
+    }
+    if let Err(vault::Error::InvalidSeed(_, reason)) = generate::validate(&seed) {
+        bail!("Invalid seed: {reason}.");
+    }
+    // new seeds are tagged by their initial identifier, whereas existing ones keep their tag
+    seed.init_gen_tag();
+    Ok(seed)
+}
+
+/// Displays a form to edit the [defaults](SeedDefaults) of new seeds and returns the edited
+/// defaults once submitted, or [None] if the user cancels.
+pub fn defaults_editor(
+    term: &mut Terminal,
+    bg: Option<&dyn State>,
+    defaults: &SeedDefaults,
+) -> Result<Option<SeedDefaults>> {
+    let base = *defaults;
+    let form = Form::new("Defaults of new seeds")
+        .validator(move |form| parse_defaults(form, &base).err().map(|e| e.to_string()))
+        .slider("min_len", "Min length", defaults.min_len as u64, 1, u8::MAX as u64, 1)
+        .slider("max_len", "Max length", defaults.max_len as u64, 1, u8::MAX as u64, 1)
+        .textbox("encoding", encoding_title(), defaults.encoding.name().to_owned())
+        .set_selector("sets", "Character sets", defaults.characters)
+        .checkbox("no_space", "Rare without space", defaults.exclude_space);
+
+    // the form has already been validated on submission
+    dialog::form(term, bg, form)?
+        .map(|form| parse_defaults(&form, defaults))
+        .transpose()
+}
+
+/// Parses and validates the contents of a form created by [`defaults_editor`]. Defaults without a
+/// corresponding field are copied from `base`.
+fn parse_defaults(form: &Form, base: &SeedDefaults) -> Result<SeedDefaults> {
+    let min_len = to_length(form.get_integer("min_len").unwrap(), "Min length")?;
+    let max_len = to_length(form.get_integer("max_len").unwrap(), "Max length")?;
+    let encoding = form.get_string("encoding").unwrap().trim().to_lowercase();
+    let characters = form.get_characters("sets").unwrap();
+    let exclude_space = form.get_bool("no_space").unwrap();
+
+    if min_len > max_len {
+        bail!("Min length must not exceed max length.");
+    }
+    let encoding = match Encoding::ALL.into_iter().find(|e| e.name() == encoding) {
+        Some(encoding) => encoding,
+        None => bail!("{} must be one of the listed encodings.", encoding_title()),
+    };
+    if encoding == Encoding::CharsetTable && characters.is_empty() {
+        bail!("Select at least one character set.");
+    }
+    Ok(SeedDefaults { min_len, max_len, characters, exclude_space, encoding, ..*base })
+}
+
+/// Converts the value of a length slider to a length, which is stored as a `u8`. The sliders are
+/// bounded accordingly, but larger values are rejected rather than truncated regardless.
+fn to_length(value: u64, title: &str) -> Result<u8> {
+    u8::try_from(value).map_err(|_| anyhow!("{title} must not exceed {}.", u8::MAX))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn length_out_of_range() {
+        assert_eq!(to_length(255, "Length").unwrap(), 255);
+        assert!(to_length(256, "Length").is_err());
+        assert!(to_length(u64::MAX, "Length").is_err());
+    }
+}
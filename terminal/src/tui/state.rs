@@ -2,8 +2,40 @@ use super::{Frame, Terminal};
 use anyhow::Result;
 use crossterm::event::{self, Event, KeyEvent};
 use crossterm::event::{KeyCode, KeyModifiers};
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
 use thiserror::Error;
 
+/// Specifies how long to wait for input before [`State::tick`] is called.
+const TICK_RATE: Duration = Duration::from_millis(100);
+
+/// Time of the most recent key press, received by any state. See [`idle`].
+static LAST_INPUT: Mutex<Option<Instant>> = Mutex::new(None);
+
+/// How long the interface may go without input before it is idle. See [`set_idle_timeout`].
+static IDLE_TIMEOUT: Mutex<Option<Duration>> = Mutex::new(None);
+
+/// Sets how long the interface may go without input before it is considered [idle](idle), or
+/// [None] if it never is. The period starts anew.
+pub fn set_idle_timeout(timeout: Option<Duration>) {
+    *IDLE_TIMEOUT.lock().unwrap() = timeout;
+    *LAST_INPUT.lock().unwrap() = Some(Instant::now());
+}
+
+/// Returns whether no key has been pressed within the [idle timeout](set_idle_timeout). States are
+/// then cancelled, see [`State::cancel_when_idle`].
+pub fn idle() -> bool {
+    let timeout = *IDLE_TIMEOUT.lock().unwrap();
+    let last_input = *LAST_INPUT.lock().unwrap();
+
+    match (timeout, last_input) {
+        (Some(timeout), Some(last_input)) => last_input.elapsed() >= timeout,
+        _ => false,
+    }
+}
+
 /// Signal that the program should exit normally.
 ///
 /// Defined as an Error to allow the use of `?` as a signal transmitter.
@@ -25,7 +57,19 @@ pub enum Status {
 pub trait State {
     fn update(&mut self, term: &mut Terminal, key: KeyCode, modifiers: KeyModifiers) -> Result<Status>;
     fn draw(&self, frame: &mut Frame);
-    
+
+    /// Called periodically when no input has been received within [`TICK_RATE`]. Used to perform
+    /// deferred work, such as expensive computations that should only occur once the user is idle.
+    fn tick(&mut self) -> Result<Status> {
+        Ok(Status::Running)
+    }
+
+    /// Whether the state is cancelled once the interface is [idle](idle), such that dialogs left
+    /// open, e.g. showing a password, are closed along with the states which opened them.
+    fn cancel_when_idle(&self) -> bool {
+        true
+    }
+
     /// Main loop for each state. Called recursively for state transitions, thereby preserving the state
     /// history on the stack and allowing the retrieval of state-data (such as forms) through the return
     /// value.
@@ -36,21 +80,60 @@ pub trait State {
     ///
     /// # Returns
     /// * `Some(state)` if the [State] exits with [`Status::Done`].
-    /// * `None` if the [State] exits with [`Status::Cancelled`].
+    /// * `None` if the [State] exits with [`Status::Cancelled`], or is cancelled when idle.
     fn exec(mut self, term: &mut Terminal) -> Result<Option<Self>>
     where
         Self: Sized
     {
         loop {
+            if self.cancel_when_idle() && idle() {
+                break Ok(None);
+            }
             term.draw(|frame| self.draw(frame))?;
 
-            if let Event::Key(KeyEvent { code, modifiers }) = event::read()? {
-                match self.update(term, code, modifiers)? {
-                    Status::Done      => break Ok(Some(self)),
-                    Status::Cancelled => break Ok(None),
-                    Status::Running   => (),
+            let status = if event::poll(TICK_RATE)? {
+                match event::read()? {
+                    Event::Key(KeyEvent { code, modifiers }) => {
+                        *LAST_INPUT.lock().unwrap() = Some(Instant::now());
+                        self.update(term, code, modifiers)?
+                    }
+                    Event::Resize(..) => {
+                        // resize the buffers immediately, such that the next draw fills the new
+                        // size instead of leaving a stale frame behind
+                        term.autoresize()?;
+                        Status::Running
+                    }
+                    _ => Status::Running,
                 }
+            } else {
+                self.tick()?
+            };
+
+            match status {
+                Status::Done      => break Ok(Some(self)),
+                Status::Cancelled => break Ok(None),
+                Status::Running   => (),
             }
         }
     }
 }
+
+/// Executes a state by reference, such that it outlives [`State::exec`] returning an error, e.g. to
+/// save unsaved changes of it.
+impl<S: State> State for &mut S {
+    fn update(&mut self, term: &mut Terminal, key: KeyCode, modifiers: KeyModifiers) -> Result<Status> {
+        (**self).update(term, key, modifiers)
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        (**self).draw(frame)
+    }
+
+    fn tick(&mut self) -> Result<Status> {
+        (**self).tick()
+    }
+
+    fn cancel_when_idle(&self) -> bool {
+        (**self).cancel_when_idle()
+    }
+}
@@ -0,0 +1,105 @@
+use serde::Deserialize;
+use tui::style::{Color, Style};
+
+/// Defines the colors used throughout the interface.
+#[derive(Deserialize, Clone, Copy)]
+#[serde(default, deny_unknown_fields)]
+pub struct Theme {
+    /// Color of the selected row in tables and forms.
+    pub highlight: Color,
+    /// Color of panel borders.
+    pub border: Color,
+    /// Color of informational dialogs.
+    pub info: Color,
+    /// Color of warning and confirmation dialogs.
+    pub warning: Color,
+    /// Color of error dialogs.
+    pub error: Color,
+    /// Disables all colors and modifiers, for terminals where they are unsupported or unreadable.
+    #[serde(skip)]
+    pub plain: bool,
+}
+
+impl Theme {
+    /// Suitable for terminals with dark backgrounds.
+    pub fn dark() -> Self {
+        Theme {
+            highlight: Color::Cyan,
+            border: Color::Reset,
+            info: Color::Cyan,
+            warning: Color::Yellow,
+            error: Color::Red,
+            plain: false,
+        }
+    }
+
+    /// Suitable for terminals with light backgrounds.
+    pub fn light() -> Self {
+        Theme {
+            highlight: Color::Blue,
+            border: Color::Reset,
+            info: Color::Blue,
+            warning: Color::Magenta,
+            error: Color::Red,
+            plain: false,
+        }
+    }
+}
+
+impl Theme {
+    /// Returns the given style, or the default style if the theme is plain.
+    pub fn style(&self, style: Style) -> Style {
+        if self.plain {
+            Style::default()
+        } else {
+            style
+        }
+    }
+
+    /// Returns the symbol marking the selected row. Only plain themes use one, since they cannot
+    /// highlight it with colors.
+    pub fn marker(&self) -> &'static str {
+        if self.plain {
+            "> "
+        } else {
+            ""
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::dark()
+    }
+}
+
+/// Specifies a [Theme] in the configuration, either by the name of a preset or by its colors.
+#[derive(Deserialize, Clone, Copy)]
+#[serde(untagged)]
+pub enum ThemeSpec {
+    Preset(Preset),
+    Custom(Theme),
+}
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum Preset {
+    Dark,
+    Light,
+}
+
+impl ThemeSpec {
+    pub fn theme(&self) -> Theme {
+        match self {
+            ThemeSpec::Preset(Preset::Dark)  => Theme::dark(),
+            ThemeSpec::Preset(Preset::Light) => Theme::light(),
+            ThemeSpec::Custom(theme)         => *theme,
+        }
+    }
+}
+
+impl Default for ThemeSpec {
+    fn default() -> Self {
+        ThemeSpec::Preset(Preset::Dark)
+    }
+}
@@ -1,10 +1,12 @@
-use std::{borrow::Cow, ops::{Add, Sub, Mul, Div}, fmt::Display};
+use crossterm::event::{KeyCode, KeyModifiers};
+use std::{borrow::Cow, iter, ops::{Add, Sub, Mul, Div}, fmt::{Display, UpperHex}};
 use tui::{
     buffer::Buffer,
     layout::{Constraint, Layout, Rect},
     style::Style,
     widgets::Widget,
 };
+use unicode_width::UnicodeWidthChar;
 use super::input::FormWidget;
 
 /// Trait implemented for all numerical types.
@@ -13,12 +15,77 @@ pub trait Number:
     Sub<Output=Self> +
     Mul<Output=Self> +
     Div<Output=Self> +
-    Ord + Copy + Display {}
+    Ord + Copy + Display + UpperHex {}
 impl<T> Number for T
 where T:
-    Add<Output=Self> + Sub<Output=Self> + Mul<Output=Self> + Div<Output=Self> + Ord + Copy + Display
+    Add<Output=Self> + Sub<Output=Self> + Mul<Output=Self> + Div<Output=Self> + Ord + Copy + Display + UpperHex
 {}
 
+/// Moves an index within `0..len` by `delta`, either stopping at the ends or wrapping around to
+/// the other end. `len` must be non-zero.
+pub fn step_index(index: usize, delta: isize, len: usize, wrap: bool) -> usize {
+    let moved = index as isize + delta;
+
+    if wrap {
+        moved.rem_euclid(len as isize) as usize
+    } else {
+        moved.clamp(0, len as isize - 1) as usize
+    }
+}
+
+/// Fits a string within `width` columns of the terminal, replacing the end of the string with `…`
+/// if it is too wide. Widths are measured per character, such that wide characters take up two
+/// columns and combining characters none. Control characters would corrupt the layout and are
+/// removed.
+pub fn truncate(string: &str, width: usize) -> String {
+    let chars = string.chars().filter_map(|c| c.width().map(|w| (c, w)));
+
+    if chars.clone().map(|(_, w)| w).sum::<usize>() <= width {
+        return chars.map(|(c, _)| c).collect();
+    }
+    // leave room for the ellipsis
+    let mut remaining = width.saturating_sub(1);
+    let mut truncated: String = chars
+        .take_while(|&(_, w)| {
+            let fits = w <= remaining;
+            remaining = remaining.saturating_sub(w);
+            fits
+        })
+        .map(|(c, _)| c)
+        .collect();
+
+    if width > 0 {
+        truncated.push('…');
+    }
+    truncated
+}
+
+/// Formats a key combination for display to the user, e.g. `ctrl + a` or `alt + ↑`.
+pub fn format_key(key: KeyCode, modifiers: KeyModifiers) -> String {
+    let key = match key {
+        KeyCode::Char(' ') => "space".to_owned(),
+        KeyCode::Char(c)   => c.to_string(),
+        KeyCode::Up        => "↑".to_owned(),
+        KeyCode::Down      => "↓".to_owned(),
+        KeyCode::Left      => "←".to_owned(),
+        KeyCode::Right     => "→".to_owned(),
+        KeyCode::F(n)      => format!("f{n}"),
+        other              => format!("{other:?}").to_lowercase(),
+    };
+    let prefixes = [
+        (KeyModifiers::CONTROL, "ctrl + "),
+        (KeyModifiers::ALT,     "alt + "),
+        (KeyModifiers::SHIFT,   "shift + "),
+    ];
+
+    prefixes
+        .iter()
+        .filter(|(modifier, _)| modifiers.contains(*modifier))
+        .map(|(_, prefix)| *prefix)
+        .chain(iter::once(key.as_str()))
+        .collect()
+}
+
 /// Widget wrapper for centering vertically according to specified height.
 pub struct CenteredWidget<W: Widget> {
     widget: W,
@@ -103,7 +170,24 @@ impl Center for FormWidget<'_> {
     type W = Self;
 
     fn center(self) -> CenteredWidget<Self::W> {
-        let height = self.0.fields().len() as u16;
+        let height = self.0.fields().iter().map(|field| field.height()).sum::<usize>() as u16;
         CenteredWidget::new(self, height)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncate_combining() {
+        // each 'e' is followed by a combining acute accent, which takes up no column
+        let identifier = "e\u{301}e\u{301}e\u{301}e\u{301}";
+
+        assert_eq!(truncate(identifier, 4), identifier);
+        assert_eq!(truncate(identifier, 3), "e\u{301}e\u{301}…");
+        assert_eq!(truncate("ab\u{200b}c\td", 5), "ab\u{200b}cd");
+        assert_eq!(truncate("日本語", 4), "日…");
+        assert_eq!(truncate("abc", 0), "");
+    }
+}
@@ -1,4 +1,5 @@
 use std::{
+    cmp::Reverse,
     collections::hash_map::DefaultHasher,
     hash::{Hash, Hasher}, cell::{RefCell, Ref}, borrow::BorrowMut,
 };
@@ -6,49 +7,64 @@ use std::{
 use super::{
     dialog,
     input::{self, Form, Input},
-    state::{self, State, Status},
+    seed_editor,
+    keymap::{Action, Key},
+    state::{self, ExitSignal, State, Status},
+    utility,
     Frame, Terminal,
 };
-use anyhow::Result;
+use crate::{clipboard, config, shared};
+use anyhow::{anyhow, Result};
 use crossterm::event::{KeyModifiers, KeyCode};
-use indoc::indoc;
 use tui::{
-    layout::{Constraint, Layout},
-    style::{Color, Modifier, Style},
+    layout::{Constraint, Layout, Margin, Rect},
+    style::{Modifier, Style},
     widgets::{Block, Borders, Paragraph, Row, Table, TableState},
 };
 use vault::{
-    seed::{Characters, Seed},
-    Vault,
+    seed::{Seed, SeedTemplate},
+    ConflictPolicy, SortKey, Vault,
 };
+use zeroize::Zeroizing;
 
-pub fn vault_view(term: &mut Terminal, vault: Vault, key: Option<String>) -> Result<()> {
+pub fn vault_view(term: &mut Terminal, vault: Vault, key: Option<Zeroizing<String>>) -> Result<()> {
     let filter = input::StringInput::default();
     let (displayed, default_row) = filter_seeds(vault.seeds(), "");
     let mut table_state = TableState::default();
     table_state.select(default_row);
     let vault_hash = hash_vault(&vault);
 
-    VaultView {
+    let mut view = VaultView {
         vault,
         key,
         filter,
+        filter_focused: false,
+        yanking: false,
         displayed,
         table_state: RefCell::new(table_state),
         prev_vault_hash: vault_hash,
-    }.exec(term)?;
-    
-    Ok(())
+    };
+    state::set_idle_timeout(config::get().lock_timeout());
+    // the view is executed by reference, such that its changes can be saved if anything fails
+    let result = (&mut view).exec(term).map(drop);
+    // dialogs shown after the view exits, e.g. of fatal errors, stay open until dismissed
+    state::set_idle_timeout(None);
+    result.map_err(|e| view.emergency_save(e))
 }
 
 struct VaultView {
     /// The vault backend.
     vault: Vault,
-    /// The key to be used generating passwords. If [None], is prompted when a password is
-    /// generated.
-    key: Option<String>,
-    /// Text input containing a string to filter seeds by.
+    /// The key to be used generating passwords. If [None], the vault is locked and the key is
+    /// prompted when a password is generated. Forgotten after the configured idle period.
+    key: Option<Zeroizing<String>>,
+    /// Text input containing a string to filter seeds by. Cleared by escape.
     filter: input::StringInput,
+    /// Whether all plain keys are typed into the filter rather than interpreted as key bindings.
+    /// Enter then generates the top match, and escape clears and unfocuses the filter.
+    filter_focused: bool,
+    /// Whether the next key picks a field of the selected seed to copy. See [`YANK_TARGETS`].
+    yanking: bool,
     /// Ordered indices of rows to display according to filter.
     displayed: Vec<usize>,
     /// Maintains index of the selected row. Uses [RefCell] for interior mutability for use in [State::draw].
@@ -70,19 +86,265 @@ impl VaultView {
         self.selected_displayed().map(|row| self.displayed[row])
     }
 
+    /// Selects the row displaying the seed at specified index, if it is displayed.
+    fn select_seed(&mut self, seed_index: usize) {
+        if let Some(row) = self.displayed.iter().position(|&i| i == seed_index) {
+            self.table_state.borrow_mut().select(Some(row));
+        }
+    }
+
+    /// Saves the vault if it has changed, recording its state as unchanged. Scratch vaults are kept
+    /// in memory only, and stay changed until saved under a name with [`VaultView::save_as`].
+    ///
+    /// If the vault file was changed by another program since it was loaded, saving would
+    /// overwrite those changes. The user is then shown what changed, and picks whether to
+    /// overwrite, merge, or reload. The vault is left unsaved if the user cancels.
+    fn save(&mut self, term: &mut Terminal) -> Result<()> {
+        if !self.is_dirty() || self.vault.is_scratch() {
+            return Ok(());
+        }
+        if self.vault.changed_on_disk()? {
+            let diff = match self.vault.diff_on_disk() {
+                Ok(diff) => diff.to_string(),
+                Err(e) => format!("The file could not be read: {e}"),
+            };
+            let msg = format!(
+                "The vault file was changed by another program since it was loaded, and saving \
+                 would overwrite those changes.\n\n{diff}"
+            );
+            dialog::warning(term, Some(self), msg)?;
+
+            let options = SAVE_CONFLICT_OPTIONS.iter().map(|&option| option.to_owned()).collect();
+            let result = match dialog::pick(term, Some(self), "Resolve changes on disk", options, 0)? {
+                Some(0) => Ok(()),
+                Some(1) => self.vault.merge_from_disk().map(drop),
+                Some(2) => self.vault.reload(),
+                _ => return Ok(()),
+            };
+            if let Err(e) = result {
+                return dialog::error(term, Some(self), e.to_string());
+            }
+            self.update_displayed();
+
+            // the reloaded vault is unchanged, and its key may differ from the entered one
+            if !self.vault.changed_on_disk()? {
+                self.prev_vault_hash = hash_vault(&self.vault);
+
+                if self.key.as_ref().is_some_and(|key| !self.vault.verify_key(key)) {
+                    self.key = None;
+                }
+            }
+        }
+        self.save_unchecked()
+    }
+
+    /// Saves the vault without checking for changes on disk. See [`VaultView::save`].
+    fn save_unchecked(&mut self) -> Result<()> {
+        self.vault.save()?;
+        self.prev_vault_hash = hash_vault(&self.vault);
+        Ok(())
+    }
+
+    /// Returns whether the vault has changed since it was loaded or last saved.
+    fn is_dirty(&self) -> bool {
+        hash_vault(&self.vault) != self.prev_vault_hash
+    }
+
+    /// Attempts to save unsaved changes before a fatal error exits the interface, such that a crash
+    /// doesn't also lose the user's edits. The outcome of the save is appended to the error message.
+    ///
+    /// Changes made to the vault file by another program are never overwritten, since they cannot
+    /// be resolved with the user as [`VaultView::save`] does.
+    fn emergency_save(&mut self, e: anyhow::Error) -> anyhow::Error {
+        if e.is::<ExitSignal>() || !self.is_dirty() {
+            return e;
+        }
+        if self.vault.is_scratch() {
+            return anyhow!("{e}\n\nUnsaved changes to the scratch vault were lost.");
+        }
+        if !matches!(self.vault.changed_on_disk(), Ok(false)) {
+            let path = self.vault.path().display();
+            return anyhow!(
+                "{e}\n\nUnsaved changes were lost, since {path} was changed by another program or \
+                 could not be read."
+            );
+        }
+        let outcome = match self.save_unchecked() {
+            Ok(()) => format!("Unsaved changes were saved to {}.", self.vault.path().display()),
+            Err(save_error) => format!("Unsaved changes could not be saved: {save_error}"),
+        };
+        anyhow!("{e}\n\n{outcome}")
+    }
+
     fn update_displayed(&mut self) {
         let (displayed, default_row) = filter_seeds(self.vault.seeds(), &self.filter.value());
         self.displayed = displayed;
         self.table_state.borrow_mut().select(default_row);
     }
     
+    /// Returns the key used to generate passwords, prompting the user for it if it hasn't been
+    /// entered yet. Returns [None] if the user cancels.
+    fn unlock(&mut self, term: &mut Terminal) -> Result<Option<Zeroizing<String>>> {
+        while self.key.is_none() {
+            let form = Form::new("Unlock vault").password("key", "Key");
+
+            match dialog::form(term, Some(self), form)? {
+                Some(form) => {
+                    let key = Zeroizing::new(form.get_string("key").unwrap());
+
+                    // upgrading the key's hash changes the vault, but saves only the new hash
+                    let saved = hash_vault(&self.vault) == self.prev_vault_hash;
+
+                    if self.vault.verify_and_upgrade(&key)? {
+                        if saved {
+                            self.prev_vault_hash = hash_vault(&self.vault);
+                        }
+                        self.key = Some(key);
+                    } else {
+                        dialog::error(term, Some(self), "Incorrect key.")?;
+                    }
+                }
+                None => break,
+            }
+        }
+        Ok(self.key.clone())
+    }
+
+    /// Displays the seed editor, previewing generated passwords if the key has been entered.
+    fn seed_editor(&self, term: &mut Terminal, title: &str, seed: &Seed) -> Result<Option<Seed>> {
+        match &self.key {
+            Some(key) => {
+                let preview = |seed: &Seed| self.vault.password(seed, key);
+                seed_editor::seed_editor(term, Some(self), title, seed, Some(&preview))
+            }
+            None => seed_editor::seed_editor(term, Some(self), title, seed, None),
+        }
+    }
+
+    /// Prompts for another vault and merges its seeds into this one. Imported seeds generate
+    /// different passwords here, since the pepper and key differ between vaults.
+    fn import(&mut self, term: &mut Terminal) -> Result<()> {
+        let mut form = Form::new("Import seeds")
+            .textbox("vault", "Vault", String::new())
+            .textbox("conflict", "On conflict (skip/rename/replace)", "skip".to_owned());
+
+        loop {
+            let submitted = match dialog::form(term, Some(self), form)? {
+                Some(submitted) => submitted,
+                None => return Ok(()),
+            };
+            let identifier = submitted.get_string("vault").unwrap();
+            let policy = match submitted.get_string("conflict").unwrap().to_lowercase().as_str() {
+                "skip"    => Some(ConflictPolicy::Skip),
+                "rename"  => Some(ConflictPolicy::Rename),
+                "replace" => Some(ConflictPolicy::Replace),
+                _ => None,
+            };
+
+            let result = match policy {
+                Some(policy) => Vault::load(&shared::vault_folder(), identifier.clone())
+                    .map(|other| (other, policy))
+                    .map_err(|e| e.to_string()),
+                None => Err("On conflict must be one of skip, rename, or replace.".to_owned()),
+            };
+
+            match result {
+                Ok((other, policy)) => {
+                    let confirm_str = format!(
+                        "Seeds imported from '{identifier}' will generate different passwords in \
+                         this vault. Continue?"
+                    );
+
+                    if dialog::confirm(term, Some(self), confirm_str)? {
+                        let count = self.vault.merge(&other, policy)?;
+                        self.save(term)?;
+                        self.update_displayed();
+                        dialog::info(term, Some(self), format!("{count} seed(s) imported."))?;
+                    }
+                    return Ok(());
+                }
+                Err(e) => {
+                    dialog::error(term, Some(self), e)?;
+                    form = submitted;
+                }
+            }
+        }
+    }
+
+    /// Handles a key press. See [`State::update`].
+    fn handle_key(&mut self, term: &mut Terminal, key: KeyCode, modifiers: KeyModifiers) -> Result<Status> {
+        if self.yanking {
+            self.yank(term, key)?;
+            return Ok(Status::Running);
+        }
+        let plain = !modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT);
+
+        // while the filter is focused, plain keys are always typed into it, such that bindings to
+        // plain characters don't interfere with filtering
+        let action = if self.filter_focused && plain {
+            None
+        } else {
+            config::get().keymap.action(key, modifiers)
+        };
+        let handler = action.and_then(|action| {
+            BINDINGS.iter().find(|(a, ..)| *a == action).map(|(.., handler)| handler)
+        });
+        // escape clears the filter even when unfocused, such that all seeds are shown again
+        let clears_filter = key == KeyCode::Esc
+            && (self.filter_focused || !self.filter.value().is_empty());
+
+        match handler {
+            Some(handler) => self.run_handler(term, handler)?,
+            // enter generates the best match of the filter, such that a password is typed, not
+            // navigated to
+            None if self.filter_focused && key == KeyCode::Enter => {
+                if !self.displayed.is_empty() {
+                    self.table_state.borrow_mut().select(Some(0));
+                    self.generate(term)?;
+                }
+            }
+            None if clears_filter => {
+                self.filter.set_value(String::new());
+                self.filter_focused = false;
+                self.update_displayed();
+            }
+            // other key combinations with modifiers are reserved for future bindings
+            None if !plain => (),
+            None => {
+                if self.filter.key_down(key, modifiers) {
+                    self.update_displayed();
+                }
+            }
+        };
+        Ok(Status::Running)
+    }
+
+    /// Runs the handler of a key binding. Errors caused by invalid input are reported in an error
+    /// dialog, after which the interface carries on, since the vault is left unchanged by them.
+    /// Other errors are returned.
+    fn run_handler(&mut self, term: &mut Terminal, handler: &Handler) -> Result<()> {
+        match handler(self, term) {
+            Err(e) => match e.downcast_ref::<vault::Error>() {
+                Some(e) if e.is_invalid_input() => dialog::error(term, Some(self), e.to_string()),
+                _ => Err(e),
+            },
+            ok => ok,
+        }
+    }
+
+    /// Moves the selected seed by `delta` rows, saving the new order.
+    fn move_seed(&mut self, term: &mut Terminal, delta: isize) -> Result<()> {
+        self.move_selected(delta, true)?;
+        self.save(term)
+    }
+
     fn move_selected(&mut self, delta: isize, move_content: bool) -> Result<()> {
         let mut table_state = self.table_state.borrow_mut();
 
         if let Some(prev) = table_state.selected() {
-            let new =
-                (prev as isize + delta).clamp(0, (self.displayed.len() - 1) as isize) as usize;
-                table_state.select(Some(new));
+            let wrap = config::get().wrap_navigation;
+            let new = utility::step_index(prev, delta, self.displayed.len(), wrap);
+            table_state.select(Some(new));
 
             if move_content {
                 self.vault.swap(self.displayed[prev], self.displayed[new])?;
@@ -92,150 +354,718 @@ impl VaultView {
     }
 }
 
-impl State for VaultView {
-    fn update(&mut self, term: &mut Terminal, key: KeyCode, modifiers: KeyModifiers) -> Result<Status> {
-        let ctrl = modifiers.contains(KeyModifiers::CONTROL);
-        let alt = modifiers.contains(KeyModifiers::ALT);
+/// Handles an [Action] of the [VaultView].
+type Handler = fn(&mut VaultView, &mut Terminal) -> Result<()>;
+
+/// Contains all actions of the [VaultView] with their descriptions and handlers. This is the single
+/// source of truth for both handling keys and listing them in the help dialog, such that the two
+/// cannot drift apart. The keys bound to each action are defined by the [Keymap](super::keymap::Keymap).
+const BINDINGS: &[(Action, &str, Handler)] = &[
+    (Action::MoveUp,        "Select previous seed",                      |view, _| view.move_selected(-1, false)),
+    (Action::MoveDown,      "Select next seed",                          |view, _| view.move_selected(1, false)),
+    (Action::MoveSeedUp,    "Move selected seed up",                     |view, term| view.move_seed(term, -1)),
+    (Action::MoveSeedDown,  "Move selected seed down",                   |view, term| view.move_seed(term, 1)),
+    (Action::Generate,      "Generate password from selected seed",      VaultView::generate),
+    (Action::GenerateAll,   "Generate passwords of all displayed seeds", VaultView::generate_all),
+    (Action::GenerateGroup, "Generate logins of selected seed's group",  VaultView::generate_group),
+    (Action::PickVariant,   "Pick among variants of the password",       VaultView::pick_variant),
+    (Action::Add,           "Add new seed below selected seed",          VaultView::add),
+    (Action::Edit,          "Edit selected seed",                        VaultView::edit),
+    (Action::Rename,        "Rename selected seed",                      VaultView::rename),
+    (Action::Duplicate,     "Duplicate selected seed",                   VaultView::duplicate),
+    (Action::Remove,        "Remove selected seed permanently",          VaultView::remove),
+    (Action::Import,        "Import seeds from another vault",           VaultView::import),
+    (Action::RestoreBackup, "Restore a backup of the vault",             VaultView::restore_backup),
+    (Action::Sort,          "Sort stored seeds by a field",              VaultView::sort),
+    (Action::Settings,      "Change the defaults of new seeds",          VaultView::settings),
+    (Action::SaveAs,        "Save vault under another name",             VaultView::save_as),
+    (Action::OpenUrl,       "Open URL of selected seed in browser",      VaultView::open_url),
+    (Action::Stats,         "Show vault statistics",                     VaultView::stats),
+    (Action::Audit,         "Audit vault for weak seeds",                VaultView::audit),
+    (Action::Help,          "Show this help",                            VaultView::help),
+    (Action::Filter,        "Focus filter; (enter) generates top match", VaultView::focus_filter),
+    (Action::Yank,          "Copy a field of selected seed",             VaultView::start_yank),
+    (Action::Quit,          "Quit",                                      VaultView::quit),
+];
+
+/// Contains the ways to resolve changes to the vault file made by another program, in the order
+/// handled by [`VaultView::save`].
+const SAVE_CONFLICT_OPTIONS: [&str; 3] = [
+    "Overwrite the file with the changes of this session",
+    "Keep the changes of this session, and add the seeds only on disk",
+    "Reload the file, discarding the changes of this session",
+];
+
+/// Terminal width below which the seed table is replaced by a single column, leaving the remaining
+/// fields to the details box.
+const COMPACT_WIDTH: u16 = 80;
+
+/// Number of password variants offered to pick from. See [`Seed::variant`].
+const VARIANT_COUNT: usize = 5;
+
+/// Contains the keys picking each field which may be copied in yank mode, and their names.
+const YANK_TARGETS: [(char, &str); 3] = [
+    ('u', "username"),
+    ('p', "password"),
+    ('l', "URL"),
+];
+
+/// Handlers of the key bindings in [`BINDINGS`].
+impl VaultView {
+    fn generate(&mut self, term: &mut Terminal) -> Result<()> {
+        if let Some(seed_index) = self.selected_seed_index() {
+            if let Some(key) = self.unlock(term)? {
+                let seed = self.seed_at(seed_index);
+                let password = self.vault.password_verified(seed, &key)?;
 
-        match key {
-            KeyCode::Up => {
-                self.move_selected(-1, alt)?;
+                if seed.exceeds_site_length(&password) {
+                    let msg = format!(
+                        "The password is {} characters long, which exceeds the maximum of {} noted \
+                         for the service. It may be rejected.",
+                        password.chars().count(),
+                        seed.max_site_length.unwrap()
+                    );
+                    dialog::warning(term, Some(self), msg)?;
+                }
+                dialog::password(term, Some(self), &seed.identifier, &password)?;
+                self.vault.mark_generated(seed_index)?;
             }
-            KeyCode::Down => {
-                self.move_selected(1, alt)?;
+        }
+        Ok(())
+    }
+
+    fn generate_all(&mut self, term: &mut Terminal) -> Result<()> {
+        if let Some(key) = self.unlock(term)? {
+            let seeds: Vec<&Seed> = self.displayed.iter().map(|&i| self.seed_at(i)).collect();
+            let generate = |i: usize| self.vault.password(seeds[i], &key);
+
+            if let Some(passwords) = dialog::progress(term, Some(self), "Generating", seeds.len(), generate)? {
+                // a seed which cannot produce a password is listed with the reason instead
+                let msg = seeds
+                    .iter()
+                    .zip(passwords)
+                    .map(|(seed, password)| match password {
+                        Ok(password) => format!("{}: {}", seed.identifier, password.as_str()),
+                        Err(e) => format!("{}: {e}", seed.identifier),
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                dialog::info(term, Some(self), msg)?;
             }
-            KeyCode::Enter => {
-                todo!()
+        }
+        Ok(())
+    }
+
+    /// Generates the passwords of all seeds in the [group](Seed::group) of the selected seed, and
+    /// lists them along with their usernames. Each picked item is copied to the clipboard, until
+    /// the list is cancelled.
+    fn generate_group(&mut self, term: &mut Terminal) -> Result<()> {
+        let seed_index = match self.selected_seed_index() {
+            Some(seed_index) => seed_index,
+            None => return Ok(()),
+        };
+        let selected = self.seed_at(seed_index);
+        let group = match &selected.group {
+            Some(group) => group.clone(),
+            None => {
+                let msg = format!("Seed '{}' is not in a group. Set one in the seed editor.", selected.identifier);
+                return dialog::error(term, Some(self), msg);
             }
-            KeyCode::Char('a') if ctrl => {
-                // let mut form = Form::new("Add seed")
-                //     .textbox("id", "Identifier", String::default())
-                //     .slider("len", "Length", 32, 1, 255, 1)
-                //     .slider("salt", "Salt", 0, 0, u64::MAX, 1)
-                //     .textbox("chars", "Sets", "ULNSR".to_owned())
-                //     .textbox("name", "Username", String::default());
-                
-                // loop {
-                //     if let Some(form_state) = dialog::form(term, Some(self), form)? {
-                //         form = form_state;
-
-                //         let identifier = form.get_string("id").unwrap();
-                //         if identifier.is_empty() {
-                //             dialog::error(term, Some(self), "Identifier must not be empty.")?;
-                //             continue;
-                //         }
-                //         let length = form.get_integer("len").unwrap() as u8;
-                //         let salt = form.get_integer("salt").unwrap();
-                //         let characters = form.get_string("chars").unwrap();
-                //         let username = form.get_string("name").unwrap();
-                        
-                //         self.vault.push(Seed {
-                //             identifier,
-                //             length,
-                //             salt,
-                //             characters: Characters::all(), // TODO
-                //             username: if username.is_empty() {
-                //                 None
-                //             } else {
-                //                 Some(username)
-                //             },
-                //         });
-                //         self.update_displayed();
-                //     }
-                //     break;
-                // }
+        };
+
+        if let Some(key) = self.unlock(term)? {
+            let seeds: Vec<&Seed> = self
+                .vault
+                .seeds()
+                .iter()
+                .filter(|seed| seed.group.as_ref() == Some(&group))
+                .collect();
+            let generate = |i: usize| self.vault.password(seeds[i], &key);
+
+            let passwords = match dialog::progress(term, Some(self), "Generating", seeds.len(), generate)? {
+                Some(passwords) => passwords.into_iter().collect::<vault::Result<Vec<_>>>()?,
+                None => return Ok(()),
+            };
+            let mut items = Vec::new();
+
+            for (seed, password) in seeds.iter().zip(passwords) {
+                if let Some(username) = &seed.username {
+                    let item = format!("{} username: {username}", seed.identifier);
+                    items.push((item, Zeroizing::new(username.clone())));
+                }
+                items.push((format!("{} password: {}", seed.identifier, password.as_str()), password));
             }
-            KeyCode::Char('h') if ctrl => {
-                dialog::info(
-                    term,
-                    Some(self),
-                    indoc!(
-                        "(alt + ↑/↓)  Move selected seed contents
-                         (ctrl + a)   Add new seed
-                         (ctrl + r)   Remove selected seed permanently
-                         (enter)      Generate password from selected seed"
-                    ),
-                )?;
+            let options: Vec<String> = items.iter().map(|(option, _)| option.clone()).collect();
+            let title = format!("Logins of group '{group}', (enter) copies");
+            let mut picked = 0;
+
+            while let Some(i) = dialog::pick(term, Some(self), &title, options.clone(), picked)? {
+                clipboard::copy(&items[i].1, false)?;
+                picked = i;
             }
-            KeyCode::Char('r') if ctrl => {
-                if let Some(selected_seed_index) = self.selected_seed_index() {
-                    let selected_seed = self.seed_at(selected_seed_index);
-                    let confirm_str = format!(
-                        "This will permanently remove seed '{}' from the vault. Continue?",
-                        selected_seed.identifier
-                    );
+        }
+        Ok(())
+    }
 
-                    if dialog::confirm(term, Some(self), confirm_str)? {
-                        self.vault.remove(selected_seed_index);
-                        self.update_displayed();
-                    };
+    /// Generates several variants of the password of the selected seed and lets the user pick one.
+    /// The picked variant is stored on the seed, such that it is generated from then on.
+    fn pick_variant(&mut self, term: &mut Terminal) -> Result<()> {
+        if let Some(seed_index) = self.selected_seed_index() {
+            if let Some(key) = self.unlock(term)? {
+                let seed = self.seed_at(seed_index).clone();
+                let count = VARIANT_COUNT.max(seed.variant as usize + 1);
+                let generate = |variant: usize| {
+                    let seed = Seed { variant: variant as u32, ..seed.clone() };
+                    self.vault.password(&seed, &key)
+                };
+
+                let passwords = match dialog::progress(term, Some(self), "Generating", count, generate)? {
+                    Some(passwords) => passwords.into_iter().collect::<vault::Result<Vec<_>>>()?,
+                    None => return Ok(()),
+                };
+                let title = format!("Variants of '{}'", seed.identifier);
+                let options = passwords.iter().map(|password| password.to_string()).collect();
+                let picked = dialog::pick(term, Some(self), &title, options, seed.variant as usize)?;
+
+                if let Some(variant) = picked.filter(|&variant| variant != seed.variant as usize) {
+                    let seed = Seed { variant: variant as u32, ..seed };
+                    self.vault.edit_seed(seed_index, seed)?;
+                    self.save(term)?;
                 }
             }
-            KeyCode::Char(_) if ctrl || alt => (),
-            _ => {
-                if self.filter.key_down(key, modifiers) {
+        }
+        Ok(())
+    }
+
+    fn add(&mut self, term: &mut Terminal) -> Result<()> {
+        let mut seed = match self.pick_template(term)? {
+            Some(template) => template.apply(String::new(), self.vault.seed_defaults()),
+            None => return Ok(()),
+        };
+        // the seed is placed next to the selected one, which is likely related
+        let seed_index = self.selected_seed_index().map_or(self.vault.seeds().len(), |i| i + 1);
+
+        // the editor is reopened with the entered parameters if the identifier is taken
+        while let Some(edited) = self.seed_editor(term, "Add seed", &seed)? {
+            match self.vault.insert(seed_index, edited.clone()) {
+                Ok(()) => {
                     self.update_displayed();
+                    self.select_seed(seed_index);
+                    return self.save(term);
+                }
+                Err(e) if e.is_invalid_input() => {
+                    dialog::error(term, Some(self), e.to_string())?;
+                    seed = edited;
                 }
+                Err(e) => return Err(e.into()),
             }
+        }
+        Ok(())
+    }
+
+    /// Lets the user pick a [template](SeedTemplate) for a new seed, listing the parameters of each.
+    /// The custom template, which starts out with the defaults of the vault, is selected initially.
+    fn pick_template(&self, term: &mut Terminal) -> Result<Option<SeedTemplate>> {
+        let options = SeedTemplate::ALL
+            .iter()
+            .map(|&template| {
+                let seed = template.apply(String::new(), self.vault.seed_defaults());
+                let length = if seed.min_len == seed.max_len {
+                    seed.min_len.to_string()
+                } else {
+                    format!("{}-{}", seed.min_len, seed.max_len)
+                };
+                format!("{:<8}{} {length}", template.name(), seed.characters.to_string())
+            })
+            .collect();
+        let custom = SeedTemplate::ALL.len() - 1;
+        let picked = dialog::pick(term, Some(self), "Template of new seed", options, custom)?;
+        Ok(picked.map(|i| SeedTemplate::ALL[i]))
+    }
+
+    fn edit(&mut self, term: &mut Terminal) -> Result<()> {
+        if let Some(seed_index) = self.selected_seed_index() {
+            let seed = self.seed_at(seed_index);
+
+            if let Some(seed) = self.seed_editor(term, "Edit seed", seed)? {
+                self.vault.edit_seed(seed_index, seed)?;
+                self.save(term)?;
+                self.update_displayed();
+            }
+        }
+        Ok(())
+    }
+
+    /// Prompts for a new identifier of the selected seed. The password is unchanged, since it is
+    /// generated from the generation tag of the seed rather than its identifier.
+    fn rename(&mut self, term: &mut Terminal) -> Result<()> {
+        if let Some(seed_index) = self.selected_seed_index() {
+            let old = self.seed_at(seed_index).identifier.clone();
+            let mut form = Form::new("Rename seed").textbox("id", "Identifier", old);
+
+            loop {
+                let submitted = match dialog::form(term, Some(self), form)? {
+                    Some(submitted) => submitted,
+                    None => return Ok(()),
+                };
+                let identifier = submitted.get_string("id").unwrap();
+
+                match self.vault.rename_seed(seed_index, identifier) {
+                    Ok(()) => {
+                        self.update_displayed();
+                        self.select_seed(seed_index);
+                        return self.save(term);
+                    }
+                    Err(e) => {
+                        dialog::error(term, Some(self), e.to_string())?;
+                        form = submitted;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Reorders the stored seeds by a field picked by the user, after confirming since the previous
+    /// order is lost.
+    fn sort(&mut self, term: &mut Terminal) -> Result<()> {
+        let options = SortKey::ALL.iter().map(|key| key.name().to_owned()).collect();
+
+        if let Some(i) = dialog::pick(term, Some(self), "Sort seeds by", options, 0)? {
+            let by = SortKey::ALL[i];
+            let msg = format!(
+                "This will reorder the seeds stored in the vault by {}. Continue?",
+                by.name()
+            );
+
+            if dialog::confirm(term, Some(self), msg)? {
+                self.vault.sort_seeds(by);
+                self.save(term)?;
+                self.update_displayed();
+            }
+        }
+        Ok(())
+    }
+
+    fn settings(&mut self, term: &mut Terminal) -> Result<()> {
+        let defaults = *self.vault.seed_defaults();
+
+        if let Some(defaults) = seed_editor::defaults_editor(term, Some(self), &defaults)? {
+            self.vault.set_seed_defaults(defaults)?;
+            self.save(term)?;
+        }
+        Ok(())
+    }
+
+    /// Prompts for an identifier and saves the vault under it. This is how scratch vaults are
+    /// persisted.
+    fn save_as(&mut self, term: &mut Terminal) -> Result<()> {
+        let mut form = Form::new("Save vault as").textbox("vault", "Vault", String::new());
+
+        loop {
+            let submitted = match dialog::form(term, Some(self), form)? {
+                Some(submitted) => submitted,
+                None => return Ok(()),
+            };
+            let identifier = submitted.get_string("vault").unwrap();
+
+            match self.vault.save_as(&shared::vault_folder(), identifier) {
+                Ok(()) => {
+                    self.prev_vault_hash = hash_vault(&self.vault);
+                    let msg = format!("Vault saved to {}.", self.vault.path().display());
+                    return dialog::info(term, Some(self), msg);
+                }
+                Err(e) => {
+                    dialog::error(term, Some(self), e.to_string())?;
+                    form = submitted;
+                }
+            }
+        }
+    }
+
+    /// Saves the vault and exits the interface. If changes remain unsaved, because the vault is a
+    /// scratch vault or saving failed or was cancelled, the user confirms that they are lost.
+    fn quit(&mut self, term: &mut Terminal) -> Result<()> {
+        if let Err(e) = self.save(term) {
+            dialog::error(term, Some(self), format!("The vault could not be saved: {e}"))?;
+        }
+        if self.is_dirty() {
+            let msg = if self.vault.is_scratch() {
+                "The scratch vault is kept in memory only, and its seeds are lost on quit. \
+                 Quit anyway?"
+            } else {
+                "The changes to the vault were not saved, and are lost on quit. Quit anyway?"
+            };
+
+            if !dialog::confirm(term, Some(self), msg)? {
+                return Ok(());
+            }
+        }
+        Err(ExitSignal.into())
+    }
+
+    fn duplicate(&mut self, term: &mut Terminal) -> Result<()> {
+        if let Some(seed_index) = self.selected_seed_index() {
+            let copy_index = self.vault.duplicate(seed_index)?;
+            self.save(term)?;
+            self.update_displayed();
+            self.select_seed(copy_index);
+        }
+        Ok(())
+    }
+
+    fn remove(&mut self, term: &mut Terminal) -> Result<()> {
+        if let Some(selected_seed_index) = self.selected_seed_index() {
+            let selected_seed = self.seed_at(selected_seed_index);
+            let confirm_str = format!(
+                "This will permanently remove seed '{}' from the vault. Continue?",
+                selected_seed.identifier
+            );
+
+            if dialog::confirm(term, Some(self), confirm_str)? {
+                self.vault.remove(selected_seed_index)?;
+                self.save(term)?;
+                self.update_displayed();
+            };
+        }
+        Ok(())
+    }
+
+    /// Prompts for a backup of the vault file and replaces the seeds with those of it. The vault
+    /// is saved immediately, keeping the replaced version as the most recent backup.
+    fn restore_backup(&mut self, term: &mut Terminal) -> Result<()> {
+        let count = self.vault.backup_count();
+
+        if count == 0 {
+            return dialog::error(term, Some(self), "Backups are disabled in the configuration.");
+        }
+        let form = Form::new("Restore backup")
+            .slider("n", "Backup (1 is the most recent)", 1, 1, count as u64, 1);
+
+        if let Some(form) = dialog::form(term, Some(self), form)? {
+            let n = form.get_integer("n").unwrap() as usize;
+            let confirm_str = format!(
+                "This replaces all seeds with those of backup {n}. The current version is kept as \
+                 backup 1. Continue?"
+            );
+
+            if dialog::confirm(term, Some(self), confirm_str)? {
+                match self.vault.restore_backup(n) {
+                    Ok(()) => {
+                        self.save(term)?;
+                        self.update_displayed();
+
+                        // the key may have been changed since the backup
+                        if self.key.as_ref().is_some_and(|key| !self.vault.verify_key(key)) {
+                            self.key = None;
+                        }
+                    }
+                    Err(e) => dialog::error(term, Some(self), e.to_string())?,
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn open_url(&mut self, term: &mut Terminal) -> Result<()> {
+        if let Some(selected_seed_index) = self.selected_seed_index() {
+            let selected_seed = self.seed_at(selected_seed_index);
+
+            match selected_seed.url.as_deref().and_then(shared::normalize_url) {
+                Some(url) => {
+                    if let Err(e) = open::that(&url) {
+                        dialog::error(term, Some(self), format!("Could not open '{url}': {e}"))?;
+                    }
+                }
+                None => dialog::error(
+                    term,
+                    Some(self),
+                    format!("Seed '{}' has no valid URL.", selected_seed.identifier),
+                )?,
+            }
+        }
+        Ok(())
+    }
+
+    fn stats(&mut self, term: &mut Terminal) -> Result<()> {
+        dialog::info(term, Some(self), self.vault.stats().to_string())
+    }
+
+    fn audit(&mut self, term: &mut Terminal) -> Result<()> {
+        let findings = self.vault.audit_with(&config::get().audit_thresholds());
+        let msg = if findings.is_empty() {
+            "No weak seeds found.".to_owned()
+        } else {
+            findings
+                .iter()
+                .map(|(seed_index, finding)| {
+                    format!("{}: {finding}", self.seed_at(*seed_index).identifier)
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+        dialog::info(term, Some(self), msg)
+    }
+
+    fn start_yank(&mut self, _: &mut Terminal) -> Result<()> {
+        self.yanking = self.selected_seed_index().is_some();
+        Ok(())
+    }
+
+    /// Copies the field of the selected seed picked by `target`, verifying the key if the password
+    /// is picked. Keys not in [`YANK_TARGETS`] cancel yank mode.
+    fn yank(&mut self, term: &mut Terminal, target: KeyCode) -> Result<()> {
+        self.yanking = false;
+
+        let seed_index = match self.selected_seed_index() {
+            Some(seed_index) => seed_index,
+            None => return Ok(()),
+        };
+        let value = match target {
+            KeyCode::Char('u') => self.seed_at(seed_index).username.clone().map(Zeroizing::new),
+            KeyCode::Char('l') => self.seed_at(seed_index).url.clone().map(Zeroizing::new),
+            KeyCode::Char('p') => match self.unlock(term)? {
+                Some(key) => {
+                    let password = self.vault.password_verified(self.seed_at(seed_index), &key)?;
+                    self.vault.mark_generated(seed_index)?;
+                    Some(password)
+                }
+                None => return Ok(()),
+            },
+            _ => return Ok(()),
         };
+
+        match value {
+            Some(value) => clipboard::copy(&value, false).map(drop),
+            None => {
+                let name = YANK_TARGETS
+                    .iter()
+                    .find(|(key, _)| target == KeyCode::Char(*key))
+                    .map_or("", |(_, name)| name);
+                let msg = format!("Seed '{}' has no {name}.", self.seed_at(seed_index).identifier);
+                dialog::error(term, Some(self), msg)
+            }
+        }
+    }
+
+    fn focus_filter(&mut self, _: &mut Terminal) -> Result<()> {
+        self.filter_focused = true;
+        Ok(())
+    }
+
+    fn help(&mut self, term: &mut Terminal) -> Result<()> {
+        let keymap = &config::get().keymap;
+        let keys: Vec<String> = BINDINGS
+            .iter()
+            .map(|(action, ..)| {
+                let keys: Vec<String> = keymap.keys(*action).iter().map(Key::to_string).collect();
+                format!("({})", keys.join(", "))
+            })
+            .collect();
+        let width = keys.iter().map(|key| key.chars().count()).max().unwrap_or(0);
+        let msg = keys
+            .iter()
+            .zip(BINDINGS)
+            .map(|(key, (_, description, _))| format!("{key:width$}  {description}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        dialog::scrollable(term, Some(self), "Help", msg)
+    }
+}
+
+impl State for VaultView {
+    fn update(&mut self, term: &mut Terminal, key: KeyCode, modifiers: KeyModifiers) -> Result<Status> {
+        self.handle_key(term, key, modifiers)
+    }
+
+    fn tick(&mut self) -> Result<Status> {
+        // dialogs opened by the view are cancelled when idle, returning here to lock the vault
+        if state::idle() {
+            self.key = None;
+        }
         Ok(Status::Running)
     }
+
+    fn cancel_when_idle(&self) -> bool {
+        false
+    }
     
     fn draw(&self, frame: &mut Frame) {
+        let theme = config::get().theme();
+        let border_style = theme.style(Style::default().fg(theme.border));
+        let compact = frame.size().width < COMPACT_WIDTH;
+        // in compact mode, the details of the selected seed are shown in the details box instead
+        let details_height = if compact { 8 } else { 5 };
+        let horizontal_margin = if compact { 1 } else { 3 };
         let layout = Layout::default()
-            .horizontal_margin(3)
+            .horizontal_margin(horizontal_margin)
             .vertical_margin(1)
-            .constraints([Constraint::Min(1), Constraint::Length(3)])
+            .constraints([
+                Constraint::Min(1),
+                Constraint::Length(details_height),
+                Constraint::Length(3),
+            ])
             .split(frame.size());
 
+        // scratch vaults are indicated in the top margin, since their changes are easily lost
+        if self.vault.is_scratch() && frame.size().height > 0 {
+            let keys: Vec<String> = config::get()
+                .keymap
+                .keys(Action::SaveAs)
+                .iter()
+                .map(Key::to_string)
+                .collect();
+            let title = format!(" SCRATCH VAULT: kept in memory only, ({}) saves it ", keys.join(", "));
+            let area = Rect {
+                height: 1,
+                ..frame.size().inner(&Margin { horizontal: horizontal_margin, vertical: 0 })
+            };
+            let style = theme.style(Style::default().fg(theme.highlight).add_modifier(Modifier::BOLD));
+            frame.render_widget(Paragraph::new(title).style(style), area);
+        }
+
         // draw the seed table
         {
-            let table_widget = Table::new(self.displayed.iter().map(|&seed_index| {
-                let Seed {
-                    identifier,
-                    length,
-                    salt,
-                    characters,
-                    username,
-                } = &self.vault.seeds()[seed_index];
-
-                Row::new(vec![
-                    identifier.clone(),
-                    length.to_string(),
-                    salt.to_string(),
-                    characters.to_string(),
-                    username.clone().unwrap_or_else(|| "None".to_owned()),
-                ])
-            }))
-                .header(
-                    Row::new(vec!["NAME", "LENGTH", "SALT", "SETS", "USERNAME"])
-                        .style(Style::default().add_modifier(Modifier::BOLD))
-                        .bottom_margin(1),
-                )
-                .widths(&[
-                    Constraint::Percentage(20),
-                    Constraint::Percentage(10),
-                    Constraint::Percentage(10),
-                    Constraint::Percentage(10),
-                    Constraint::Percentage(50),
-                ])
-                .highlight_style(
+            let table_widget = if compact {
+                // identifiers and usernames are fit to the single column besides the highlight
+                // symbol
+                let width = (layout[0].width as usize).saturating_sub(theme.marker().len());
+                let rows = self.displayed.iter().map(|&seed_index| {
+                    let seed = &self.vault.seeds()[seed_index];
+                    let line = match &seed.username {
+                        Some(username) => format!("{} — {username}", seed.identifier),
+                        None => seed.identifier.clone(),
+                    };
+                    Row::new(vec![utility::truncate(&line, width)])
+                });
+                Table::new(rows)
+                    .header(
+                        Row::new(vec!["NAME — USERNAME"])
+                            .style(theme.style(Style::default().add_modifier(Modifier::BOLD)))
+                            .bottom_margin(1),
+                    )
+                    .widths(&[Constraint::Percentage(100)])
+            } else {
+                // identifiers are fit to the name column, which takes a fifth of the table besides
+                // the highlight symbol and the spacing between columns
+                let name_width = (layout[0].width as usize)
+                    .saturating_sub(theme.marker().len())
+                    / 5
+                    - 1;
+                let rows = self.displayed.iter().map(|&seed_index| {
+                    let Seed {
+                        identifier,
+                        min_len,
+                        max_len,
+                        salt,
+                        characters,
+                        username,
+                        ..
+                    } = &self.vault.seeds()[seed_index];
+
+                    Row::new(vec![
+                        utility::truncate(identifier, name_width),
+                        format_length(*min_len, *max_len),
+                        salt.to_string(),
+                        characters.to_string(),
+                        username.clone().unwrap_or_else(|| "None".to_owned()),
+                    ])
+                });
+                Table::new(rows)
+                    .header(
+                        Row::new(vec!["NAME", "LENGTH", "SALT", "SETS", "USERNAME"])
+                            .style(theme.style(Style::default().add_modifier(Modifier::BOLD)))
+                            .bottom_margin(1),
+                    )
+                    .widths(&[
+                        Constraint::Percentage(20),
+                        Constraint::Percentage(10),
+                        Constraint::Percentage(10),
+                        Constraint::Percentage(10),
+                        Constraint::Percentage(50),
+                    ])
+            };
+            let table_widget = table_widget
+                .highlight_style(theme.style(
                     Style::default()
-                        .fg(Color::Cyan)
+                        .fg(theme.highlight)
                         .add_modifier(Modifier::BOLD),
-                );
+                ))
+                .highlight_symbol(theme.marker());
             frame.render_stateful_widget(table_widget, layout[0], &mut self.table_state.borrow_mut());
         }
 
+        // draw the details of the selected seed
+        {
+            let lines = match self.selected_seed_index().map(|i| self.seed_at(i)) {
+                Some(seed) => {
+                    let mut lines = vec![
+                        format!("Name:     {}", seed.identifier),
+                        format!("Username: {}", seed.username.as_deref().unwrap_or("None")),
+                        format!("URL:      {}", seed.url.as_deref().unwrap_or("None")),
+                    ];
+                    if compact {
+                        lines.extend([
+                            format!("Length:   {}", format_length(seed.min_len, seed.max_len)),
+                            format!("Salt:     {}", seed.salt),
+                            format!("Sets:     {}", seed.characters.to_string()),
+                        ]);
+                    }
+                    lines
+                }
+                None => vec![],
+            };
+            // in yank mode, the title lists the fields which may be copied instead
+            let (title, details_border_style) = if self.yanking {
+                let targets: Vec<String> = YANK_TARGETS
+                    .iter()
+                    .map(|(key, name)| format!("({key}) {name}"))
+                    .collect();
+                let title = format!(" COPY: {}, other key cancels ", targets.join(", "));
+                (title, theme.style(Style::default().fg(theme.highlight)))
+            } else if self.key.is_none() {
+                (" DETAILS (locked) ".to_owned(), border_style)
+            } else {
+                (" DETAILS ".to_owned(), border_style)
+            };
+            let widget = Paragraph::new(lines.join("\n"))
+                .block(
+                    Block::default()
+                        .title(title)
+                        .borders(Borders::ALL)
+                        .border_style(details_border_style),
+                );
+            frame.render_widget(widget, layout[1]);
+        }
+
         // draw the filter input box
         {
+            let filter_border_style = if self.filter_focused {
+                theme.style(Style::default().fg(theme.highlight))
+            } else {
+                border_style
+            };
             let widget = Paragraph::new(self.filter.format(true))
-                .block(Block::default().title(" FILTER ").borders(Borders::ALL));
-            frame.render_widget(widget, layout[1]);
+                .block(
+                    Block::default()
+                        .title(" FILTER ")
+                        .borders(Borders::ALL)
+                        .border_style(filter_border_style),
+                );
+            frame.render_widget(widget, layout[2]);
         }
     }
 }
 
+/// Formats the length range of a seed, e.g. `16` or `12-20`.
+fn format_length(min_len: u8, max_len: u8) -> String {
+    if min_len == max_len {
+        min_len.to_string()
+    } else {
+        format!("{min_len}-{max_len}")
+    }
+}
+
 fn hash_vault(vault: &Vault) -> u64 {
     let mut hasher = DefaultHasher::new();
     vault.hash(&mut hasher);
@@ -251,15 +1081,34 @@ fn filter_seeds(seeds: &[Seed], filter: &str) -> (Vec<usize>, Option<usize>) {
         let mut scores: Vec<(usize, isize)> = seeds
             .iter()
             .enumerate()
-            .filter_map(|(i, seed)| {
-                sublime_fuzzy::best_match(filter, &seed.identifier).map(|m| (i, m.score()))
-            })
+            .filter_map(|(i, seed)| seed.match_score(filter).map(|score| (i, score)))
             .collect();
 
-        // sort pairs such that the highest match score is first, and return the indexes
-        scores.sort_by(|(_, a), (_, b)| b.cmp(a));
+        // sort pairs such that the highest match score is first, and return the indexes. ties are
+        // broken by the original index, such that equally matching seeds don't jump around between
+        // keystrokes
+        scores.sort_by_key(|&(i, score)| (Reverse(score), i));
         scores.into_iter().map(|(i, _)| i).collect()
     };
     let default_row = if filtered.is_empty() { None } else { Some(0) };
     (filtered, default_row)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filter_seeds_ties() {
+        let seeds: Vec<Seed> = ["mail-b", "mail-a", "other", "mail-c"]
+            .into_iter()
+            .map(|identifier| Seed::basic(identifier.to_owned(), None))
+            .collect();
+
+        for _ in 0..3 {
+            let (displayed, default_row) = filter_seeds(&seeds, "mail");
+            assert_eq!(displayed, [0, 1, 3]);
+            assert_eq!(default_row, Some(0));
+        }
+    }
+}
@@ -0,0 +1,238 @@
+//! Contains the audit flagging [Seeds](Seed) which produce weak passwords.
+
+use std::{
+    fmt,
+    time::{Duration, SystemTime},
+};
+
+use crate::seed::{Encoding, Seed, SeedKind};
+
+const SECS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Specifies the limits below which a [Seed] is flagged by an audit.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuditThresholds {
+    /// Minimum estimated entropy in bits, see [`Seed::entropy_bits`].
+    pub min_entropy_bits: f64,
+    /// Minimum password length.
+    pub min_length: u8,
+    /// Minimum number of character sets.
+    pub min_sets: usize,
+    /// Maximum time since the password was last generated, see [`Seed::last_generated`]. Seeds
+    /// never generated are not flagged. If [None], no seed is flagged.
+    pub max_age: Option<Duration>,
+}
+
+impl Default for AuditThresholds {
+    fn default() -> Self {
+        AuditThresholds {
+            min_entropy_bits: 64.0,
+            min_length: 8,
+            min_sets: 2,
+            max_age: Some(Duration::from_secs(365 * SECS_PER_DAY)),
+        }
+    }
+}
+
+/// Defines how severe an [`AuditFinding`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// Not a weakness, but a hint. Never fails an audit.
+    Info,
+    Low,
+    Medium,
+    High,
+}
+
+/// Describes why a [Seed] was flagged by an audit.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AuditFinding {
+    /// The estimated entropy in bits is below the threshold.
+    LowEntropy(f64),
+    /// The minimum length is below the threshold.
+    ShortLength(u8),
+    /// Fewer character sets than the threshold are used.
+    FewSets(usize),
+    /// The password was last generated longer ago than the threshold, contained in whole days.
+    /// Passwords of services used rarely are likely not rotated either.
+    Stale(u64),
+    /// All parameters are left at one of the presets, see [`Seed::uses_default_parameters`].
+    DefaultParameters,
+    /// The same passwords are generated as by an earlier seed, whose identifier is contained. See
+    /// [`collisions`].
+    Collision(String),
+}
+
+impl AuditFinding {
+    pub fn severity(&self) -> Severity {
+        match self {
+            AuditFinding::LowEntropy(_)  => Severity::High,
+            AuditFinding::ShortLength(_) => Severity::Medium,
+            AuditFinding::FewSets(_)     => Severity::Low,
+            AuditFinding::Stale(_)       => Severity::Low,
+            AuditFinding::DefaultParameters => Severity::Info,
+            AuditFinding::Collision(_)   => Severity::High,
+        }
+    }
+}
+
+impl fmt::Display for AuditFinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuditFinding::LowEntropy(bits) => write!(f, "low entropy ({bits:.0} bits)"),
+            AuditFinding::ShortLength(len) => write!(f, "short length ({len} characters)"),
+            AuditFinding::FewSets(1)       => write!(f, "only one character set"),
+            AuditFinding::FewSets(count)   => write!(f, "only {count} character sets"),
+            AuditFinding::Stale(days)      => write!(f, "not generated in {days} days; consider rotating"),
+            AuditFinding::DefaultParameters => {
+                write!(f, "default parameters; consider customizing to the service's requirements")
+            }
+            AuditFinding::Collision(other) => {
+                write!(f, "same password as '{other}'; change the salt of either seed")
+            }
+        }
+    }
+}
+
+/// Audits seeds against the given thresholds, returning each finding paired with the index of the
+/// flagged seed. A seed may be flagged multiple times.
+pub fn audit(seeds: &[Seed], thresholds: &AuditThresholds) -> Vec<(usize, AuditFinding)> {
+    audit_at(seeds, thresholds, SystemTime::now())
+}
+
+/// Audits seeds like [`audit`], measuring the age of passwords up to `now`.
+fn audit_at(seeds: &[Seed], thresholds: &AuditThresholds, now: SystemTime) -> Vec<(usize, AuditFinding)> {
+    let mut findings = Vec::new();
+
+    for (i, seed) in seeds.iter().enumerate() {
+        let entropy = seed.entropy_bits();
+        let set_count = seed.characters.get().len() + seed.custom_set.is_some() as usize;
+
+        if entropy < thresholds.min_entropy_bits {
+            findings.push((i, AuditFinding::LowEntropy(entropy)));
+        }
+        // passphrases ignore the length and character sets of their seeds
+        let password = seed.kind == SeedKind::Password;
+
+        if password && seed.min_len < thresholds.min_length {
+            findings.push((i, AuditFinding::ShortLength(seed.min_len)));
+        }
+        // seeds with a fixed alphabet ignore their character sets
+        if password && seed.encoding == Encoding::CharsetTable && set_count < thresholds.min_sets {
+            findings.push((i, AuditFinding::FewSets(set_count)));
+        }
+        // a clock set back yields no age, rather than flagging the seed
+        let age = seed.last_generated.and_then(|time| now.duration_since(time).ok());
+        if let (Some(age), Some(max_age)) = (age, thresholds.max_age) {
+            if age > max_age {
+                findings.push((i, AuditFinding::Stale(age.as_secs() / SECS_PER_DAY)));
+            }
+        }
+        if seed.uses_default_parameters() {
+            findings.push((i, AuditFinding::DefaultParameters));
+        }
+    }
+    // collisions are flagged on the later seed only, which is then the one to change
+    for (i, j) in collisions(seeds) {
+        findings.push((j, AuditFinding::Collision(seeds[i].identifier.clone())));
+    }
+    findings.sort_by_key(|&(i, _)| i);
+    findings
+}
+
+/// Returns the index pairs of seeds which generate the same passwords, see
+/// [`Seed::generates_like`]. The first index of each pair is the lower.
+pub fn collisions(seeds: &[Seed]) -> Vec<(usize, usize)> {
+    let mut pairs = Vec::new();
+
+    for (i, seed) in seeds.iter().enumerate() {
+        for (j, other) in seeds.iter().enumerate().skip(i + 1) {
+            if seed.generates_like(other) {
+                pairs.push((i, j));
+            }
+        }
+    }
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn audit_findings() {
+        let mut strong = Seed::advanced("strong".to_owned(), None);
+        strong.salt = 1;
+        let seeds = [strong, Seed::pin("pin".to_owned(), None, 4)];
+        let findings = audit(&seeds, &AuditThresholds::default());
+
+        assert!(findings.iter().all(|&(i, _)| i == 1));
+        assert!(matches!(findings[0], (1, AuditFinding::LowEntropy(_))));
+        assert_eq!(findings[1], (1, AuditFinding::ShortLength(4)));
+        assert_eq!(findings[2], (1, AuditFinding::FewSets(1)));
+    }
+
+    #[test]
+    fn audit_thresholds() {
+        let seeds = [Seed::pin("pin".to_owned(), None, 4)];
+        let thresholds = AuditThresholds {
+            min_entropy_bits: 0.0,
+            min_length: 0,
+            min_sets: 0,
+            max_age: None,
+        };
+        assert!(audit(&seeds, &thresholds).is_empty());
+    }
+
+    #[test]
+    fn audit_default_parameters() {
+        let seeds = [Seed::medium("default".to_owned(), None)];
+        let findings = audit(&seeds, &AuditThresholds::default());
+
+        assert_eq!(findings, [(0, AuditFinding::DefaultParameters)]);
+        assert_eq!(findings[0].1.severity(), Severity::Info);
+    }
+
+    #[test]
+    fn audit_stale() {
+        let now = SystemTime::now();
+        let day = Duration::from_secs(SECS_PER_DAY);
+        let mut seeds = [
+            Seed::advanced("stale".to_owned(), None),
+            Seed::advanced("fresh".to_owned(), None),
+            Seed::advanced("never".to_owned(), None),
+        ];
+        for (i, seed) in seeds.iter_mut().enumerate() {
+            seed.salt = i as u64 + 1;
+        }
+        seeds[0].last_generated = Some(now - day * 400);
+        seeds[1].last_generated = Some(now - day * 10);
+
+        let findings = audit_at(&seeds, &AuditThresholds::default(), now);
+        assert_eq!(findings, [(0, AuditFinding::Stale(400))]);
+
+        let thresholds = AuditThresholds { max_age: None, ..AuditThresholds::default() };
+        assert!(audit_at(&seeds, &thresholds, now).is_empty());
+    }
+
+    #[test]
+    fn audit_collisions() {
+        let mut seeds = [
+            Seed::advanced("a".to_owned(), None),
+            Seed::advanced("b".to_owned(), None),
+            Seed::advanced("c".to_owned(), None),
+        ];
+        for seed in &mut seeds {
+            seed.gen_tag = "tag".to_owned();
+            seed.salt = 1;
+        }
+        seeds[1].salt = 2;
+        seeds[2].username = Some("user".to_owned());
+
+        assert_eq!(collisions(&seeds), [(0, 2)]);
+        assert_eq!(
+            audit(&seeds, &AuditThresholds::default()),
+            [(2, AuditFinding::Collision("a".to_owned()))]
+        );
+    }
+}
@@ -1,374 +1,1063 @@
 //! Defines all generative algorithms used.
 
-use std::iter;
+use std::{fmt, iter, sync::OnceLock};
 
 use argon2;
-use rand::Rng;
-
-use crate::seed::*;
-
-struct PasswordTable {
-    target_len: usize,
-    sets: Vec<&'static[u8]>,
-    rows: Vec<Vec<(usize, u8)>>,
-}
-
-impl PasswordTable {
-    fn new(target_len: u8, sets: Vec<&'static [u8]>, digest: &[u8]) -> PasswordTable {
-        let target_len = target_len as usize;
-        let char_count = sets.iter().map(|set| set.len()).sum::<usize>();
-        let mut rows = vec![vec![]; sets.len()];
-
-        
-        
-        for (i, chunk) in digest.chunks_exact(2).enumerate() {
-            if let &[set_seed, char_seed] = chunk {
-                let set_idx = {
-                    let mut i = set_seed as usize % char_count;
-                    let mut set_index = 0;
-                    
-                    for set in &sets {
-                        if i >= set.len() {
-                            i -= set.len();
-                            set_index += 1;
-                        } else {
-                            break;
-                        }
-                    };
-                    set_index
-                };
-                rows[set_idx].push((i, char_seed));
-            } else {
-                unreachable!()
-            }
-        };
-        PasswordTable {
-            target_len,
-            sets,
-            rows,
+use data_encoding::{BASE32_NOPAD, HEXLOWER};
+use rand::{Rng, RngCore};
+use serde::{Deserialize, Serialize};
+use unicode_normalization::UnicodeNormalization;
+use zeroize::Zeroizing;
+
+use crate::{seed::*, Error, Result};
+
+mod table;
+
+use table::PasswordTable;
+
+/// Words picked by [passphrases](SeedKind::Passphrase), one per line. Any change alters every
+/// passphrase, so the list is frozen.
+const WORDLIST: &str = include_str!("generate/words.txt");
+
+/// Pairs of digest bytes at or above this value are skipped when picking words, such that every
+/// word of the [list](words) is equally likely. The list is 7776 words long.
+const WORD_PICK_LIMIT: usize = 65536 - 65536 % 7776;
+
+/// Pairs of digest bytes appended to the digest of passphrases, to replace those skipped when
+/// picking words.
+const SPARE_PAIRS: usize = 8;
+
+/// Gets the words picked by [passphrases](SeedKind::Passphrase), split from the list once.
+pub fn words() -> &'static [&'static str] {
+    static WORDS: OnceLock<Vec<&'static str>> = OnceLock::new();
+    WORDS.get_or_init(|| WORDLIST.lines().collect())
+}
+
+/// Exposes internal steps of password generation, such that their behaviour may be verified
+/// directly rather than through generated passwords. Only available with the `testing` feature,
+/// since the steps are subject to change.
+#[cfg(feature = "testing")]
+pub mod testing {
+    use super::*;
+
+    pub use super::table::PasswordTable;
+
+    /// Computes the digest from which a password is derived. See [`password_with`].
+    pub fn digest(
+        key: &str,
+        pepper: &[u8],
+        seed: &Seed,
+        normalization: Normalization,
+        variant: Argon2Variant,
+    ) -> Result<Zeroizing<Vec<u8>>> {
+        super::digest(key, pepper, seed, normalization, variant)
+    }
+}
+
+/// Validates a seed and derives its password, which is then [checked](check) against the
+/// requirements of the seed. This is the stable entry point for library users, requiring no
+/// [Vault](crate::Vault); the parameters are as in [`password`], whose outputs it matches.
+///
+/// # Errors
+/// * [`Error::InvalidSeed`] if the seed cannot produce a password. See [`validate`].
+/// * [`Error::Hashing`] if argon2 rejects the [cost parameters](Seed::argon2) of the seed.
+/// * [`Error::UnmetRequirements`] if the password does not meet the requirements of the seed.
+pub fn derive(key: &str, pepper: &[u8], seed: &Seed) -> Result<Zeroizing<String>> {
+    derive_with(key, pepper, seed, Normalization::None, Argon2Variant::Argon2d)
+}
+
+/// Validates and derives a password like [`derive`], with the normalization and argon2 variant of
+/// [`password_with`]. A [Vault](crate::Vault) derives its passwords with its
+/// [normalization](crate::Vault::normalization) and [variant](crate::Vault::argon2_variant), so
+/// these must match it to reproduce them.
+///
+/// # Errors
+/// As [`derive`].
+pub fn derive_with(
+    key: &str,
+    pepper: &[u8],
+    seed: &Seed,
+    normalization: Normalization,
+    variant: Argon2Variant,
+) -> Result<Zeroizing<String>> {
+    validate(seed)?;
+    let password = password_with(key, pepper, seed, normalization, variant)?;
+    check(&password, seed)?;
+    Ok(password)
+}
+
+/// Checks that a seed can produce a password:
+///
+/// * Its minimum length is at least 1 and does not exceed its maximum length.
+/// * It selects at least one character set, unless its [encoding](Encoding) has a fixed alphabet.
+/// * Its [exclusions](Seed::exclude) leave at least one character.
+/// * Its [custom set](Seed::custom_set), if any, is non-empty printable ASCII without duplicates.
+///
+/// Passphrases are instead checked to have at least one word, and a separator without letters.
+///
+/// # Errors
+/// * [`Error::InvalidSeed`] describing the first violation.
+pub fn validate(seed: &Seed) -> Result<()> {
+    let fail = |reason: &str| Err(Error::InvalidSeed(seed.identifier.clone(), reason.to_owned()));
+
+    if let SeedKind::Passphrase { words, separator, .. } = &seed.kind {
+        if *words == 0 {
+            return fail("passphrase must have at least one word");
         }
+        if separator.chars().any(char::is_alphabetic) {
+            return fail("passphrase separator must not contain letters");
+        }
+        return Ok(());
     }
 
-    fn balance(mut self) -> Self {
-        let min_freq = 2.min(self.target_len as usize / self.sets.len());
-        let compensations: Vec<usize> = self.rows.iter()
-            .enumerate()
-            .filter_map(|(i, row)| {
-                if row.len() < min_freq {
-                    Some((i, min_freq - row.len()))
-                } else {
-                    None
-                }
-            })
-            .flat_map(|(i, needed)| iter::repeat(i).take(needed))
-            .collect();
-        
-        for i in compensations {
-            let cell = self.rows.iter_mut()
-                .max_by(|a, b| a.len().cmp(&b.len()))
-                .and_then(|row| row.pop())
-                .unwrap();
-            self.rows[i].push(cell);
+    if seed.min_len == 0 {
+        return fail("min length must be at least 1");
+    }
+    if seed.min_len > seed.max_len {
+        return fail("min length exceeds max length");
+    }
+    if let Some(set) = &seed.custom_set {
+        if set.is_empty() {
+            return fail("custom set is empty");
+        }
+        if !set.bytes().all(|c| c.is_ascii_graphic() || c == b' ') {
+            return fail("custom set must be printable ASCII");
         }
-        for row in &self.rows {
-            debug_assert!(row.len() >= min_freq);
+        if set.bytes().enumerate().any(|(i, c)| set.as_bytes()[..i].contains(&c)) {
+            return fail("custom set contains duplicate characters");
+        }
+    }
+    if seed.sets().is_empty() {
+        return if seed.characters.is_empty() && seed.custom_set.is_none() {
+            fail("no character set is selected")
+        } else {
+            fail("every character is excluded")
         };
-        self
     }
+    Ok(())
+}
 
-    fn build(self) -> String {
-        todo!()
+/// Specifies how the key and [generation tags](Seed::gen_tag) are normalized before hashing.
+///
+/// Text which looks the same may be encoded differently depending on the system or keyboard it was
+/// typed on, e.g. `é` as one code point or as `e` followed by a combining accent. Normalizing it
+/// ensures that such text produces the same passwords everywhere.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Normalization {
+    /// Hashes text as is. Used by vaults created before normalization was introduced, since
+    /// normalizing would change their passwords if their key or tags contain non-ASCII characters.
+    #[default]
+    None,
+    /// Normalizes text to Unicode normalization form C.
+    Nfc,
+}
+
+impl Normalization {
+    /// Applies the normalization to text. The result is cleared from memory once dropped, since it
+    /// may be the key.
+    pub fn apply(self, text: &str) -> Zeroizing<String> {
+        match self {
+            Normalization::None => Zeroizing::new(text.to_owned()),
+            Normalization::Nfc => Zeroizing::new(text.nfc().collect()),
+        }
     }
 }
 
-/// Derives a password using the given parameters.
+impl fmt::Display for Normalization {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Normalization::None => write!(f, "none"),
+            Normalization::Nfc => write!(f, "NFC"),
+        }
+    }
+}
+
+/// Specifies the variant of [argon2] used to hash passwords. The variant is part of the output, so
+/// switching it changes every password of a vault.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Argon2Variant {
+    /// Maximizes resistance to GPU cracking, but is more vulnerable to side-channel attacks. Used
+    /// by vaults created before the variant could be chosen.
+    #[default]
+    Argon2d,
+    /// Hybrid of argon2d and argon2i, resisting both side-channel attacks and GPU cracking.
+    Argon2id,
+}
+
+impl Argon2Variant {
+    pub const ALL: [Argon2Variant; 2] = [Argon2Variant::Argon2d, Argon2Variant::Argon2id];
+
+    /// Gets the name of the variant, as stored in the [header](crate::header::Header) of vaults.
+    pub fn name(self) -> &'static str {
+        match self {
+            Argon2Variant::Argon2d  => "argon2d",
+            Argon2Variant::Argon2id => "argon2id",
+        }
+    }
+
+    fn to_argon2(self) -> argon2::Variant {
+        match self {
+            Argon2Variant::Argon2d  => argon2::Variant::Argon2d,
+            Argon2Variant::Argon2id => argon2::Variant::Argon2id,
+        }
+    }
+}
+
+impl fmt::Display for Argon2Variant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Derives a password with the key and generation tag hashed [as is](Normalization::None), with
+/// [argon2d](Argon2Variant::Argon2d). These are the parameters of vaults created before either
+/// could be chosen, and its outputs are therefore kept unchanged. See [`password_with`].
+pub fn password(key: &str, pepper: &[u8], seed: &Seed) -> Result<Zeroizing<String>> {
+    password_with(key, pepper, seed, Normalization::None, Argon2Variant::Argon2d)
+}
+
+/// Derives a password using the given parameters. The parameters are not validated; see [`derive`].
 ///
 /// * `key` - Specific to the user, essentially equivalent to a master password.
 /// * `pepper` - Specific to the [Vault](crate::Vault).
 /// * `seed` - Specific to the password.
+/// * `normalization` - Applied to the key and generation tag. Should be [`Normalization::Nfc`]
+///   unless compatibility with vaults created before normalization is needed.
+/// * `variant` - Variant of argon2 used in step 2, specific to the [Vault](crate::Vault).
+///
+/// The password is cleared from memory once dropped, as is the digest it is derived from. Copies
+/// made by the caller, e.g. to display the password, are not; neither is the key, which remains
+/// the responsibility of the caller.
 ///
 /// # Algorithm overview
 ///
-/// 1. Concatenate the key and the seed identifier.
-/// 2. Hash using [argon2d](argon2) with the following parameters:
+/// 1. Concatenate the normalized key and [generation tag](Seed::gen_tag) of the seed, followed by
+///    a null character and the [variant](Seed::variant) unless it is 0.
+/// 2. Hash using [argon2](argon2) of the given `variant` with the following parameters:
 ///     * secret: `pepper`,
 ///     * salt: `seed.salt`,
-///     * output length: `u32::max(seed.length, 4)`.
-/// 3.
-pub fn password(key: &str, pepper: &[u8], seed: &Seed) -> String {
-    let target_len = seed.length as usize;
-    let digest = {
-        use argon2::*;
-
-        let mut config = Config::default();
-        config.hash_length = 4.max(target_len * 2) as u32;
-        config.secret = pepper;
-        config.variant = Variant::Argon2d;
-
-        let data = format!("{}{}", key, seed.identifier);
-        hash(&data, &seed.salt.to_be_bytes(), config)
+///     * output length: `u32::max(2 * seed.max_len, 4)`, plus 1 if `seed.min_len < seed.max_len`,
+///     * cost: [`Seed::argon2`] if overridden, and otherwise the [defaults](Argon2Params::default).
+/// 3. If the seed is a [passphrase](SeedKind::Passphrase), pick each word from the [list](words)
+///    by the next pair of bytes of the digest, skipping pairs whose value is 62208 or above to
+///    avoid bias, and join the words with the separator. Steps 4 to 6 are then skipped, and the
+///    output length in step 2 is `2 * (words + 8)`. If the passphrase is
+///    [hybrid](SeedKind::Passphrase::hybrid), the output is a further pair of bytes per word
+///    longer, and the first byte of the pair of each word picks its digit and the second its
+///    symbol.
+/// 4. Pick the target length within `seed.min_len..=seed.max_len` with the extra last byte of the
+///    digest, if the seed has a length range.
+/// 5. If the seed uses an [encoding](Encoding) with a fixed alphabet, encode the digest with it and
+///    truncate to the target length. The character sets of the seed are then ignored.
+/// 6. Otherwise, split the first `2 * target_len` bytes of the digest into pairs, one per position
+///    of the password, and distribute them among the character sets in a [`PasswordTable`]. The
+///    table is balanced such that each set is represented, after which the second byte of each
+///    pair picks the character at its position.
+///
+/// # Errors
+/// * [`Error::Hashing`] if argon2 rejects the [cost parameters](Seed::argon2) of the seed.
+pub fn password_with(
+    key: &str,
+    pepper: &[u8],
+    seed: &Seed,
+    normalization: Normalization,
+    variant: Argon2Variant,
+) -> Result<Zeroizing<String>> {
+    let digest = digest(key, pepper, seed, normalization, variant)?;
+
+    if let SeedKind::Passphrase { words, separator, .. } = &seed.kind {
+        let words = *words as usize;
+        let (pairs, decorations) = digest.split_at(2 * (words + SPARE_PAIRS));
+        let hybrid = HybridSets::of(seed);
+        let mut passphrase = Zeroizing::new(String::new());
+
+        for (i, word) in pick_words(pairs, words).into_iter().enumerate() {
+            if i > 0 {
+                passphrase.push_str(separator);
+            }
+            match &hybrid {
+                Some(hybrid) => hybrid.push(&mut passphrase, word, &decorations[2 * i..2 * i + 2]),
+                None => passphrase.push_str(word),
+            }
+        }
+        return Ok(passphrase);
+    }
+    let target_len = target_len(seed, &digest);
+
+    // the digest is twice the target length in bytes, so every encoding yields enough characters
+    let encoded = match seed.encoding {
+        Encoding::CharsetTable => None,
+        Encoding::Hex          => Some(Zeroizing::new(HEXLOWER.encode(&digest))),
+        Encoding::Base32       => Some(Zeroizing::new(BASE32_NOPAD.encode(&digest))),
+        Encoding::Base58       => Some(Zeroizing::new(base58(&digest))),
     };
+    if let Some(encoded) = encoded {
+        return Ok(Zeroizing::new(encoded.chars().take(target_len).collect()));
+    }
+
+    let table = PasswordTable::new(target_len as u8, seed.sets(), &digest[..target_len * 2]);
+    Ok(Zeroizing::new(table.balance().build()))
+}
+
+/// Checks that a password generated from a seed meets the requirements stated by it:
+///
+/// * Its length is within `seed.min_len..=seed.max_len`.
+/// * Each of its characters belongs to a [set](Seed::sets) of the seed, such that excluded
+///   characters never appear.
+/// * Each set is represented by the minimum number of characters guaranteed by the
+///   [`PasswordTable`]: two, or one if the password is too short to fit two of every set, or none
+///   if it cannot even fit one.
+///
+/// Passphrases are instead checked to consist of the number of words of the seed, each from the
+/// [list](words). Those which are [hybrid](SeedKind::Passphrase::hybrid) are also checked to
+/// capitalize their words as the upper case set allows, and to hold a digit and a symbol per word
+/// if any are selected.
+///
+/// # Errors
+/// * [`Error::UnmetRequirements`] describing the first requirement violated.
+pub fn check(password: &str, seed: &Seed) -> Result<()> {
+    let fail = |requirement: String| Err(Error::UnmetRequirements(seed.identifier.clone(), requirement));
+
+    if let SeedKind::Passphrase { words, separator, .. } = &seed.kind {
+        let valid = if let Some(hybrid) = HybridSets::of(seed) {
+            hybrid.check(password, *words as usize, separator)
+        } else if separator.is_empty() {
+            // words without a separator cannot be told apart, so only their letters are checked
+            password.chars().all(|c| c.is_ascii_lowercase())
+        } else {
+            let list = self::words();
+            let parts: Vec<&str> = password.split(separator.as_str()).collect();
+            parts.len() == *words as usize && parts.iter().all(|word| list.contains(word))
+        };
+        if !valid {
+            return fail(format!("passphrase is not {words} words of the word list"));
+        }
+        return Ok(());
+    }
+    let sets = seed.sets();
+    let len = password.chars().count();
+
+    if len < seed.min_len as usize || len > seed.max_len as usize {
+        return fail(format!("length {len} is outside {}..={}", seed.min_len, seed.max_len));
+    }
+    if let Some(c) = password.bytes().find(|c| !sets.iter().any(|set| set.contains(c))) {
+        return fail(format!("character {:?} is not in any selected set", c as char));
+    }
+    let min_freq = 2.min(len / sets.len().max(1));
+    let scarce = sets
+        .iter()
+        .position(|set| password.bytes().filter(|c| set.contains(c)).count() < min_freq);
+
+    if let Some(i) = scarce {
+        let set = String::from_utf8_lossy(&sets[i]);
+        return fail(format!("fewer than {min_freq} characters of set {set:?}"));
+    }
+    Ok(())
+}
+
+/// Estimates the entropy in bits of the passwords generated from a seed, as a measure of their
+/// strength. Assumes the minimum length of the seed, and that passwords are picked uniformly among
+/// those of the combined alphabet of its [sets](Seed::sets) which meet the guaranteed minimum of
+/// each set, see step 6 of [`password_with`]. The guarantee lowers the entropy slightly below
+/// `length * log2(alphabet_size)`.
+///
+/// The words of passphrases are likewise assumed to be picked uniformly from the [list](words),
+/// as are the digits and symbols of [hybrid](SeedKind::Passphrase::hybrid) ones from their sets.
+pub fn entropy(seed: &Seed) -> f64 {
+    if let SeedKind::Passphrase { words, .. } = seed.kind {
+        let per_word = match HybridSets::of(seed) {
+            Some(hybrid) => hybrid.digits.len().max(1) * hybrid.symbols.len().max(1),
+            None => 1,
+        };
+        return words as f64 * (self::words().len() as f64 * per_word as f64).log2();
+    }
+    let sets = seed.sets();
+    let alphabet_size: usize = sets.iter().map(|set| set.len()).sum();
+
+    if alphabet_size == 0 {
+        return 0.0;
+    }
+    let len = seed.min_len as usize;
+    let min_freq = 2.min(len / sets.len());
+    let shares: Vec<f64> = sets.iter().map(|set| set.len() as f64 / alphabet_size as f64).collect();
+
+    len as f64 * (alphabet_size as f64).log2() + balanced_share(&shares, len, min_freq).log2()
+}
+
+/// Computes the share of the strings of length `len` over a combined alphabet which contain at
+/// least `min_freq` characters of each set, given the share of the alphabet of each set.
+fn balanced_share(shares: &[f64], len: usize, min_freq: usize) -> f64 {
+    // the probability of each tally of characters per set, counted up to the minimum frequency
+    // and encoded as the digits of a number in base `min_freq + 1`
+    let base = min_freq + 1;
+    let tallies = base.pow(shares.len() as u32);
+    let mut probs = vec![0.0; tallies];
+    probs[0] = 1.0;
+
+    for _ in 0..len {
+        let mut next = vec![0.0; tallies];
+
+        for (tally, &prob) in probs.iter().enumerate() {
+            let mut place = 1;
+
+            for &share in shares {
+                let counted = tally / place % base < min_freq;
+                next[if counted { tally + place } else { tally }] += prob * share;
+                place *= base;
+            }
+        }
+        probs = next;
+    }
+    // the tally where every set has reached the minimum frequency
+    probs[tallies - 1]
+}
+
+/// Encodes bytes as base58 using the Bitcoin alphabet. Leading zero bytes are encoded as leading
+/// `1`s.
+fn base58(bytes: &[u8]) -> String {
+    let alphabet = Encoding::Base58.alphabet().unwrap();
+    // little-endian base58 digits of the big-endian number formed by the bytes
+    let mut digits: Vec<u8> = Vec::new();
 
-    PasswordTable::new(seed.length, seed.characters.get(), &digest)
-        .balance()
-        .build()
-    
-    // let mut seed_table = digest.chunks_exact(3).map(|chunk| {
-    //     if let &[set_seed, char_seed, shuffle_seed] = chunk {
-    //         let set_idx = get_set_idx(set_seed as usize % domain_char_count);
-    //         let shuffle_idx = shuffle_seed as usize % target_len;
-    //         (set_idx, char_seed, shuffle_idx)
-    //     } else {
-    //         unreachable!()
-    //     }
-    // });
-    
-    // let char_seeds = digest.chunks_exact(3).map(|chunk| {
-    //     if let &[set_seed, char_seed, pos_seed] = chunk {
-    //         let set_idx = get_set_idx(set_seed as usize);
-
-    //         todo!()
-    //         // (outer_seed, inner_seed, pos_seed)
-    //     } else {
-    //         unreachable!()
-    //     }
-    // });
-
-    // let min_set_freq = 2.min(target_len / sets.len());
-
-
-    // let (mut password, freq_table) = {
-    //     let mut freq_table = vec![0_u8; sets.len()];
-    //     let mut password = Vec::with_capacity(target_len);
-    //     let char_count: usize = sets.iter()
-    //         .map(|s| s.len())
-    //         .sum();
-
-    //     for i in 0..target_len {
-    //         let index = digest[i] as usize % char_count;
-    //         let (set_idx, char_idx) = split_index(index);
-    //         freq_table[set_idx] += 1;
-    //         password.push(sets[set_idx][char_idx]);
-    //     }
-    //     (password, freq_table)
-    // };
-
-    // let freq_comps = freq_table.iter()
-    //     .enumerate()
-    //     .filter_map(|(set, &freq)| {
-    //         let needed = min_set_freq
-    //             .checked_sub(freq as usize)
-    //             .unwrap_or(0);
-
-    //         if needed == 0 {
-    //             None
-    //         } else {
-    //             Some((set, needed))
-    //         }
-    //     })
-    //     .flat_map(|(set, needed)| iter::repeat(set).take(needed));
-
-    // for (set_idx, digest_data) in freq_comps.zip(digest.rchunks_exact(2)) {
-    //     if let &[char_seed, insert_seed] = digest_data {
-    //         let set = sets[set_idx];
-    //         let char_idx = char_seed as usize % set.len();
-    //         let insert_idx = insert_seed as usize % target_len;
-
-    //         password[insert]
-
-    //         let char_idx = char_seed as usize % target_len;
-    //         let insert_idx = insert_seed as usize % target_len;
-    //         password[insert_idx] = sets[set][set_char_idx];
-    //     } else {
-    //         unreachable!()
-    //     }
-    // }
-
-    // let sets = seed.characters.get();
-    // let digest = {
-    //     let needed_bytes = seed.length + sets.len() as u32 * 2; // extra bytes to ensure adequate set representation
-
-    //     let mut config = argon2::Config::default();
-    //     config.hash_length = needed_bytes.max(4); // argon2 requires at least 4 bytes output
-    //     config.secret = pepper;
-    //     config.variant = argon2::Variant::Argon2d;
-
-    //     let data = format!("{}{}", key, seed.identifier);
-
-    //     self::hash(&data, &seed.salt.to_be_bytes(), config)
-    // };
-
-    // let target_len = seed.length as usize;
-
-    // // utility to split index to (set_index, offset)
-    // let split_index = |i: usize| -> (usize, usize) {
-    //     let mut offset = i;
-    //     let mut set_index = 0;
-
-    //     for set in &sets {
-    //         if offset >= set.len() {
-    //             offset -= set.len();
-    //             set_index += 1;
-    //         } else {
-    //             break;
-    //         }
-    //     };
-    //     (set_index, offset)
-    // };
-
-    // // build the seed table from the first half of the digest
-    // let seed_table = {
-    //     let mut table = vec![vec![]; sets.len()];
-    //     let total_set_len: usize = sets.iter()
-    //         .map(|s| s.len())
-    //         .sum();
-
-    //     // build initial table
-    //     for i in 0..target_len {
-    //         let seed = digest[i] as usize % total_set_len;
-    //         let (set_index, offset) = split_index(seed);
-    //         table[set_index].push((offset, seed));
-    //     };
-
-    //     // determine which sets are underrepresented and by how much
-    //     let min_freq = 2.min(target_len / sets.len());
-    //     let freq_comps: Vec<usize> = table.iter()
-    //         .map(|set| {
-    //             let freq = set.len();
-    //             min_freq.max(freq) - freq
-    //         })
-    //         .enumerate()
-    //         .filter(|&(_, need)| need > 0)
-    //         .flat_map(|(set_index, need)| std::iter::repeat(set_index).take(need))
-    //         .collect();
-
-    //     // rebalance the table to ensure set adequate representation
-    //     for set_index in freq_comps {
-    //         let (offset, seed) = table.iter_mut()
-    //             .max_by(|a, b| a.len().cmp(&b.len()))
-    //             .unwrap()
-    //             .pop()
-    //             .unwrap();
-    //         // table[set_index].push();
-    //     };
-    //     table
-    // };
-
-    // seed_table.iter();
-
-    // todo!()
-
-    // let min_freq = 2.min(target_len / sets.len());
-    // let needed_freqs = char_table.iter()
-    //     .map(|set| {
-    //         let freq = set.len();
-    //         min_freq.max(freq) - freq
-    //     });
-
-    // // make room
-    // for _ in 0..needed_freqs.sum() {
-    //     let over_rep_set = char_table.iter_mut()
-    //         .filter(|s| s.len() > min_freq)
-    //         .next()
-    //         .unwrap();
-    //     over_rep_set.pop();
-    // }
-
-    // let target_length = seed.length as usize;
-    // let sets = seed.characters.get();
-    // let chars_count: usize = sets.iter()
-    //     .map(|s| s.len())
-    //     .sum();
-
-    // // utility to split index to (set_index, offset)
-    // let split_index = |i: usize| -> (usize, usize) {
-    //     let mut offset = i;
-    //     let mut set_index = 0;
-
-    //     for set in &sets {
-    //         if offset >= set.len() {
-    //             offset -= set.len();
-    //             set_index += 1;
-    //         } else {
-    //             break;
-    //         }
-    //     }
-    //     (set_index, offset)
-    // };
-
-    // // create all characters and keep track of absolute set frequency
-    // let (mut password, frequencies) = {
-    //     let mut frequencies = vec![0_u32; sets.len()];
-    //     let password = (0..target_length).map(|i| {
-    //         let char_index = digest[i] as usize % chars_count;
-    //         let (set_index, offset) = split_index(char_index);
-    //         frequencies[set_index] += 1;
-    //         sets[set_index][offset]
-    //     }).collect();
-
-    //     (password, frequencies)
-    // };
-
-    // // calculate the needed frequencies for each set
-    // let needed_frequencies = {
-    //     let wanted = 2.min(target_length / sets.len()) as u32;
-    //     frequencies.iter()
-    //         .map(move |&f| wanted.max(f) - f)
-    //         .enumerate()
-    //         .filter(|&(_, f)| f > 0)
-    //         .flat_map(|(s, f)|
-    //             (0..f).map(move |i| (i, s))
-    //         )
-    // };
-
-    // // fulfill the needs
-    // for (i, set_index) in needed_frequencies {
-
-    // }
-
-    // String::from_utf8(password).unwrap()
+    for &byte in bytes {
+        let mut carry = byte as u32;
+
+        for digit in &mut digits {
+            carry += (*digit as u32) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+    let zeros = bytes.iter().take_while(|&&byte| byte == 0).count();
+
+    iter::repeat_n(alphabet[0] as char, zeros)
+        .chain(digits.iter().rev().map(|&digit| alphabet[digit as usize] as char))
+        .collect()
 }
 
 /// Generates a new pepper value.
 pub fn pepper() -> Vec<u8> {
+    pepper_with(&mut rand::thread_rng())
+}
+
+/// Generates a new pepper value from the given random number generator. A seeded generator yields
+/// a deterministic pepper, e.g. for tests.
+pub fn pepper_with<R: RngCore>(rng: &mut R) -> Vec<u8> {
     const LENGTH: usize = 20;
 
-    let mut rng = rand::thread_rng();
     let mut buffer = vec![0_u8; LENGTH];
     rng.fill(buffer.as_mut_slice());
     buffer
 }
 
-/// Generates an authentication token from a key.
+/// Specifies the cost of an [argon2] hash, either of [authentication tokens](auth_token) or of
+/// [passwords](password) if overridden by [`Seed::argon2`]. Higher costs slow down attempts to guess
+/// the key, at the cost of slower verification and generation.
+#[derive(Serialize, Deserialize, Hash, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Argon2Params {
+    /// Memory used in KiB.
+    pub mem_cost: u32,
+    /// Number of passes over the memory.
+    pub time_cost: u32,
+    /// Degree of parallelism.
+    pub lanes: u32,
+}
+
+impl Default for Argon2Params {
+    /// Returns the defaults of [argon2], with which all tokens were generated before the parameters
+    /// were stored in the vault, and with which passwords are generated unless overridden.
+    fn default() -> Self {
+        let config = argon2::Config::default();
+
+        Argon2Params {
+            mem_cost: config.mem_cost,
+            time_cost: config.time_cost,
+            lanes: config.lanes,
+        }
+    }
+}
+
+impl Argon2Params {
+    /// Returns whether hashing with these parameters costs at least as much memory and as many
+    /// passes as with `other`, i.e. whether replacing a hash generated with `other` does not weaken
+    /// it.
+    pub fn at_least(&self, other: &Argon2Params) -> bool {
+        self.mem_cost >= other.mem_cost && self.time_cost >= other.time_cost
+    }
+}
+
+impl fmt::Display for Argon2Params {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} KiB, {} passes, {} lanes", self.mem_cost, self.time_cost, self.lanes)
+    }
+}
+
+/// Hashes the key and generation tag of a seed into the digest a password is derived from. See
+/// steps 1 and 2 of [`password_with`].
+fn digest(
+    key: &str,
+    pepper: &[u8],
+    seed: &Seed,
+    normalization: Normalization,
+    variant: Argon2Variant,
+) -> Result<Zeroizing<Vec<u8>>> {
+    use argon2::*;
+
+    let mut config = Config::default();
+    config.hash_length = match &seed.kind {
+        SeedKind::Password => {
+            // a byte is added to pick the target length of seeds with a range, see [`target_len`]
+            let len_picker = (seed.min_len < seed.max_len) as usize;
+            (4.max(seed.max_len as usize * 2) + len_picker) as u32
+        }
+        SeedKind::Passphrase { words, hybrid, .. } => {
+            // hybrid passphrases are followed by a pair of bytes per word to pick its decorations
+            let decorations = if *hybrid { *words as u32 } else { 0 };
+            2 * (*words as u32 + SPARE_PAIRS as u32 + decorations)
+        }
+    };
+    config.secret = pepper;
+    config.variant = variant.to_argon2();
+
+    if let Some(params) = &seed.argon2 {
+        config.mem_cost = params.mem_cost;
+        config.time_cost = params.time_cost;
+        config.lanes = params.lanes;
+    }
+
+    let (key, gen_tag) = (normalization.apply(key), normalization.apply(&seed.gen_tag));
+
+    // the key is recoverable from the data, so both are cleared from memory once dropped
+    let data = Zeroizing::new(match seed.variant {
+        0 => format!("{}{}", *key, *gen_tag),
+        // the separator keeps variants from colliding with generation tags ending in digits
+        variant => format!("{}{}\0{}", *key, *gen_tag, variant),
+    });
+    let digest = hash(&data, &seed.salt.to_be_bytes(), config).map_err(crate::Error::Hashing)?;
+    Ok(Zeroizing::new(digest))
+}
+
+/// Picks the length of a password within the length range of its seed, from the last byte of its
+/// digest. Step 4 of [`password_with`].
+fn target_len(seed: &Seed, digest: &[u8]) -> usize {
+    let range = (seed.max_len - seed.min_len) as usize + 1;
+
+    match range {
+        1 => seed.max_len as usize,
+        _ => seed.min_len as usize + *digest.last().unwrap() as usize % range,
+    }
+}
+
+/// Picks a word from the [list](words) per pair of bytes of the digest. Step 3 of
+/// [`password_with`].
+///
+/// Pairs at or above [`WORD_PICK_LIMIT`] are skipped, drawing on the spare pairs at the end of the
+/// digest. Should only as many pairs remain as words left to pick, which is vanishingly unlikely,
+/// the remaining pairs are used regardless.
+fn pick_words(digest: &[u8], words: usize) -> Vec<&'static str> {
+    let list = self::words();
+    let pairs = digest.chunks_exact(2).map(|pair| u16::from_be_bytes([pair[0], pair[1]]) as usize);
+    let mut remaining = digest.len() / 2;
+    let mut picked = Vec::with_capacity(words);
+
+    for pair in pairs {
+        if picked.len() == words {
+            break;
+        }
+        if pair < WORD_PICK_LIMIT || remaining == words - picked.len() {
+            picked.push(list[pair % list.len()]);
+        }
+        remaining -= 1;
+    }
+    picked
+}
+
+/// Holds the characters with which the words of a [hybrid](SeedKind::Passphrase::hybrid)
+/// passphrase are decorated, taken from the sets selected by its seed after its exclusions. Empty
+/// if the set is not selected.
+struct HybridSets {
+    /// Upper case letters, with which words are capitalized.
+    upper: Vec<u8>,
+    /// Digits, one of which follows each word.
+    digits: Vec<u8>,
+    /// Special and rare characters, one of which follows the digit of each word.
+    symbols: Vec<u8>,
+}
+
+impl HybridSets {
+    /// Gets the sets of a seed, or [None] if it is not a hybrid passphrase.
+    fn of(seed: &Seed) -> Option<HybridSets> {
+        if !matches!(seed.kind, SeedKind::Passphrase { hybrid: true, .. }) {
+            return None;
+        }
+        let excluded = seed.exclude.as_bytes();
+        let set = |flag: Characters, chars: &[u8]| -> Vec<u8> {
+            match seed.characters.contains(flag) {
+                true => chars.iter().copied().filter(|c| !excluded.contains(c)).collect(),
+                false => Vec::new(),
+            }
+        };
+        let rare = match seed.exclude_space {
+            true => Characters::RARE_WITHOUT_SPACE,
+            false => Characters::SETS[4],
+        };
+        let mut symbols = set(Characters::SPECIAL, Characters::SETS[3]);
+        symbols.extend(set(Characters::RARE, rare));
+
+        Some(HybridSets {
+            upper: set(Characters::UPPER_CASE, Characters::SETS[0]),
+            digits: set(Characters::NUMERICAL, Characters::SETS[2]),
+            symbols,
+        })
+    }
+
+    /// Gets the first letter of a word as capitalized, or unchanged if the upper case letter is not
+    /// available.
+    fn capitalize(&self, letter: u8) -> u8 {
+        let upper = letter.to_ascii_uppercase();
+        if self.upper.contains(&upper) { upper } else { letter }
+    }
+
+    /// Appends a word capitalized, followed by a digit picked by the first byte of the pair and a
+    /// symbol picked by the second.
+    fn push(&self, passphrase: &mut String, word: &str, pair: &[u8]) {
+        passphrase.push(self.capitalize(word.as_bytes()[0]) as char);
+        passphrase.push_str(&word[1..]);
+
+        for (set, &byte) in [&self.digits, &self.symbols].into_iter().zip(pair) {
+            if !set.is_empty() {
+                passphrase.push(set[byte as usize % set.len()] as char);
+            }
+        }
+    }
+
+    /// Checks that a passphrase consists of the number of words decorated as by
+    /// [`HybridSets::push`]. Words are told apart by the characters between them, so only the
+    /// characters are checked if there are none.
+    fn check(&self, passphrase: &str, words: usize, separator: &str) -> bool {
+        let list = self::words();
+        let allowed = |c: &u8| {
+            c.is_ascii_alphabetic()
+                || self.digits.contains(c)
+                || self.symbols.contains(c)
+                || separator.as_bytes().contains(c)
+        };
+        let tally = |set: &[u8]| passphrase.bytes().filter(|c| set.contains(c)).count();
+        let delimited =
+            !separator.is_empty() || !self.digits.is_empty() || !self.symbols.is_empty();
+        let runs: Vec<&str> = passphrase
+            .split(|c: char| !c.is_ascii_alphabetic())
+            .filter(|run| !run.is_empty())
+            .collect();
+
+        let words_valid = !delimited
+            || runs.len() == words
+                && runs.iter().all(|run| {
+                    let lower = run.to_ascii_lowercase();
+                    let first = self.capitalize(lower.as_bytes()[0]);
+                    list.contains(&lower.as_str())
+                        && run.as_bytes()[0] == first
+                        && run[1..] == lower[1..]
+                });
+        words_valid
+            && passphrase.bytes().all(|c| allowed(&c))
+            && (self.digits.is_empty() || tally(&self.digits) >= words)
+            && (self.symbols.is_empty() || tally(&self.symbols) >= words)
+    }
+}
+
+/// Generates an authentication token from a key with the [default parameters](Argon2Params::default),
+/// hashing the key [as is](Normalization::None) like tokens generated before normalization.
 ///
 /// Internally, hashes the key using [argon2].
-pub fn auth_token(key: &str, vault_pepper: &[u8]) -> Vec<u8> {
-    self::hash(key, vault_pepper, argon2::Config::default())
+///
+/// # Errors
+/// * [`Error::Hashing`] if argon2 fails, which the default parameters should never cause.
+pub fn auth_token(key: &str, vault_pepper: &[u8]) -> Result<Vec<u8>> {
+    auth_token_with(key, vault_pepper, &Argon2Params::default(), Normalization::None)
+}
+
+/// Generates an authentication token from a key with the given parameters. Tokens generated with
+/// different parameters differ, so a token must be verified with the parameters it was generated
+/// with.
+///
+/// # Errors
+/// * [`Error::Hashing`] if argon2 rejects the parameters, e.g. since `params.lanes` is 0.
+pub fn auth_token_with(
+    key: &str,
+    vault_pepper: &[u8],
+    params: &Argon2Params,
+    normalization: Normalization,
+) -> Result<Vec<u8>> {
+    let config = argon2::Config {
+        mem_cost: params.mem_cost,
+        time_cost: params.time_cost,
+        lanes: params.lanes,
+        ..Default::default()
+    };
+    self::hash(&normalization.apply(key), vault_pepper, config).map_err(Error::Hashing)
 }
 
-/// Utility function to hash data using [argon2].
-fn hash(data: &str, salt: &[u8], config: argon2::Config) -> Vec<u8> {
-    argon2::hash_raw(&data.as_bytes(), &salt, &config).unwrap()
+/// Compares two authentication tokens in constant time, such that the time taken reveals nothing
+/// about how much of the tokens match. Tokens of different lengths never match, but are still
+/// compared over their common length, such that neither case returns early.
+pub(crate) fn tokens_match(a: &[u8], b: &[u8]) -> bool {
+    let diff = a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y));
+    (diff == 0) & (a.len() == b.len())
+}
+
+/// Utility function to hash data using [argon2]. Fails if argon2 rejects the configuration, e.g.
+/// since its output length or lane count is below the minimum.
+fn hash(
+    data: &str,
+    salt: &[u8],
+    config: argon2::Config,
+) -> std::result::Result<Vec<u8>, argon2::Error> {
+    argon2::hash_raw(data.as_bytes(), salt, &config)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::HashSet;
 
     #[test]
     fn password() {
         let mut seed = Seed {
             identifier: "".to_string(),
-            length: 255,
+            gen_tag: "".to_string(),
+            min_len: 255,
+            max_len: 255,
             salt: 2,
             characters: Characters::all(),
+            custom_set: None,
             username: None,
+            url: None,
+            notes: None,
+            group: None,
+            exclude_space: false,
+            exclude: String::new(),
+            encoding: Encoding::CharsetTable,
+            kind: SeedKind::Password,
+            variant: 0,
+            argon2: None,
+            max_site_length: None,
+            last_generated: None,
         };
-        super::password("", b"", &seed);
+        let password = super::password("", b"", &seed).unwrap();
+        check(&password, &seed).unwrap();
+
+        seed.min_len = 12;
+        seed.max_len = 20;
+        seed.characters = Characters::all() - Characters::RARE;
+        let password = super::password("key", b"pepper", &seed).unwrap();
+        check(&password, &seed).unwrap();
+        assert_eq!(password.as_str(), ",H9DHM,L#6[18njwhiJk");
+    }
+
+    #[test]
+    fn length_range() {
+        let mut seed = Seed::basic(String::new(), None);
+        seed.min_len = 8;
+        seed.max_len = 16;
+
+        let lengths: HashSet<_> = (0..16)
+            .map(|i| {
+                seed.identifier = format!("site {i}");
+                seed.gen_tag = seed.identifier.clone();
+                let password = super::password("key", b"pepper", &seed).unwrap();
+                assert_eq!(password, super::password("key", b"pepper", &seed).unwrap());
+                check(&password, &seed).unwrap();
+                password.chars().count()
+            })
+            .collect();
+        assert!(lengths.len() > 1);
+
+        seed.min_len = 12;
+        seed.max_len = 12;
+        assert_eq!(super::password("key", b"pepper", &seed).unwrap().chars().count(), 12);
+    }
+
+    #[test]
+    fn tokens_match() {
+        assert!(super::tokens_match(b"token", b"token"));
+        assert!(!super::tokens_match(b"token", b"tokem"));
+        assert!(!super::tokens_match(b"token", b"tok"));
+        assert!(!super::tokens_match(b"", b"token"));
+    }
+
+    #[test]
+    fn encoded_password() {
+        for encoding in [Encoding::Hex, Encoding::Base32, Encoding::Base58] {
+            let mut seed = Seed::advanced("token".to_owned(), None);
+            seed.encoding = encoding;
+            let password = super::password("key", b"pepper", &seed).unwrap();
+
+            assert_eq!(password, super::password("key", b"pepper", &seed).unwrap());
+            check(&password, &seed).unwrap();
+        }
+    }
+
+    #[test]
+    fn password_exclude() {
+        let mut seed = Seed::basic("exclude".to_owned(), None);
+        let original = super::password("key", b"pepper", &seed).unwrap();
+        seed.exclude = "aeiou0".to_owned();
+        let password = derive("key", b"pepper", &seed).unwrap();
+        assert_ne!(password, original);
+        assert!(!password.contains(|c| seed.exclude.contains(c)));
+
+        seed.characters = Characters::NUMERICAL;
+        seed.exclude = "123456789".to_owned();
+        assert!(matches!(validate(&seed), Err(Error::InvalidSeed(..))));
+    }
+
+    #[test]
+    fn password_custom_set() {
+        let mut seed = Seed::basic("custom".to_owned(), None);
+        seed.characters = Characters::LOWER_CASE;
+        seed.custom_set = Some("-_.".to_owned());
+        let password = derive("key", b"pepper", &seed).unwrap();
+        assert!(password.contains(['-', '_', '.']));
+        assert!(password.chars().all(|c| c.is_ascii_lowercase() || "-_.".contains(c)));
+
+        // the custom set alone suffices
+        seed.characters = Characters::empty();
+        assert!(validate(&seed).is_ok());
+
+        for invalid in ["", "-_-", "\u{e9}", "\t"] {
+            seed.custom_set = Some(invalid.to_owned());
+            assert!(matches!(validate(&seed), Err(Error::InvalidSeed(..))), "{invalid:?}");
+        }
+    }
+
+    #[test]
+    fn password_zeroize() {
+        // fails to compile should an entry point return passwords which are not cleared on drop
+        let _: fn(&str, &[u8], &Seed) -> Result<Zeroizing<String>> = derive;
+        let _: fn(&str, &[u8], &Seed) -> Result<Zeroizing<String>> = super::password;
+        let _: fn(&crate::Vault, &Seed, &str) -> Result<Zeroizing<String>> = crate::Vault::password;
+    }
+
+    #[test]
+    fn derive_validates() {
+        let mut seed = Seed::basic("basic".to_owned(), None);
+        let password = derive("key", b"pepper", &seed).unwrap();
+        assert_eq!(password, super::password("key", b"pepper", &seed).unwrap());
+
+        seed.min_len = 0;
+        assert!(matches!(derive("key", b"pepper", &seed), Err(Error::InvalidSeed(..))));
+        seed.min_len = seed.max_len + 1;
+        assert!(matches!(derive("key", b"pepper", &seed), Err(Error::InvalidSeed(..))));
+
+        seed = Seed::basic("basic".to_owned(), None);
+        seed.characters = Characters::empty();
+        assert!(matches!(validate(&seed), Err(Error::InvalidSeed(..))));
+    }
+
+    #[test]
+    fn password_normalization() {
+        let mut seed = Seed::basic("caf\u{e9}".to_owned(), None);
+        let variant = Argon2Variant::Argon2d;
+        let composed =
+            password_with("cl\u{e9}", b"pepper", &seed, Normalization::Nfc, variant).unwrap();
+
+        seed.gen_tag = "cafe\u{301}".to_owned();
+        let decomposed =
+            password_with("cle\u{301}", b"pepper", &seed, Normalization::Nfc, variant).unwrap();
+        assert_eq!(decomposed, composed);
+
+        let legacy =
+            password_with("cle\u{301}", b"pepper", &seed, Normalization::None, variant).unwrap();
+        assert_ne!(legacy, composed);
+
+        // the plain entry points hash as is, keeping their outputs from before normalization
+        assert_eq!(super::password("cle\u{301}", b"pepper", &seed).unwrap(), legacy);
+        assert_eq!(derive("cle\u{301}", b"pepper", &seed).unwrap(), legacy);
+    }
+
+    #[test]
+    fn password_argon2_variant() {
+        let seed = Seed::basic("variant".to_owned(), None);
+        let with =
+            |variant| password_with("key", b"pepper", &seed, Normalization::Nfc, variant).unwrap();
+
+        // switching the variant of a vault changes all of its passwords
+        assert_eq!(with(Argon2Variant::Argon2d), super::password("key", b"pepper", &seed).unwrap());
+        assert_ne!(with(Argon2Variant::Argon2id), with(Argon2Variant::Argon2d));
+    }
+
+    #[test]
+    fn passphrase() {
+        let mut seed = Seed::basic("words".to_owned(), None);
+        seed.kind = SeedKind::Passphrase { words: 4, separator: "-".to_owned(), hybrid: false };
+        let passphrase = derive("key", b"pepper", &seed).unwrap();
+        assert_eq!(passphrase, super::password("key", b"pepper", &seed).unwrap());
+        assert_eq!(passphrase.split('-').count(), 4);
+
+        // the same words are picked regardless of the separator
+        seed.kind = SeedKind::Passphrase { words: 4, separator: " ".to_owned(), hybrid: false };
+        let spaced = super::password("key", b"pepper", &seed).unwrap();
+        assert_eq!(*spaced, passphrase.replace('-', " "));
+
+        seed.kind = SeedKind::Passphrase { words: 0, separator: "-".to_owned(), hybrid: false };
+        assert!(matches!(validate(&seed), Err(Error::InvalidSeed(..))));
+        seed.kind = SeedKind::Passphrase { words: 4, separator: "x".to_owned(), hybrid: false };
+        assert!(matches!(validate(&seed), Err(Error::InvalidSeed(..))));
+    }
+
+    #[test]
+    fn hybrid_passphrase() {
+        let mut seed = Seed::basic("hybrid".to_owned(), None);
+        seed.characters = Characters::all();
+        seed.exclude = "3".to_owned();
+        seed.kind = SeedKind::Passphrase { words: 4, separator: "-".to_owned(), hybrid: true };
+        let passphrase = derive("key", b"pepper", &seed).unwrap();
+        assert_eq!(passphrase, derive("key", b"pepper", &seed).unwrap());
+
+        // each word is capitalized and followed by a digit and a symbol
+        let digit_count = passphrase.chars().filter(char::is_ascii_digit).count();
+        assert!(digit_count >= 4 && !passphrase.contains('3'));
+        assert!(passphrase.chars().filter(char::is_ascii_uppercase).count() >= 1);
+        assert!(check("Crane4-Ivory7-Pluck2-Sauna5", &seed).is_err());
+
+        // only the selected sets decorate the words
+        seed.characters = Characters::LOWER_CASE | Characters::NUMERICAL;
+        let numbered = derive("key", b"pepper", &seed).unwrap();
+        assert!(numbered.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-'));
+        assert!(check(&numbered.replace(char::is_numeric, ""), &seed).is_err());
+        assert!(super::entropy(&seed) > 4.0 * (words().len() as f64).log2());
+
+        seed.characters = Characters::LOWER_CASE;
+        let plain = derive("key", b"pepper", &seed).unwrap();
+        seed.kind = SeedKind::Passphrase { words: 4, separator: "-".to_owned(), hybrid: false };
+        assert_ne!(plain, derive("key", b"pepper", &seed).unwrap());
+    }
+
+    #[test]
+    fn passphrase_skips_biased_pairs() {
+        let list = words();
+        let pick = |digest: &[u8]| pick_words(digest, 2).join("-");
+        // 0xffff is past the limit, so the next pairs are used instead
+        assert_eq!(pick(&[0xff, 0xff, 0, 1, 0, 2]), format!("{}-{}", list[1], list[2]));
+        // unless only as many pairs remain as words left to pick
+        assert_eq!(pick(&[0xff, 0xff, 0, 1]), format!("{}-{}", list[65535 % 7776], list[1]));
+    }
+
+    #[test]
+    fn wordlist() {
+        let words = words();
+        let mut sorted = words.to_vec();
+        sorted.sort_unstable();
+        sorted.dedup();
+        assert_eq!(sorted, words);
+        assert_eq!(words.len(), 7776);
+        assert_eq!(WORD_PICK_LIMIT % words.len(), 0);
+        assert!(words.iter().all(|word| word.chars().all(|c| c.is_ascii_lowercase())));
+    }
+
+    #[test]
+    fn password_variants() {
+        let mut seed = Seed::basic("variant".to_owned(), None);
+        let original = super::password("key", b"pepper", &seed).unwrap();
+
+        seed.variant = 1;
+        let variant = super::password("key", b"pepper", &seed).unwrap();
+        assert_ne!(variant, original);
+
+        seed.variant = 0;
+        assert_eq!(super::password("key", b"pepper", &seed).unwrap(), original);
+    }
+
+    #[test]
+    fn password_argon2_override() {
+        let mut seed = Seed::basic("legacy".to_owned(), None);
+        let original = super::password("key", b"pepper", &seed).unwrap();
+
+        // overriding with the defaults leaves the password unchanged
+        seed.argon2 = Some(Argon2Params::default());
+        assert_eq!(super::password("key", b"pepper", &seed).unwrap(), original);
+
+        seed.argon2 = Some(Argon2Params { mem_cost: 1024, time_cost: 1, lanes: 1 });
+        assert_ne!(super::password("key", b"pepper", &seed).unwrap(), original);
+    }
+
+    #[test]
+    fn password_hashing_error() {
+        let mut seed = Seed::basic("lanes".to_owned(), None);
+        seed.argon2 = Some(Argon2Params { mem_cost: 1024, time_cost: 1, lanes: 0 });
+        assert!(matches!(derive("key", b"pepper", &seed), Err(Error::Hashing(_))));
+
+        let params = Argon2Params { mem_cost: 1024, time_cost: 1, lanes: 0 };
+        let token = auth_token_with("key", b"pepper", &params, Normalization::Nfc);
+        assert!(matches!(token, Err(Error::Hashing(_))));
+    }
+
+    #[test]
+    fn entropy() {
+        let pin = Seed::pin("pin".to_owned(), None, 4);
+        assert!((super::entropy(&pin) - 4.0 * 9_f64.log2()).abs() < 1e-9);
+        assert!((12.0..14.0).contains(&super::entropy(&pin)));
+
+        let mut full = Seed::basic("full".to_owned(), None);
+        full.min_len = 24;
+        full.max_len = 24;
+        full.characters = Characters::all();
+        let alphabet_size: usize = full.sets().iter().map(|set| set.len()).sum();
+        let unbalanced = 24.0 * (alphabet_size as f64).log2();
+
+        // guaranteeing each set slightly lowers the entropy
+        let entropy = super::entropy(&full);
+        assert!(entropy < unbalanced && entropy > unbalanced - 1.0);
+        assert!(entropy > 150.0);
+    }
+
+    #[test]
+    fn balanced_share() {
+        // half of the strings of two characters over two sets of equal size contain both sets
+        assert!((super::balanced_share(&[0.5, 0.5], 2, 1) - 0.5).abs() < 1e-9);
+        assert_eq!(super::balanced_share(&[1.0], 8, 2), 1.0);
+        assert_eq!(super::balanced_share(&[0.5, 0.5], 8, 0), 1.0);
+    }
+
+    #[test]
+    fn base58_encoding() {
+        assert_eq!(base58(b"hello world"), "StV1DL6CwTryKyV");
+        assert_eq!(base58(&[0, 0, 1]), "112");
+        assert_eq!(base58(&[]), "");
+    }
+
+    #[test]
+    fn check_requirements() {
+        let mut seed = Seed::pin("pin".to_owned(), None, 4);
+        assert!(check("1234", &seed).is_ok());
+        assert!(check("123", &seed).is_err());
+        assert!(check("12a4", &seed).is_err());
+
+        seed.characters = Characters::LOWER_CASE | Characters::NUMERICAL;
+        assert!(check("ab12", &seed).is_ok());
+        assert!(check("abcd", &seed).is_err());
+
+        // two characters of each set are guaranteed once the password fits them
+        assert!(check("abc1", &seed).is_err());
+        seed.min_len = 3;
+        assert!(check("ab1", &seed).is_ok());
+        seed.min_len = 4;
+
+        seed.characters |= Characters::RARE;
+        assert!(check("a1 ~", &seed).is_err());
+        seed.exclude_space = false;
+        assert!(check("a1 ~", &seed).is_ok());
 
-        // for i in 0..100 {
-        //     seed.salt = i;
-        //     dbg!(super::password("", b"", &seed));
-        // }
+        // too short to fit every set
+        seed = Seed::advanced("advanced".to_owned(), None);
+        seed.min_len = 1;
+        assert!(check("abc", &seed).is_ok());
     }
 }
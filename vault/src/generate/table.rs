@@ -0,0 +1,150 @@
+//! Contains the table distributing a digest among character sets, see [`PasswordTable`].
+
+use std::iter;
+
+use zeroize::Zeroize;
+
+/// Assigns the bytes of a digest to the character sets of a seed, from which a password is built.
+/// The bytes kept in the rows are cleared from memory once dropped, like the digest itself.
+pub struct PasswordTable {
+    target_len: usize,
+    sets: Vec<Vec<u8>>,
+    rows: Vec<Vec<(usize, u8)>>,
+}
+
+// the accessors are only used by tests and the `testing` feature
+#[cfg_attr(not(feature = "testing"), allow(dead_code))]
+impl PasswordTable {
+    /// Gets the length of the password being built.
+    pub fn target_len(&self) -> usize {
+        self.target_len
+    }
+
+    /// Gets the character sets, in the order of the rows.
+    pub fn sets(&self) -> &[Vec<u8>] {
+        &self.sets
+    }
+
+    /// Gets the cells assigned to each set, as pairs of the index of the digest chunk and the
+    /// byte picking the character.
+    pub fn rows(&self) -> &[Vec<(usize, u8)>] {
+        &self.rows
+    }
+}
+
+impl Drop for PasswordTable {
+    fn drop(&mut self) {
+        self.rows.zeroize();
+    }
+}
+
+impl PasswordTable {
+    /// Distributes pairs of digest bytes among the rows of the sets. The first byte of each pair
+    /// picks the set, weighted by the number of characters in each, and the second is kept to pick
+    /// the character.
+    pub fn new(target_len: u8, sets: Vec<Vec<u8>>, digest: &[u8]) -> PasswordTable {
+        let target_len = target_len as usize;
+        let char_count = sets.iter().map(|set| set.len()).sum::<usize>();
+        let mut rows = vec![vec![]; sets.len()];
+
+        
+        
+        for (i, chunk) in digest.chunks_exact(2).enumerate() {
+            if let &[set_seed, char_seed] = chunk {
+                let set_idx = {
+                    let mut i = set_seed as usize % char_count;
+                    let mut set_index = 0;
+                    
+                    for set in &sets {
+                        if i >= set.len() {
+                            i -= set.len();
+                            set_index += 1;
+                        } else {
+                            break;
+                        }
+                    };
+                    set_index
+                };
+                rows[set_idx].push((i, char_seed));
+            } else {
+                unreachable!()
+            }
+        };
+        PasswordTable {
+            target_len,
+            sets,
+            rows,
+        }
+    }
+
+    /// Moves cells from the largest rows to those below the minimum frequency of each set, such
+    /// that every set is represented in the password if it is long enough.
+    pub fn balance(mut self) -> Self {
+        let min_freq = 2.min(self.target_len / self.sets.len());
+        let compensations: Vec<usize> = self.rows.iter()
+            .enumerate()
+            .filter_map(|(i, row)| {
+                if row.len() < min_freq {
+                    Some((i, min_freq - row.len()))
+                } else {
+                    None
+                }
+            })
+            .flat_map(|(i, needed)| iter::repeat_n(i, needed))
+            .collect();
+        
+        for i in compensations {
+            let cell = self.rows.iter_mut()
+                .max_by(|a, b| a.len().cmp(&b.len()))
+                .and_then(|row| row.pop())
+                .unwrap();
+            self.rows[i].push(cell);
+        }
+        for row in &self.rows {
+            debug_assert!(row.len() >= min_freq);
+        };
+        self
+    }
+
+    /// Builds the password, picking the character at the position of each cell from the set of
+    /// its row with its byte.
+    pub fn build(self) -> String {
+        let mut password = vec![0; self.target_len];
+
+        for (set, row) in self.sets.iter().zip(&self.rows) {
+            for &(position, char_seed) in row {
+                password[position] = set[char_seed as usize % set.len()];
+            }
+        }
+        // every position is assigned exactly one cell, since each chunk of the digest is
+        debug_assert!(!password.contains(&0));
+        String::from_utf8(password).expect("character sets are ASCII")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn balance() {
+        let sets = vec![b"abcdefghijklmnopqrstuvwxyz".to_vec(), b"0".to_vec()];
+        // every chunk picks the first set, since the picking bytes are below its length
+        let digest: Vec<u8> = (0..16).flat_map(|i| [i, i]).collect();
+        let table = PasswordTable::new(16, sets, &digest);
+        assert_eq!(table.rows()[1].len(), 0);
+
+        let table = table.balance();
+        assert_eq!(table.rows()[0].len(), 14);
+        assert_eq!(table.rows()[1].len(), 2);
+    }
+
+    #[test]
+    fn build() {
+        let sets = vec![b"abc".to_vec(), b"0".to_vec()];
+        // chunks alternate between the sets, picking increasing characters
+        let digest = [0, 0, 3, 0, 1, 1, 3, 1, 2, 2];
+        let password = PasswordTable::new(5, sets, &digest).balance().build();
+        assert_eq!(password, "a0b0c");
+    }
+}
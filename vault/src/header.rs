@@ -0,0 +1,131 @@
+//! Contains the header identifying vault files, stored as the first field of each file.
+//!
+//! The header allows [`Vault::load`](crate::Vault::load) to reject files it cannot read, such as
+//! those written by a newer version, instead of misinterpreting them. It also allows external
+//! tools to identify vault files without knowledge of the remaining fields, e.g.:
+//!
+//! ```json
+//! "header": { "format": "svalbard-vault", "version": 3, "kdf": "argon2d", "encoding": "json" }
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+use crate::generate::Argon2Variant;
+
+/// Identifies vault files.
+pub const FORMAT: &str = "svalbard-vault";
+/// Version of the format written by this build. Files of any version up to it may be loaded.
+///
+/// * 2: added the header.
+/// * 3: [character sets](crate::seed::Characters) are stored as masks, e.g. `"UL-S-"`.
+pub const VERSION: u32 = 3;
+/// Key derivation function of [passwords](crate::generate::password) in vaults using the default
+/// [variant](Argon2Variant), and of all vaults stored before the variant could be chosen.
+pub const KDF: &str = "argon2d";
+/// Encoding of the file contents.
+pub const ENCODING: &str = "json";
+
+/// Describes the format of a vault file.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Header {
+    /// Identifies the file as a vault, see [`FORMAT`].
+    pub format: String,
+    /// Version of the format, incremented on changes which older versions cannot read.
+    pub version: u32,
+    /// Key derivation function of passwords, the [name](Argon2Variant::name) of the variant used by
+    /// the vault. See [`KDF`].
+    pub kdf: String,
+    /// Encoding of the file contents, see [`ENCODING`].
+    pub encoding: String,
+}
+
+impl Header {
+    /// Returns the header written by this build.
+    pub fn current() -> Self {
+        Header::with_version(VERSION)
+    }
+
+    /// Returns the header written by this build for a vault hashing passwords with `variant`.
+    /// Older builds reject vaults of variants they do not know, rather than generating wrong
+    /// passwords.
+    pub fn current_with(variant: Argon2Variant) -> Self {
+        Header {
+            kdf: variant.name().to_owned(),
+            ..Header::current()
+        }
+    }
+
+    /// Returns the header implied by files stored before headers existed, which are JSON encoded
+    /// files of version 1.
+    pub fn legacy() -> Self {
+        Header::with_version(1)
+    }
+
+    fn with_version(version: u32) -> Self {
+        Header {
+            format: FORMAT.to_owned(),
+            version,
+            kdf: KDF.to_owned(),
+            encoding: ENCODING.to_owned(),
+        }
+    }
+
+    /// Parses the header of the contents of a vault file without parsing the remaining fields.
+    /// Files without a header are [legacy](Header::legacy) files.
+    pub fn parse(contents: &str) -> serde_json::Result<Self> {
+        #[derive(Deserialize)]
+        struct File {
+            header: Option<Header>,
+        }
+        serde_json::from_str::<File>(contents).map(|file| file.header.unwrap_or_else(Header::legacy))
+    }
+
+    /// Checks whether a file with this header can be loaded by this build, returning the reason if
+    /// not.
+    pub fn check(&self) -> Result<(), String> {
+        if self.format != FORMAT {
+            Err(format!("unknown format '{}'", self.format))
+        } else if self.version > VERSION {
+            Err(format!(
+                "format version {} is newer than the supported version {VERSION}",
+                self.version
+            ))
+        } else if Argon2Variant::ALL.iter().all(|variant| variant.name() != self.kdf) {
+            Err(format!("unknown key derivation function '{}'", self.kdf))
+        } else if self.encoding != ENCODING {
+            Err(format!("unknown encoding '{}'", self.encoding))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        assert_eq!(Header::parse(r#"{ "seeds": [] }"#).unwrap(), Header::legacy());
+
+        let contents = serde_json::json!({ "header": Header::current(), "seeds": [] }).to_string();
+        assert_eq!(Header::parse(&contents).unwrap(), Header::current());
+        assert!(Header::parse("not json").is_err());
+    }
+
+    #[test]
+    fn check() {
+        assert!(Header::legacy().check().is_ok());
+        assert!(Header::current().check().is_ok());
+
+        let newer = Header::with_version(VERSION + 1);
+        assert!(newer.check().is_err());
+
+        let other = Header { encoding: "toml".to_owned(), ..Header::current() };
+        assert!(other.check().is_err());
+
+        assert!(Header::current_with(Argon2Variant::Argon2id).check().is_ok());
+        let other = Header { kdf: "scrypt".to_owned(), ..Header::current() };
+        assert!(other.check().is_err());
+    }
+}
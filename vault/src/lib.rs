@@ -11,18 +11,52 @@
 //! This system of providing layer-specific data helps ensure the security and uniqueness of each
 //! generated password. For more details, see the [password derivation](generate::password)
 //! algorithm.
+//!
+//! Secrets held by the library, i.e. the pepper, derived passwords, and the digests, password
+//! tables and normalized keys they are derived from, are cleared from memory once dropped using
+//! [zeroize]. The key itself is borrowed from the caller, who is responsible for clearing it, e.g.
+//! by holding it in a [`Zeroizing<String>`](Zeroizing). So are copies of passwords made by the
+//! caller, e.g. to display them. Clearing is best-effort: it does not cover copies made by the
+//! allocator when buffers grow, nor memory swapped to disk.
 
-use std::{path::*, fs, io, result};
+use std::{
+    borrow::Cow,
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    ops::Deref,
+    path::*,
+    fmt, fs, io, mem,
+    sync::atomic::{AtomicU64, Ordering},
+    time::SystemTime,
+};
 
+use audit::{AuditFinding, AuditThresholds};
 use deunicode::AsciiChars;
-use seed::Seed;
+use generate::{Argon2Params, Argon2Variant, Normalization};
+use header::Header;
+use rand::RngCore;
+use seed::{Seed, SeedDefaults};
+use stats::VaultStats;
 use serde::{Serialize, Deserialize};
 use serde_with::base64::Base64;
 use serde_with::serde_as;
-use thiserror::Error;
+use zeroize::{Zeroize, Zeroizing};
+
+pub use result::{Error, Result};
 
+pub mod audit;
 pub mod generate;
+pub mod header;
+mod result;
 pub mod seed;
+pub mod selftest;
+pub mod stats;
+
+/// Extension of vault files, see [`Vault::path_of`].
+const EXTENSION: &str = ".vault";
+
+/// Number of previous versions of a vault file kept by [`Vault::save`] unless otherwise specified.
+pub const DEFAULT_BACKUP_COUNT: usize = 3;
 
 /// Manages seeds and performs password generation.
 ///
@@ -30,75 +64,510 @@ pub mod seed;
 #[serde_as]
 #[derive(Serialize, Deserialize, Hash)]
 pub struct Vault {
+    /// Identifies the file and the version of its format. Set to the current version when loaded,
+    /// such that older files are upgraded once saved. See [`header`].
+    #[serde(default = "Header::legacy")]
+    header: Header,
     /// Contains path to vault on disk.
     #[serde(skip)]
     path: PathBuf,
     /// Unique vault identifier.
     identifier: String,
-    /// Contains a pepper included when generating passwords.
-    #[serde_as(as = "Base64")]
-    pepper: Vec<u8>,
+    /// Specifies where the pepper is stored.
+    #[serde(rename = "pepper")]
+    pepper_source: PepperSource,
+    /// Contains a pepper included when generating passwords. Read from [`Vault::pepper_source`]
+    /// when loaded. Cleared from memory once dropped.
+    #[serde(skip)]
+    pepper: Pepper,
     /// Contains all seeds.
     seeds: Vec<Seed>,
     /// Authentication token generated from the user key
     #[serde_as(as = "Base64")]
     auth_token: Vec<u8>,
+    /// Parameters the authentication token was generated with. Vaults stored before parameters
+    /// were stored used the defaults.
+    #[serde(default)]
+    auth_params: Argon2Params,
+    /// Parameters the authentication token is upgraded to. See [`Vault::set_auth_params`].
+    #[serde(skip)]
+    target_auth_params: Argon2Params,
+    /// Number of previous versions of the vault file kept on save. See [`Vault::save`].
+    #[serde(skip, default = "default_backup_count")]
+    backup_count: usize,
+    /// Fixed text every password of the vault starts with, e.g. to satisfy an organizational
+    /// policy. See [`Vault::set_prefix`].
+    #[serde(default)]
+    prefix: Option<String>,
+    /// Normalization of the key and generation tags before hashing. Vaults created before
+    /// normalization was introduced hash them as is, such that their passwords are unchanged.
+    #[serde(default)]
+    normalization: Normalization,
+    /// Variant of argon2 used to hash passwords, chosen on creation. Vaults created before it could
+    /// be chosen use argon2d. Also stored in the [header](Header::kdf).
+    #[serde(default)]
+    argon2_variant: Argon2Variant,
+    /// Parameters new seeds start out with. See [`Vault::new_seed`].
+    #[serde(default)]
+    seed_defaults: SeedDefaults,
+    /// Callback notified of changes to the seeds. See [`Vault::set_observer`].
+    #[serde(skip)]
+    observer: Observer,
+    /// Hash of the file contents as last read or written. See [`Vault::changed_on_disk`].
+    #[serde(skip)]
+    file_hash: FileHash,
+    /// Whether the vault is kept in memory only. See [`Vault::scratch`].
+    #[serde(skip)]
+    scratch: bool,
+}
+
+fn default_backup_count() -> usize {
+    DEFAULT_BACKUP_COUNT
 }
 
 impl Vault {
-    /// Creates a new [Vault] from an identifier.
+    /// Creates a new [Vault] from an identifier, hashing passwords with the given argon2 `variant`.
+    /// [Argon2id](Argon2Variant::Argon2id) is recommended for new vaults; the variant cannot be
+    /// changed later, since every password depends on it.
     ///
     /// # Errors
     /// * [`Error::VaultNameConflict`] if a [Vault] with given identifier already exists on disk.
+    /// * [`Error::NotADirectory`] if the vault folder path exists but is not a folder.
     /// * [`Error::IO`] if creation of vault folder fails.
-    pub fn new(vault_folder: &Path, identifier: String, key: &str) -> Result<Self> {
+    ///
+    /// On unix, the vault folder and file are made accessible only to the current user. See
+    /// [`Vault::save`].
+    pub fn new(
+        vault_folder: &Path,
+        identifier: String,
+        key: &str,
+        variant: Argon2Variant,
+    ) -> Result<Self> {
+        Vault::new_with_rng(vault_folder, identifier, key, variant, &mut rand::thread_rng())
+    }
+
+    /// Creates a new [Vault] like [`Vault::new`], generating the pepper from the given random number
+    /// generator. A seeded generator yields a deterministic pepper, and thereby deterministic
+    /// passwords, e.g. for tests.
+    pub fn new_with_rng<R: RngCore>(
+        vault_folder: &Path,
+        identifier: String,
+        key: &str,
+        variant: Argon2Variant,
+        rng: &mut R,
+    ) -> Result<Self> {
+        check_folder(vault_folder)?;
         fs::create_dir_all(vault_folder).map_err(|e| Error::IO(e, vault_folder.to_owned()))?;
+        restrict_permissions(vault_folder, 0o700)?;
 
         let path = Vault::path_of(vault_folder, &identifier);
-        let pepper = generate::pepper();
-        let auth_token = generate::auth_token(key, &pepper);
 
         if path.exists() {
             Err(Error::VaultNameConflict(identifier))
         } else {
-            let vault = Vault {
-                path,
-                identifier,
-                seeds: Vec::new(),
-                pepper,
-                auth_token,
-            };
+            let pepper = generate::pepper_with(rng);
+            let vault = Vault::with_pepper(path, identifier, key, pepper, variant)?;
             vault.save().map(|_| vault)
         }
     }
 
+    /// Creates a new scratch [Vault], which is kept in memory only, e.g. for trying things out
+    /// before committing a vault to disk. Saving a scratch vault is a no-op; it may instead be
+    /// persisted with [`Vault::save_as`].
+    ///
+    /// # Errors
+    /// * [`Error::Hashing`] if the authentication token cannot be generated, which should never
+    ///   occur with the default parameters.
+    pub fn scratch(identifier: String, key: &str, variant: Argon2Variant) -> Result<Self> {
+        Ok(Vault {
+            scratch: true,
+            ..Vault::with_pepper(PathBuf::new(), identifier, key, generate::pepper(), variant)?
+        })
+    }
+
+    /// Creates an empty [Vault] stored at a path, without touching the disk.
+    fn with_pepper(
+        path: PathBuf,
+        identifier: String,
+        key: &str,
+        pepper: Vec<u8>,
+        variant: Argon2Variant,
+    ) -> Result<Self> {
+        Ok(Vault {
+            header: Header::current_with(variant),
+            path,
+            identifier,
+            seeds: Vec::new(),
+            pepper_source: PepperSource::Inline(pepper.clone()),
+            auth_token: generate::auth_token_with(
+                key,
+                &pepper,
+                &Argon2Params::default(),
+                Normalization::Nfc,
+            )?,
+            pepper: Pepper::from(pepper),
+            auth_params: Argon2Params::default(),
+            target_auth_params: Argon2Params::default(),
+            backup_count: DEFAULT_BACKUP_COUNT,
+            prefix: None,
+            normalization: Normalization::Nfc,
+            argon2_variant: variant,
+            seed_defaults: SeedDefaults::default(),
+            observer: Observer::default(),
+            file_hash: FileHash::default(),
+            scratch: false,
+        })
+    }
+
     /// Loads an existing [Vault] with given identifier from disk.
     ///
     /// # Errors
+    /// * [`Error::NotADirectory`] if the vault folder path exists but is not a folder.
     /// * [`Error::IO`] if [Vault] with given identifier does not exist.
     /// * [`Error::JSON`] if file contains corrupted data.
+    /// * [`Error::UnsupportedFormat`] if the [header](Header) of the file is not supported, e.g.
+    ///   if it was written by a newer version.
+    /// * [`Error::PepperUnavailable`] if the pepper is stored externally and cannot be read.
+    ///
+    /// Seeds stored before [generation tags](Seed::gen_tag) existed are migrated by setting their
+    /// tags to their identifiers, which leaves their passwords unchanged. Likewise, seeds stored
+    /// with a fixed length before [length ranges](Seed::min_len) existed are given a range of that
+    /// length.
     pub fn load(vault_folder: &Path, identifier: String) -> Result<Self> {
-        let path = Vault::path_of(vault_folder, &identifier);
+        check_folder(vault_folder)?;
+        Vault::read(Vault::path_of(vault_folder, &identifier))
+    }
 
+    /// Reads and parses a vault file. See [`Vault::load`].
+    fn read(path: PathBuf) -> Result<Self> {
         fs::read_to_string(&path)
             .map_err(|e| Error::IO(e, path.to_owned()))
             .and_then(|string| {
-                serde_json::from_str::<Vault>(&string).map_err(|e| Error::JSON(e, path.to_owned()))
+                let json_error = |e: serde_json::Error| {
+                    let offset = byte_offset(&string, e.line(), e.column());
+                    Error::JSON(e, path.to_owned(), offset)
+                };
+                // the header is checked first, since the remaining fields depend on the format
+                Header::parse(&string)
+                    .map_err(json_error)?
+                    .check()
+                    .map_err(|reason| Error::UnsupportedFormat(path.to_owned(), reason))?;
+
+                match serde_json::from_str::<Vault>(&string) {
+                    Ok(vault) => Ok((string, vault)),
+                    Err(e) => Err(json_error(e)),
+                }
             })
-            .map(|mut vault| {
+            .and_then(|(string, mut vault)| {
+                vault.file_hash.set(&string);
+                vault.header = Header::current_with(vault.argon2_variant);
                 vault.path = path;
-                vault
+                vault.pepper = Pepper::from(vault.pepper_source.read()?);
+                vault.target_auth_params = vault.auth_params;
+                for seed in &mut vault.seeds {
+                    seed.init_gen_tag();
+                    seed.init_max_len();
+                }
+                Ok(vault)
             })
     }
 
+    /// Returns whether a [Vault] with given identifier exists on disk. Cheaper than
+    /// [`Vault::load`], since the vault is not read or parsed.
+    pub fn exists(vault_folder: &Path, identifier: &str) -> bool {
+        Vault::path_of(vault_folder, identifier).is_file()
+    }
+
+    /// Lists the identifiers of the vaults in a folder, in alphabetical order. A folder which does
+    /// not exist contains no vaults.
+    ///
+    /// The identifiers are taken from the file names, which are normalized from the identifiers the
+    /// vaults were created with, e.g. `My vault` is stored as `my_vault.vault`. They may therefore
+    /// differ from the identifiers stored within the files, but always load the same vaults with
+    /// [`Vault::load`]. Other files, such as backups, and folders are skipped.
+    ///
+    /// # Errors
+    /// * [`Error::NotADirectory`] if the vault folder path exists but is not a folder.
+    /// * [`Error::IO`] if the folder could not be read.
+    pub fn list(vault_folder: &Path) -> Result<Vec<String>> {
+        check_folder(vault_folder)?;
+        let entries = match fs::read_dir(vault_folder) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(Error::IO(e, vault_folder.to_owned())),
+        };
+        let mut identifiers = Vec::new();
+
+        for entry in entries {
+            let entry = entry.map_err(|e| Error::IO(e, vault_folder.to_owned()))?;
+            let is_file = entry.file_type().is_ok_and(|file_type| file_type.is_file());
+            let name = entry.file_name();
+            let identifier = name.to_str().and_then(|name| name.strip_suffix(EXTENSION));
+
+            match identifier {
+                Some(identifier) if is_file && !identifier.is_empty() => {
+                    identifiers.push(identifier.to_owned())
+                }
+                _ => (),
+            }
+        }
+        identifiers.sort();
+        Ok(identifiers)
+    }
+
     /// Saves [Vault] contents to disk.
     ///
+    /// The previous version of the file is first kept as backup 1, shifting older backups up by one
+    /// and discarding those beyond the [backup count](Vault::set_backup_count). Backups are stored
+    /// next to the vault as `{file}.bak.{n}`, copied with the same permissions as the vault file.
+    ///
+    /// The contents are first written to `{file}.tmp`, which then replaces the file. Since replacing
+    /// a file is atomic, the file holds either the previous or the new contents if saving is
+    /// interrupted, and never a mix of both. The temporary file is removed if saving fails.
+    ///
+    /// Since the file contains the pepper, it is made accessible only to the current user on unix,
+    /// i.e. mode `0600` for the file and its backups and `0700` for its folder. This is a no-op on
+    /// other platforms; on Windows, the file inherits the access control list of its folder instead.
+    ///
     /// # Errors
     /// * [`Error::JSON`] on internal [`serde_json`] errors.
-    /// * [`Error::IO`] if file could not be written to.
+    /// * [`Error::IO`] if file could not be written to, or its backups could not be rotated.
+    ///
+    /// This is a no-op for [scratch](Vault::scratch) vaults.
     pub fn save(&self) -> Result<()> {
+        if self.scratch {
+            return Ok(());
+        }
+        if self.backup_count > 0 && self.path.is_file() {
+            for n in (1..self.backup_count).rev() {
+                let from = self.backup_path(n);
+
+                if from.is_file() {
+                    let to = self.backup_path(n + 1);
+                    fs::rename(&from, &to).map_err(|e| Error::IO(e, to))?;
+                }
+            }
+            let backup = self.backup_path(1);
+            fs::copy(&self.path, &backup).map_err(|e| Error::IO(e, backup.clone()))?;
+            restrict_permissions(&backup, 0o600)?;
+        }
+        if let Some(folder) = self.path.parent().filter(|folder| folder.is_dir()) {
+            restrict_permissions(folder, 0o700)?;
+        }
         let string = serde_json::to_string_pretty(self).unwrap();
-        fs::write(&self.path, string).map_err(|e| Error::IO(e, self.path.clone()))
+
+        // the contents are written to a temporary file which then replaces the vault file, such that
+        // the vault file is never left partially written, e.g. if the process is killed
+        let temp = self.temp_path();
+        let result = write_private(&temp, &string)
+            .and_then(|_| fs::rename(&temp, &self.path).map_err(|e| Error::IO(e, self.path.clone())));
+
+        if result.is_err() {
+            let _ = fs::remove_file(&temp);
+        }
+        result?;
+        self.file_hash.set(&string);
+        Ok(())
+    }
+
+    /// Returns whether the vault file has been changed by another program since it was last read
+    /// or written by this vault, e.g. edited by hand. Saving would then overwrite those changes. A
+    /// file which no longer exists is considered changed.
+    ///
+    /// # Errors
+    /// * [`Error::IO`] if the file exists but could not be read.
+    ///
+    /// Scratch vaults are never considered changed, since they have no file.
+    pub fn changed_on_disk(&self) -> Result<bool> {
+        if self.scratch {
+            return Ok(false);
+        }
+        match fs::read_to_string(&self.path) {
+            Ok(string) => Ok(!self.file_hash.matches(&string)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(true),
+            Err(e) => Err(Error::IO(e, self.path.clone())),
+        }
+    }
+
+    /// Compares the seeds of the vault with those in the vault file, e.g. to show what was changed
+    /// on disk. See [`Vault::changed_on_disk`].
+    ///
+    /// # Errors
+    /// As [`Vault::load`].
+    pub fn diff_on_disk(&self) -> Result<VaultDiff> {
+        let disk = Vault::read(self.path.clone())?;
+        Ok(VaultDiff::new(&self.seeds, &disk.seeds))
+    }
+
+    /// Replaces the contents of the vault with those of the vault file, discarding unsaved changes.
+    ///
+    /// # Errors
+    /// As [`Vault::load`].
+    pub fn reload(&mut self) -> Result<()> {
+        self.replace_with(Vault::read(self.path.clone())?);
+        Ok(())
+    }
+
+    /// Copies the seeds of the vault file which are not in the vault, keeping unsaved changes to
+    /// other seeds. Returns the number of seeds copied. See [`Vault::merge`].
+    ///
+    /// # Errors
+    /// As [`Vault::load`].
+    pub fn merge_from_disk(&mut self) -> Result<usize> {
+        let disk = Vault::read(self.path.clone())?;
+        let count = self.merge_seeds(&disk.seeds, ConflictPolicy::Skip);
+
+        // the merged vault accounts for the changes on disk, so saving no longer overwrites them
+        self.file_hash = disk.file_hash;
+        Ok(count)
+    }
+
+    /// Sets the number of previous versions of the vault file kept by [`Vault::save`], which
+    /// defaults to [`DEFAULT_BACKUP_COUNT`]. Backups are disabled if 0.
+    pub fn set_backup_count(&mut self, count: usize) {
+        self.backup_count = count;
+    }
+
+    /// Returns the number of previous versions of the vault file kept by [`Vault::save`].
+    pub fn backup_count(&self) -> usize {
+        self.backup_count
+    }
+
+    /// Replaces the contents of the vault with those of backup `n`, where 1 is the most recent. The
+    /// vault is not saved, such that the current file is itself backed up once it is.
+    ///
+    /// # Errors
+    /// * [`Error::IO`] if the backup does not exist.
+    /// * [`Error::JSON`] if the backup contains corrupted data.
+    /// * [`Error::PepperUnavailable`] if the pepper of the backup is stored externally and cannot
+    ///   be read.
+    pub fn restore_backup(&mut self, n: usize) -> Result<()> {
+        let backup = Vault::read(self.backup_path(n))?;
+        let file_hash = mem::take(&mut self.file_hash);
+
+        // the vault file itself is unchanged
+        self.replace_with(backup);
+        self.file_hash = file_hash;
+        Ok(())
+    }
+
+    /// Replaces the contents of the vault with those of another read from disk, keeping the
+    /// settings which are not stored in the file.
+    fn replace_with(&mut self, other: Vault) {
+        *self = Vault {
+            path: self.path.clone(),
+            backup_count: self.backup_count,
+            target_auth_params: self.target_auth_params,
+            observer: mem::take(&mut self.observer),
+            ..other
+        };
+        self.notify(VaultEvent::Reloaded);
+    }
+
+    /// Calculates the path of the temporary file written by [`Vault::save`].
+    fn temp_path(&self) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(".tmp");
+        path.into()
+    }
+
+    /// Calculates the path of backup `n` of the vault file.
+    fn backup_path(&self, n: usize) -> PathBuf {
+        Vault::backup_path_of(&self.path, n)
+    }
+
+    /// Calculates the path of backup `n` of a vault file stored at `path`.
+    fn backup_path_of(path: &Path, n: usize) -> PathBuf {
+        let mut path = path.to_owned().into_os_string();
+        path.push(format!(".bak.{n}"));
+        path.into()
+    }
+
+    /// Saves the vault under another identifier, after which it is stored in the file of that
+    /// identifier. Scratch vaults are thereby persisted, and are no longer scratch vaults.
+    ///
+    /// # Errors
+    /// * [`Error::VaultNameConflict`] if a [Vault] with given identifier already exists on disk.
+    /// * [`Error::NotADirectory`] if the vault folder path exists but is not a folder.
+    /// * [`Error::IO`] if creation of vault folder fails, or the file could not be written to.
+    ///
+    /// The vault is left unchanged on errors.
+    pub fn save_as(&mut self, vault_folder: &Path, identifier: String) -> Result<()> {
+        check_folder(vault_folder)?;
+        let path = Vault::path_of(vault_folder, &identifier);
+
+        if path.exists() {
+            return Err(Error::VaultNameConflict(identifier));
+        }
+        fs::create_dir_all(vault_folder).map_err(|e| Error::IO(e, vault_folder.to_owned()))?;
+        restrict_permissions(vault_folder, 0o700)?;
+
+        let previous = (
+            mem::replace(&mut self.path, path),
+            mem::replace(&mut self.identifier, identifier),
+            mem::replace(&mut self.scratch, false),
+        );
+        self.save().inspect_err(|_| (self.path, self.identifier, self.scratch) = previous)
+    }
+
+    /// Changes the identifier of the vault, moving its file and backups to the path of the new
+    /// identifier, and saves the vault. The file is left in place if the new identifier maps to the
+    /// same file name, e.g. when only changing case.
+    ///
+    /// # Errors
+    /// * [`Error::VaultNameConflict`] if another [Vault] with given identifier exists on disk.
+    /// * [`Error::NotADirectory`] if the vault folder path exists but is not a folder.
+    /// * [`Error::IO`] if the file or its backups could not be moved, or the file could not be
+    ///   written to.
+    ///
+    /// The vault and its files are left unchanged if they could not be moved.
+    pub fn rename(&mut self, new_identifier: String) -> Result<()> {
+        let folder = self.path.parent().unwrap_or(Path::new("")).to_owned();
+        check_folder(&folder)?;
+        let new_path = Vault::path_of(&folder, &new_identifier);
+
+        if new_path != self.path {
+            if new_path.exists() {
+                return Err(Error::VaultNameConflict(new_identifier));
+            }
+            if !self.scratch {
+                self.move_files(&new_path)?;
+                self.path = new_path;
+            }
+        }
+        self.identifier = new_identifier;
+        self.save()
+    }
+
+    /// Moves the vault file and its backups to `new_path`, such that the backups may still be
+    /// restored. The backups are moved before the file, and the files already moved are moved
+    /// back should any move fail.
+    fn move_files(&self, new_path: &Path) -> Result<()> {
+        let mut moves: Vec<(PathBuf, PathBuf)> = (1..=self.backup_count)
+            .map(|n| (self.backup_path(n), Vault::backup_path_of(new_path, n)))
+            .filter(|(from, _)| from.is_file())
+            .collect();
+        moves.push((self.path.clone(), new_path.to_owned()));
+
+        for (i, (from, to)) in moves.iter().enumerate() {
+            if let Err(e) = fs::rename(from, to) {
+                for (from, to) in &moves[..i] {
+                    let _ = fs::rename(to, from);
+                }
+                return Err(Error::IO(e, from.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns whether the vault is kept in memory only. See [`Vault::scratch`].
+    pub fn is_scratch(&self) -> bool {
+        self.scratch
+    }
+
+    /// Returns the path of the [Vault] on disk, which is empty for [scratch](Vault::scratch) vaults.
+    pub fn path(&self) -> &Path {
+        &self.path
     }
 
     /// Returns a slice of the [Vault] identifier.
@@ -106,6 +575,28 @@ impl Vault {
         &self.identifier
     }
 
+    /// Moves the pepper to an external file, such as one on removable media, leaving only a
+    /// reference to it in the vault. Once saved, the vault cannot be loaded without the file. See
+    /// [`PepperSource::External`].
+    ///
+    /// # Errors
+    /// * [`Error::IO`] if the file already exists or could not be written to.
+    pub fn externalize_pepper(&mut self, path: &Path) -> Result<()> {
+        private_file()
+            .create_new(true)
+            .open(path)
+            .and_then(|mut file| io::Write::write_all(&mut file, &self.pepper))
+            .map_err(|e| Error::IO(e, path.to_owned()))?;
+
+        self.pepper_source = PepperSource::External { path: path.to_owned() };
+        Ok(())
+    }
+
+    /// Returns where the pepper is stored.
+    pub fn pepper_source(&self) -> &PepperSource {
+        &self.pepper_source
+    }
+
     /// Returns a slice of the pepper.
     pub fn pepper(&self) -> &[u8] {
         &self.pepper
@@ -116,14 +607,184 @@ impl Vault {
         &self.seeds
     }
 
-    /// Inserts a new [Seed] in the back.
-    pub fn push(&mut self, seed: Seed) {
+    /// Summarizes the metadata of all stored [Seeds](Seed).
+    pub fn stats(&self) -> VaultStats {
+        VaultStats::new(&self.seeds)
+    }
+
+    /// Flags seeds which produce weak passwords according to the default [`AuditThresholds`]. Each
+    /// finding is paired with the index of the flagged seed.
+    pub fn audit(&self) -> Vec<(usize, AuditFinding)> {
+        self.audit_with(&AuditThresholds::default())
+    }
+
+    /// Flags seeds which produce weak passwords according to the given thresholds. Each finding is
+    /// paired with the index of the flagged seed.
+    pub fn audit_with(&self, thresholds: &AuditThresholds) -> Vec<(usize, AuditFinding)> {
+        audit::audit(&self.seeds, thresholds)
+    }
+
+    /// Returns the index pairs of seeds which generate the same passwords, such that one of each
+    /// pair should be changed. See [`Seed::generates_like`].
+    pub fn check_collisions(&self) -> Vec<(usize, usize)> {
+        audit::collisions(&self.seeds)
+    }
+
+    /// Inserts a new [Seed] in the back, setting its [generation tag](Seed::gen_tag) to its
+    /// identifier if unset.
+    ///
+    /// The identifier is not checked, such that seeds may share identifiers, e.g. when loaded from
+    /// files edited by hand. Prefer [`Vault::try_push`] for seeds entered by the user.
+    pub fn push(&mut self, mut seed: Seed) {
+        seed.init_gen_tag();
         self.seeds.push(seed);
+        self.notify(VaultEvent::Inserted(self.seeds.len() - 1));
+    }
+
+    /// Inserts a new [Seed] in the back like [`Vault::push`], unless its identifier is empty or
+    /// already taken by another seed, ignoring case. Its [generation tag](Seed::gen_tag) is made
+    /// unique as by [`Vault::insert`].
+    ///
+    /// # Errors
+    /// * [`Error::EmptyIdentifier`] if the identifier is empty.
+    /// * [`Error::DuplicateSeed`] if the identifier is taken.
+    pub fn try_push(&mut self, seed: Seed) -> Result<()> {
+        self.insert(self.seeds.len(), seed)
+    }
+
+    /// Inserts a new [Seed] at specified index, shifting the seeds after it back, and setting its
+    /// [generation tag](Seed::gen_tag) to its identifier if unset. The identifier is checked as by
+    /// [`Vault::try_push`].
+    ///
+    /// If another seed already uses the generation tag, e.g. since it was renamed from the
+    /// identifier of the new seed, a number is appended to the tag of the new seed. Otherwise, the
+    /// new seed would silently reproduce the password of the other.
+    ///
+    /// # Errors
+    /// * [`Error::SeedIndex`] if `seed_index` is greater than the number of seeds.
+    /// * [`Error::EmptyIdentifier`] if the identifier is empty.
+    /// * [`Error::DuplicateSeed`] if the identifier is taken.
+    pub fn insert(&mut self, seed_index: usize, mut seed: Seed) -> Result<()> {
+        if seed_index > self.seeds.len() {
+            return Err(Error::SeedIndex(seed_index));
+        }
+        if seed.identifier.trim().is_empty() {
+            return Err(Error::EmptyIdentifier);
+        }
+        if self.identifier_taken(&seed.identifier, None) {
+            return Err(Error::DuplicateSeed(seed.identifier));
+        }
+        seed.init_gen_tag();
+        seed.gen_tag = self.available_gen_tag(&seed.gen_tag, None);
+        self.seeds.insert(seed_index, seed);
+        self.notify(VaultEvent::Inserted(seed_index));
+        Ok(())
+    }
+
+    /// Inserts a copy of the [Seed] at specified index right after it, and returns the index of the
+    /// copy.
+    ///
+    /// The copy is identified by appending " copy" to the original identifier (followed by a number
+    /// if needed to make it unique). Its [generation tag](Seed::gen_tag) is set to the new
+    /// identifier, such that the copy generates a different password than the original, followed
+    /// by a number if another seed already uses it as tag.
+    ///
+    /// # Errors
+    /// * [`Error::SeedIndex`] if `seed_index` is out-of-bounds.
+    pub fn duplicate(&mut self, seed_index: usize) -> Result<usize> {
+        let mut copy = self.get(seed_index)?.clone();
+        copy.identifier = self.available_identifier(&format!("{} copy", copy.identifier));
+        copy.gen_tag = self.available_gen_tag(&copy.identifier, None);
+        self.seeds.insert(seed_index + 1, copy);
+        self.notify(VaultEvent::Inserted(seed_index + 1));
+        Ok(seed_index + 1)
+    }
+
+    /// Copies all seeds of another [Vault] into this one, resolving identifier conflicts (ignoring
+    /// case) according to `on_conflict`. Returns the number of seeds copied.
+    ///
+    /// Note that the copied seeds generate passwords using the pepper of this vault, and the key
+    /// used with it. Their passwords therefore differ from those generated in the other vault.
+    /// Copied seeds whose [generation tag](Seed::gen_tag) is used by another seed are given an
+    /// unused one, as by [`Vault::insert`], such that no two seeds generate the same passwords.
+    ///
+    /// # Errors
+    /// * [`Error::SelfMerge`] if both vaults are stored at the same path.
+    pub fn merge(&mut self, other: &Vault, on_conflict: ConflictPolicy) -> Result<usize> {
+        if self.path == other.path {
+            return Err(Error::SelfMerge(self.identifier.clone()));
+        }
+        Ok(self.merge_seeds(&other.seeds, on_conflict))
+    }
+
+    /// Copies seeds into the vault as described by [`Vault::merge`].
+    fn merge_seeds(&mut self, seeds: &[Seed], on_conflict: ConflictPolicy) -> usize {
+        let mut count = 0;
+
+        for seed in seeds {
+            let identifier = seed.identifier.to_lowercase();
+            let conflict = self.seeds.iter().position(|s| s.identifier.to_lowercase() == identifier);
+            let mut seed = seed.clone();
+            seed.init_gen_tag();
+
+            let event = match (conflict, on_conflict) {
+                (None, _) => {
+                    seed.gen_tag = self.available_gen_tag(&seed.gen_tag, None);
+                    self.seeds.push(seed);
+                    VaultEvent::Inserted(self.seeds.len() - 1)
+                }
+                (Some(_), ConflictPolicy::Skip) => continue,
+                (Some(_), ConflictPolicy::Rename) => {
+                    seed.identifier = self.available_identifier(&seed.identifier);
+                    seed.gen_tag = self.available_gen_tag(&seed.gen_tag, None);
+                    self.seeds.push(seed);
+                    VaultEvent::Inserted(self.seeds.len() - 1)
+                }
+                (Some(i), ConflictPolicy::Replace) => {
+                    seed.gen_tag = self.available_gen_tag(&seed.gen_tag, Some(i));
+                    self.seeds[i] = seed;
+                    VaultEvent::Edited(i)
+                }
+            };
+            self.notify(event);
+            count += 1;
+        }
+        count
+    }
+
+    /// Returns `base` if no seed uses it as identifier, ignoring case, otherwise `base` followed by
+    /// the lowest number making it unique.
+    fn available_identifier(&self, base: &str) -> String {
+        (1..)
+            .map(|n| if n == 1 { base.to_owned() } else { format!("{base} {n}") })
+            .find(|identifier| !self.identifier_taken(identifier, None))
+            .unwrap()
+    }
+
+    /// Returns `base` if no seed other than the one at index `except` uses it as [generation
+    /// tag](Seed::gen_tag), otherwise `base` followed by the lowest number making it unique.
+    fn available_gen_tag(&self, base: &str, except: Option<usize>) -> String {
+        (1..)
+            .map(|n| if n == 1 { base.to_owned() } else { format!("{base} {n}") })
+            .find(|tag| {
+                !self
+                    .seeds
+                    .iter()
+                    .enumerate()
+                    .any(|(i, seed)| Some(i) != except && seed.gen_tag == *tag)
+            })
+            .unwrap()
     }
 
     /// Removes [Seed] at specified index.
-    pub fn remove(&mut self, seed_index: usize) {
+    ///
+    /// # Errors
+    /// * [`Error::SeedIndex`] if `seed_index` is out-of-bounds.
+    pub fn remove(&mut self, seed_index: usize) -> Result<()> {
+        self.get(seed_index)?;
         self.seeds.remove(seed_index);
+        self.notify(VaultEvent::Removed(seed_index));
+        Ok(())
     }
 
     /// Gets the seed at specified index.
@@ -136,6 +797,80 @@ impl Vault {
             .ok_or(Error::SeedIndex(seed_index))
     }
 
+    /// Records that the password of the seed at specified index was generated now, e.g. after a
+    /// front-end displayed or copied it. Kept separate from [`Vault::password`], such that
+    /// generating remains possible without mutable access. See [`Seed::last_generated`].
+    ///
+    /// # Errors
+    /// * [`Error::SeedIndex`] if `seed_index` is out-of-bounds.
+    pub fn mark_generated(&mut self, seed_index: usize) -> Result<()> {
+        self.seeds
+            .get_mut(seed_index)
+            .map(|seed| seed.last_generated = Some(SystemTime::now()))
+            .ok_or(Error::SeedIndex(seed_index))
+    }
+
+    /// Replaces the seed at specified index. The vault is left unchanged if the new seed is
+    /// invalid.
+    ///
+    /// # Errors
+    /// * [`Error::SeedIndex`] if `seed_index` is out-of-bounds.
+    /// * [`Error::EmptyIdentifier`] if the identifier of `seed` is empty or only whitespace.
+    /// * [`Error::DuplicateSeed`] if another seed already uses the identifier of `seed`, ignoring
+    ///   case.
+    /// * [`Error::InvalidSeed`] if `seed` cannot produce a password, see [`generate::validate`].
+    pub fn edit_seed(&mut self, seed_index: usize, seed: Seed) -> Result<()> {
+        self.check_identifier(seed_index, &seed.identifier)?;
+        generate::validate(&seed)?;
+        self.check_prefix_fits(&seed)?;
+        self.seeds[seed_index] = seed;
+        self.notify(VaultEvent::Edited(seed_index));
+        Ok(())
+    }
+
+    /// Checks that the seed at specified index exists, and may be identified by `identifier`.
+    ///
+    /// # Errors
+    /// As [`Vault::rename_seed`].
+    fn check_identifier(&self, seed_index: usize, identifier: &str) -> Result<()> {
+        self.get(seed_index)?;
+
+        if identifier.trim().is_empty() {
+            return Err(Error::EmptyIdentifier);
+        }
+        if self.identifier_taken(identifier, Some(seed_index)) {
+            return Err(Error::DuplicateSeed(identifier.to_owned()));
+        }
+        Ok(())
+    }
+
+    /// Returns whether a seed other than the one at index `except` uses `identifier`, ignoring
+    /// case.
+    fn identifier_taken(&self, identifier: &str, except: Option<usize>) -> bool {
+        let identifier = identifier.to_lowercase();
+
+        self.seeds
+            .iter()
+            .enumerate()
+            .any(|(i, seed)| Some(i) != except && seed.identifier.to_lowercase() == identifier)
+    }
+
+    /// Changes the identifier of the seed at specified index, leaving its other parameters intact.
+    ///
+    /// Since passwords are generated from the [generation tag](Seed::gen_tag) rather than the
+    /// identifier, renaming a seed does not change its password.
+    ///
+    /// # Errors
+    /// * [`Error::SeedIndex`] if `seed_index` is out-of-bounds.
+    /// * [`Error::EmptyIdentifier`] if `identifier` is empty or only whitespace.
+    /// * [`Error::DuplicateSeed`] if another seed already uses `identifier`, ignoring case.
+    pub fn rename_seed(&mut self, seed_index: usize, identifier: String) -> Result<()> {
+        self.check_identifier(seed_index, &identifier)?;
+        self.seeds[seed_index].identifier = identifier;
+        self.notify(VaultEvent::Edited(seed_index));
+        Ok(())
+    }
+
     /// Swaps seeds at specified indices.
     ///
     /// # Errors
@@ -146,71 +881,1160 @@ impl Vault {
         if max >= self.seeds.len() {
             Err(Error::SeedIndex(max))
         } else {
-            Ok(self.seeds.swap(a, b))
+            self.seeds.swap(a, b);
+            self.notify(VaultEvent::Swapped(a, b));
+            Ok(())
         }
     }
 
-    /// Extracts the password based on the given [Seed].
+    /// Reorders the stored seeds alphabetically by the given field, ignoring case. Seeds without the
+    /// field are placed last, and ties are broken by identifier. Only the order changes, which
+    /// keeps the vault file tidy under version control; passwords are unaffected.
+    pub fn sort_seeds(&mut self, by: SortKey) {
+        self.seeds.sort_by_cached_key(|seed| {
+            let field = match by {
+                SortKey::Identifier => Some(&seed.identifier),
+                SortKey::Username => seed.username.as_ref(),
+                SortKey::Url => seed.url.as_ref(),
+            };
+            let field = field.map(|field| field.to_lowercase());
+            (field.is_none(), field, seed.identifier.to_lowercase())
+        });
+        self.notify(VaultEvent::Reordered);
+    }
+
+    /// Registers a callback invoked after each change to the seeds of the vault, replacing any
+    /// previous one. This allows front-ends to react to changes without comparing the seeds.
     ///
-    /// In order to maintain flexibility, the given key is not verified. To verify the key, first
-    /// call [`Vault::verify_key`].
-    pub fn password(&self, seed: &Seed, key: &str) -> String {
-        generate::password(key, &self.pepper, seed)
+    /// The callback is invoked while the vault is mutably borrowed, so it cannot access the vault
+    /// itself; the [event](VaultEvent) describes the change instead.
+    pub fn set_observer(&mut self, observer: impl FnMut(&VaultEvent) + Send + Sync + 'static) {
+        self.observer = Observer(Some(Box::new(observer)));
     }
 
-    /// Verifies the hash of the entered key against a hash of the key entered when the vault was
-    /// created.
-    pub fn verify_key(&self, key: &str) -> bool {
-        generate::auth_token(key, self.pepper()) == self.auth_token
+    /// Removes the callback registered with [`Vault::set_observer`].
+    pub fn clear_observer(&mut self) {
+        self.observer = Observer::default();
     }
 
-    /// Calculates the path of a vault, normalizing the vault name to adhere to the POSIX portable
-    /// filename standard.
-    fn path_of(folder: &Path, identifier: &str) -> PathBuf {
-        const EXTENSION: &str = ".vault";
-        const LEGAL_SYMBOLS: &str = "._-";
+    fn notify(&mut self, event: VaultEvent) {
+        if let Some(observer) = &mut self.observer.0 {
+            observer(&event);
+        }
+    }
 
-        let file_name: String = identifier
-            .ascii_chars()               // attempt to convert all non-ascii charcters
-            .flatten()                   // discard characters with no known ascii representation
-            .flat_map(|str| str.chars()) // iterate over all converted characters
-            .filter_map(|c| {            // filter or normalize invalid path characters
-                if LEGAL_SYMBOLS.contains(c) {
-                    Some(c)
-                } else if c.is_alphanumeric() {
-                    Some(c.to_ascii_lowercase())
-                } else if c == ' ' {
-                    Some('_')
-                } else {
-                    None
-                }
-            })
-            .take(255 - EXTENSION.len()) // enforce max length of filename
-            .chain(EXTENSION.chars())    // add extension
-            .collect();
-        [folder, Path::new(&file_name)].iter().collect()
+    /// Extracts the password based on the given [Seed], starting with the [prefix](Vault::prefix)
+    /// of the vault if set.
+    ///
+    /// In order to maintain flexibility, the given key is not verified. An incorrect key silently
+    /// produces an incorrect password, so unless the key has already been verified with
+    /// [`Vault::verify_key`], prefer [`Vault::password_verified`]. Neither is it verified that the
+    /// prefix fits within the length of the seed; if it doesn't, the password is too long.
+    ///
+    /// The password is cleared from memory once dropped.
+    ///
+    /// # Errors
+    /// * [`Error::InvalidSeed`] if the seed cannot produce a password. See [`generate::validate`].
+    /// * [`Error::Hashing`] if argon2 rejects the [cost parameters](Seed::argon2) of the seed.
+    pub fn password(&self, seed: &Seed, key: &str) -> Result<Zeroizing<String>> {
+        generate::validate(seed)?;
+        let seed = self.core_seed(seed);
+        let core = generate::password_with(
+            key,
+            &self.pepper,
+            &seed,
+            self.normalization,
+            self.argon2_variant,
+        )?;
+        Ok(Zeroizing::new(format!("{}{}", self.prefix().unwrap_or_default(), *core)))
     }
-}
 
-/// Contains all [Vault] errors and their respective messages.
-#[derive(Error, Debug)]
-pub enum Error {
-    #[error("Vault name '{0}' already exists. Try a different name")]
-    VaultNameConflict(String),
-    #[error("Seed index {0} out-of-bounds. This is a bug, please report to Mr. Simon.")]
-    SeedIndex(usize),
-    #[error("{1}: {0}")]
-    IO(io::Error, PathBuf),
-    #[error("Could not parse JSON in {1}. Attempt to fix manually and retry: {0}")]
-    JSON(serde_json::Error, PathBuf),
-}
+    /// Verifies the key and extracts the password based on the given [Seed]. The password is then
+    /// [checked](generate::check) against the requirements of the seed, such that a password not
+    /// meeting them is never returned.
+    ///
+    /// # Errors
+    /// * [`Error::KeyMismatch`] if the key is incorrect.
+    /// * [`Error::InvalidSeed`] if the seed cannot produce a password, or the prefix leaves no room
+    ///   for it.
+    /// * [`Error::Hashing`] if argon2 rejects the [cost parameters](Seed::argon2) of the seed.
+    /// * [`Error::UnmetRequirements`] if the password does not meet the requirements of the seed.
+    pub fn password_verified(&self, seed: &Seed, key: &str) -> Result<Zeroizing<String>> {
+        if !self.verify_key(key) {
+            return Err(Error::KeyMismatch(self.identifier.clone()));
+        }
+        generate::validate(seed)?;
+        self.check_prefix_fits(seed)?;
+        let core_seed = self.core_seed(seed);
+        let core = generate::password_with(
+            key,
+            &self.pepper,
+            &core_seed,
+            self.normalization,
+            self.argon2_variant,
+        )?;
+        generate::check(&core, &core_seed)?;
+        Ok(Zeroizing::new(format!("{}{}", self.prefix().unwrap_or_default(), *core)))
+    }
 
-/// Result type using the Svalbard [Error](crate::Error) enum.
-pub type Result<T> = result::Result<T, Error>;
+    /// Gets the normalization applied to the key and generation tags before hashing.
+    pub fn normalization(&self) -> Normalization {
+        self.normalization
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Gets the variant of argon2 used to hash passwords.
+    pub fn argon2_variant(&self) -> Argon2Variant {
+        self.argon2_variant
+    }
+
+    /// Creates a seed with the [defaults](Vault::seed_defaults) of the vault, without inserting it.
+    pub fn new_seed(&self, identifier: String, username: Option<String>) -> Seed {
+        Seed::from_defaults(identifier, username, &self.seed_defaults)
+    }
+
+    /// Gets the parameters new seeds start out with.
+    pub fn seed_defaults(&self) -> &SeedDefaults {
+        &self.seed_defaults
+    }
+
+    /// Sets the parameters new seeds start out with. Existing seeds are unaffected.
+    ///
+    /// # Errors
+    /// * [`Error::InvalidSeed`] if seeds with the defaults cannot produce a password, see
+    ///   [`generate::validate`]. The defaults are then left unchanged.
+    pub fn set_seed_defaults(&mut self, defaults: SeedDefaults) -> Result<()> {
+        generate::validate(&Seed::from_defaults("defaults".to_owned(), None, &defaults))?;
+        self.seed_defaults = defaults;
+        Ok(())
+    }
+
+    /// Gets the prefix of all passwords, if any.
+    pub fn prefix(&self) -> Option<&str> {
+        self.prefix.as_deref()
+    }
+
+    /// Sets the prefix of all passwords, or removes it if [None]. The remainder of each password is
+    /// generated as before, but shortened by the length of the prefix such that the length range
+    /// of each seed still holds. Since this changes every password of the vault, the passwords of
+    /// all corresponding services must be updated too.
+    ///
+    /// The vault prefix comes first, such that any prefix of a seed follows it.
+    ///
+    /// # Errors
+    /// * [`Error::InvalidSeed`] if the prefix leaves no room for generated characters within the
+    ///   max length of a seed. The prefix is then left unchanged.
+    pub fn set_prefix(&mut self, prefix: Option<String>) -> Result<()> {
+        let previous = std::mem::replace(&mut self.prefix, prefix.filter(|p| !p.is_empty()));
+
+        if let Some(e) = self.seeds.iter().find_map(|seed| self.check_prefix_fits(seed).err()) {
+            self.prefix = previous;
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Checks that at least one generated character fits after the prefix within the max length
+    /// of a seed.
+    fn check_prefix_fits(&self, seed: &Seed) -> Result<()> {
+        let len = self.prefix().map_or(0, |prefix| prefix.chars().count());
+
+        if len >= seed.max_len as usize {
+            let reason = format!("vault prefix of length {len} leaves no room within the max length");
+            return Err(Error::InvalidSeed(seed.identifier.clone(), reason));
+        }
+        Ok(())
+    }
+
+    /// Returns the seed generating the part of a password following the prefix, i.e. with its
+    /// length range shortened by the length of the prefix.
+    fn core_seed<'a>(&self, seed: &'a Seed) -> Cow<'a, Seed> {
+        match self.prefix() {
+            None => Cow::Borrowed(seed),
+            Some(prefix) => {
+                let len = prefix.chars().count().min(u8::MAX as usize) as u8;
+                let mut core = seed.clone();
+                core.min_len = seed.min_len.saturating_sub(len).max(1);
+                core.max_len = seed.max_len.saturating_sub(len).max(1);
+                Cow::Owned(core)
+            }
+        }
+    }
+
+    /// Changes the key of the vault, replacing the authentication token with one of the new key.
+    /// The new token is generated with the [target parameters](Vault::set_auth_params) if they are
+    /// [at least as strong](Argon2Params::at_least) as the current ones, and with the current ones
+    /// otherwise.
+    ///
+    /// Since the key participates in password generation, every password generated by the vault
+    /// changes, and the passwords of all corresponding services must be updated too. The returned
+    /// [KeyChange] lists them, such that front-ends may remind the user.
+    ///
+    /// # Errors
+    /// * [`Error::KeyVerificationFailed`] if `old_key` is incorrect.
+    /// * [`Error::Hashing`] if argon2 rejects the [target parameters](Vault::set_auth_params).
+    pub fn change_key(&mut self, old_key: &str, new_key: &str) -> Result<KeyChange> {
+        if !self.verify_key(old_key) {
+            return Err(Error::KeyVerificationFailed(self.identifier.clone()));
+        }
+        let params = match self.target_auth_params.at_least(&self.auth_params) {
+            true => self.target_auth_params,
+            false => self.auth_params,
+        };
+        self.auth_token =
+            generate::auth_token_with(new_key, &self.pepper, &params, self.normalization)?;
+        self.auth_params = params;
+
+        let changed_passwords = self.seeds.iter().map(|seed| seed.identifier.clone()).collect();
+        Ok(KeyChange { changed_passwords })
+    }
+
+    /// Verifies the hash of the entered key against a hash of the key entered when the vault was
+    /// created. The hash is computed with the parameters the stored hash was generated with. No key
+    /// is correct if argon2 rejects them.
+    pub fn verify_key(&self, key: &str) -> bool {
+        let token =
+            generate::auth_token_with(key, self.pepper(), &self.auth_params, self.normalization);
+        matches!(token, Ok(token) if generate::tokens_match(&token, &self.auth_token))
+    }
+
+    /// Verifies the key like [`Vault::verify_key`]. If it is correct but the authentication token
+    /// was generated with weaker parameters than the [target ones](Vault::set_auth_params), the
+    /// token is regenerated with the target parameters and written to the vault file. Tokens are
+    /// never downgraded. Returns whether the key is correct.
+    ///
+    /// Only the token is written; the rest of the file is kept as it is on disk, such that unsaved
+    /// changes to the vault are not saved along with it.
+    ///
+    /// # Errors
+    /// * [`Error::Hashing`] if argon2 rejects the target parameters.
+    /// * [`Error::IO`] if the vault file cannot be read or written.
+    /// * [`Error::JSON`] if the vault file contains corrupted data.
+    pub fn verify_and_upgrade(&mut self, key: &str) -> Result<bool> {
+        if !self.verify_key(key) {
+            return Ok(false);
+        }
+        let params = self.target_auth_params;
+
+        if params != self.auth_params && params.at_least(&self.auth_params) {
+            let token = generate::auth_token_with(key, &self.pepper, &params, self.normalization)?;
+            self.save_auth_token(&token, params)?;
+            self.auth_token = token;
+            self.auth_params = params;
+        }
+        Ok(true)
+    }
+
+    /// Replaces the authentication token in the vault file, keeping the rest of the file as it is.
+    /// The file is left as is if it holds another token, e.g. if the key was changed by another
+    /// program, or if the vault has not been saved yet; the token is then saved with the vault.
+    fn save_auth_token(&mut self, token: &[u8], params: Argon2Params) -> Result<()> {
+        if self.scratch || !self.path.is_file() {
+            return Ok(());
+        }
+        let mut disk = Vault::read(self.path.clone())?;
+
+        if disk.auth_token != self.auth_token {
+            return Ok(());
+        }
+        let unchanged = !self.changed_on_disk()?;
+        disk.auth_token = token.to_owned();
+        disk.auth_params = params;
+        disk.backup_count = self.backup_count;
+        disk.save()?;
+
+        // the file only differs from what the vault last read by the token, which it now holds too
+        if unchanged {
+            self.file_hash = disk.file_hash;
+        }
+        Ok(())
+    }
+
+    /// Sets the parameters of the authentication token. Since the key is needed to regenerate the
+    /// token, existing tokens are only upgraded once the key is next verified with
+    /// [`Vault::verify_and_upgrade`] or [changed](Vault::change_key).
+    pub fn set_auth_params(&mut self, params: Argon2Params) {
+        self.target_auth_params = params;
+    }
+
+    /// Gets the parameters the authentication token was generated with.
+    pub fn auth_params(&self) -> Argon2Params {
+        self.auth_params
+    }
+
+    /// Calculates the path of a vault, normalizing the vault name to adhere to the POSIX portable
+    /// filename standard.
+    fn path_of(folder: &Path, identifier: &str) -> PathBuf {
+        const LEGAL_SYMBOLS: &str = "._-";
+
+        let file_name: String = identifier
+            .ascii_chars()               // attempt to convert all non-ascii charcters
+            .flatten()                   // discard characters with no known ascii representation
+            .flat_map(|str| str.chars()) // iterate over all converted characters
+            .filter_map(|c| {            // filter or normalize invalid path characters
+                if LEGAL_SYMBOLS.contains(c) {
+                    Some(c)
+                } else if c.is_alphanumeric() {
+                    Some(c.to_ascii_lowercase())
+                } else if c == ' ' {
+                    Some('_')
+                } else {
+                    None
+                }
+            })
+            .take(255 - EXTENSION.len()) // enforce max length of filename
+            .chain(EXTENSION.chars())    // add extension
+            .collect();
+        [folder, Path::new(&file_name)].iter().collect()
+    }
+}
+
+/// Specifies where the pepper of a [Vault] is stored.
+///
+/// By default, the pepper is stored in the vault file itself. Alternatively, it may be stored in an
+/// external file, e.g. on removable media, such that the vault file alone is useless to an attacker:
+/// without the pepper, no passwords can be generated even if the key is known. Note that the
+/// pepper is as sensitive as the vault file, and that losing it means losing every password
+/// generated from the vault, so it must be backed up.
+#[serde_as]
+#[derive(Serialize, Deserialize, Hash, Clone, Debug, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum PepperSource {
+    /// Stored in the vault file, encoded as base64.
+    Inline(#[serde_as(as = "Base64")] Vec<u8>),
+    /// Stored in an external file. If the file is not present, e.g. because the medium holding
+    /// it is not connected, the vault fails to load with [`Error::PepperUnavailable`].
+    External { path: PathBuf },
+}
+
+impl PepperSource {
+    /// Reads the pepper from its source.
+    ///
+    /// # Errors
+    /// * [`Error::PepperUnavailable`] if the pepper is stored externally and cannot be read.
+    fn read(&self) -> Result<Vec<u8>> {
+        match self {
+            PepperSource::Inline(pepper) => Ok(pepper.clone()),
+            PepperSource::External { path } => {
+                fs::read(path).map_err(|e| Error::PepperUnavailable(e, path.to_owned()))
+            }
+        }
+    }
+}
+
+impl Drop for PepperSource {
+    /// Clears an inline pepper from memory.
+    fn drop(&mut self) {
+        if let PepperSource::Inline(pepper) = self {
+            pepper.zeroize();
+        }
+    }
+}
+
+/// Holds the pepper of a [Vault] as read from its [source](PepperSource), cleared from memory once
+/// dropped.
+#[derive(Default)]
+struct Pepper(Zeroizing<Vec<u8>>);
+
+impl From<Vec<u8>> for Pepper {
+    fn from(pepper: Vec<u8>) -> Self {
+        Pepper(Zeroizing::new(pepper))
+    }
+}
+
+impl Deref for Pepper {
+    type Target = Vec<u8>;
+
+    fn deref(&self) -> &Vec<u8> {
+        &self.0
+    }
+}
+
+impl Hash for Pepper {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.as_slice().hash(state);
+    }
+}
+
+/// Describes a change to the seeds of a [Vault], passed to the callback registered with
+/// [`Vault::set_observer`]. Indices refer to the seeds after the change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VaultEvent {
+    /// A seed was inserted at the index, e.g. by [`Vault::push`] or [`Vault::duplicate`].
+    Inserted(usize),
+    /// The seed at the index was removed.
+    Removed(usize),
+    /// The seed at the index was replaced or renamed.
+    Edited(usize),
+    /// The seeds at the indices were swapped.
+    Swapped(usize, usize),
+    /// All seeds were replaced, e.g. by [`Vault::restore_backup`].
+    Reloaded,
+    /// The seeds were reordered by [`Vault::sort_seeds`].
+    Reordered,
+}
+
+/// Holds the optional callback of [`Vault::set_observer`]. Excluded from the hash of the vault,
+/// since it is not part of its contents.
+#[derive(Default)]
+struct Observer(Option<ObserverFn>);
+
+type ObserverFn = Box<dyn FnMut(&VaultEvent) + Send + Sync>;
+
+impl Hash for Observer {
+    fn hash<H: Hasher>(&self, _: &mut H) {}
+}
+
+/// Lists the identifiers of seeds which differ between a [Vault] and its file. See
+/// [`Vault::diff_on_disk`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct VaultDiff {
+    /// Seeds only in the file, e.g. added by hand.
+    pub only_on_disk: Vec<String>,
+    /// Seeds only in the vault, e.g. added since it was loaded or removed from the file.
+    pub only_in_vault: Vec<String>,
+    /// Seeds in both, but with different parameters.
+    pub changed: Vec<String>,
+}
+
+impl VaultDiff {
+    fn new(vault: &[Seed], disk: &[Seed]) -> Self {
+        let find = |seeds: &[Seed], identifier: &str| {
+            seeds.iter().find(|seed| seed.identifier == identifier).map(|seed| {
+                serde_json::to_string(seed).unwrap()
+            })
+        };
+        let mut diff = VaultDiff::default();
+
+        for seed in disk {
+            match find(vault, &seed.identifier) {
+                None => diff.only_on_disk.push(seed.identifier.clone()),
+                Some(json) if json != serde_json::to_string(seed).unwrap() => {
+                    diff.changed.push(seed.identifier.clone());
+                }
+                Some(_) => (),
+            }
+        }
+        for seed in vault {
+            if find(disk, &seed.identifier).is_none() {
+                diff.only_in_vault.push(seed.identifier.clone());
+            }
+        }
+        diff
+    }
+
+    /// Returns whether the seeds are the same, in which case only the order of the seeds or the
+    /// settings of the vault differ.
+    pub fn is_empty(&self) -> bool {
+        self.only_on_disk.is_empty() && self.only_in_vault.is_empty() && self.changed.is_empty()
+    }
+}
+
+impl fmt::Display for VaultDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "No seeds differ.");
+        }
+        let lines = [
+            ("Only on disk", &self.only_on_disk),
+            ("Only in this session", &self.only_in_vault),
+            ("Changed", &self.changed),
+        ];
+        let lines: Vec<String> = lines
+            .iter()
+            .filter(|(_, identifiers)| !identifiers.is_empty())
+            .map(|(label, identifiers)| format!("{label}: {}", identifiers.join(", ")))
+            .collect();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+/// Describes the effect of [changing the key](Vault::change_key) of a [Vault]. Every password is
+/// derived from the key, so the passwords of all seeds change.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct KeyChange {
+    /// Identifiers of the seeds whose passwords changed, i.e. every seed of the vault.
+    pub changed_passwords: Vec<String>,
+}
+
+impl fmt::Display for KeyChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.changed_passwords.len() {
+            0 => write!(f, "No passwords changed, since the vault has no seeds."),
+            count => write!(
+                f,
+                "All {count} passwords changed. Update the services of: {}",
+                self.changed_passwords.join(", ")
+            ),
+        }
+    }
+}
+
+/// Holds the hash of the contents of a vault file. Atomic such that it may be updated by
+/// [`Vault::save`], which only borrows the vault, while the vault remains shareable between
+/// threads. Excluded from the hash of the vault, since it is not part of its contents.
+#[derive(Default)]
+struct FileHash(AtomicU64);
+
+impl FileHash {
+    fn of(string: &str) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        string.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn set(&self, string: &str) {
+        self.0.store(FileHash::of(string), Ordering::Relaxed);
+    }
+
+    fn matches(&self, string: &str) -> bool {
+        self.0.load(Ordering::Relaxed) == FileHash::of(string)
+    }
+}
+
+impl Hash for FileHash {
+    fn hash<H: Hasher>(&self, _: &mut H) {}
+}
+
+/// Specifies the field by which [`Vault::sort_seeds`] orders the seeds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    Identifier,
+    Username,
+    Url,
+}
+
+impl SortKey {
+    pub const ALL: [SortKey; 3] = [SortKey::Identifier, SortKey::Username, SortKey::Url];
+
+    /// Gets the short name of the field, e.g. for use in commands.
+    pub fn name(self) -> &'static str {
+        match self {
+            SortKey::Identifier => "identifier",
+            SortKey::Username   => "username",
+            SortKey::Url        => "url",
+        }
+    }
+}
+
+/// Specifies how to resolve seeds with conflicting identifiers in [`Vault::merge`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    /// Keeps the existing seed and discards the incoming one.
+    Skip,
+    /// Keeps both seeds, appending a number to the identifier of the incoming one.
+    Rename,
+    /// Replaces the existing seed with the incoming one.
+    Replace,
+}
+
+/// Checks that the vault folder is a folder if it exists, since operating on it would otherwise
+/// fail with confusing errors.
+///
+/// # Errors
+/// * [`Error::NotADirectory`] if the path exists but is not a folder.
+fn check_folder(vault_folder: &Path) -> Result<()> {
+    if vault_folder.exists() && !vault_folder.is_dir() {
+        return Err(Error::NotADirectory(vault_folder.to_owned()));
+    }
+    Ok(())
+}
+
+/// Writes a file accessible only to the current user on unix, and flushes it to disk.
+fn write_private(path: &Path, contents: &str) -> Result<()> {
+    let io_error = |e| Error::IO(e, path.to_owned());
+    let mut file = private_file()
+        .create(true)
+        .truncate(true)
+        .open(path)
+        .map_err(io_error)?;
+    // files created before permissions were restricted keep their mode when opened
+    restrict_permissions(path, 0o600)?;
+    io::Write::write_all(&mut file, contents.as_bytes()).map_err(io_error)?;
+    file.sync_all().map_err(io_error)
+}
+
+/// Returns options to open a file for writing, which is created accessible only to the current user
+/// on unix (mode `0600`).
+fn private_file() -> fs::OpenOptions {
+    let mut options = fs::OpenOptions::new();
+    options.write(true);
+
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+    options
+}
+
+/// Sets the unix permissions of a file or folder to `mode`. This is a no-op on other platforms,
+/// where permissions are inherited from the containing folder instead.
+///
+/// # Errors
+/// * [`Error::IO`] if the permissions could not be set.
+#[cfg(unix)]
+fn restrict_permissions(path: &Path, mode: u32) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    fs::set_permissions(path, fs::Permissions::from_mode(mode))
+        .map_err(|e| Error::IO(e, path.to_owned()))
+}
+
+#[cfg(not(unix))]
+fn restrict_permissions(_: &Path, _: u32) -> Result<()> {
+    Ok(())
+}
+
+/// Converts the one-based line and column of a [`serde_json::Error`] to a byte offset in the parsed
+/// string.
+fn byte_offset(string: &str, line: usize, column: usize) -> usize {
+    let line_start: usize = string
+        .split_inclusive('\n')
+        .take(line.saturating_sub(1))
+        .map(str::len)
+        .sum();
+    (line_start + column.saturating_sub(1)).min(string.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Creates a vault in memory containing the given seeds.
+    fn vault_with(seeds: Vec<Seed>) -> Vault {
+        Vault {
+            header: Header::current(),
+            path: PathBuf::new(),
+            identifier: "test".to_owned(),
+            pepper_source: PepperSource::Inline(Vec::new()),
+            pepper: Pepper::default(),
+            seeds,
+            auth_token: Vec::new(),
+            auth_params: Argon2Params::default(),
+            target_auth_params: Argon2Params::default(),
+            backup_count: DEFAULT_BACKUP_COUNT,
+            prefix: None,
+            normalization: Normalization::Nfc,
+            argon2_variant: Argon2Variant::Argon2d,
+            seed_defaults: SeedDefaults::default(),
+            observer: Observer::default(),
+            file_hash: FileHash::default(),
+            scratch: false,
+        }
+    }
+
+    #[test]
+    fn password_verified() {
+        let mut vault = vault_with(vec![Seed::basic("a".to_owned(), None)]);
+        vault.pepper = Pepper::from(generate::pepper());
+        vault.auth_token = generate::auth_token("key", &vault.pepper).unwrap();
+
+        let result = vault.password_verified(vault.get(0).unwrap(), "wrong");
+        assert!(matches!(result, Err(Error::KeyMismatch(_))));
+    }
+
+    #[test]
+    fn verify_key() {
+        let mut vault = vault_with(Vec::new());
+        vault.pepper = Pepper::from(generate::pepper());
+        vault.auth_token = generate::auth_token("key", &vault.pepper).unwrap();
+
+        assert!(vault.verify_key("key"));
+        assert!(!vault.verify_key("kez"));
+        assert!(!vault.verify_key(""));
+    }
+
+    #[test]
+    fn password_invalid_seed() {
+        let vault = vault_with(Vec::new());
+        let mut seed = Seed::basic("a".to_owned(), None);
+        seed.characters = seed::Characters::empty();
+        assert!(matches!(vault.password(&seed, "key"), Err(Error::InvalidSeed(..))));
+
+        seed = Seed::basic("a".to_owned(), None);
+        seed.min_len = 0;
+        assert!(matches!(vault.password(&seed, "key"), Err(Error::InvalidSeed(..))));
+    }
+
+    #[test]
+    fn seed_defaults() {
+        let mut vault = vault_with(Vec::new());
+        let defaults = SeedDefaults {
+            min_len: 12,
+            max_len: 16,
+            characters: seed::Characters::LOWER_CASE,
+            ..SeedDefaults::default()
+        };
+        vault.set_seed_defaults(defaults).unwrap();
+
+        let seed = vault.new_seed("new".to_owned(), None);
+        assert_eq!((seed.min_len, seed.max_len), (12, 16));
+        assert_eq!(seed.characters, seed::Characters::LOWER_CASE);
+
+        let invalid = SeedDefaults { min_len: 0, ..defaults };
+        assert!(matches!(vault.set_seed_defaults(invalid), Err(Error::InvalidSeed(..))));
+        assert_eq!(vault.seed_defaults(), &defaults);
+    }
+
+    #[test]
+    fn prefix() {
+        let mut vault = vault_with(vec![Seed::pin("pin".to_owned(), None, 6)]);
+        vault.pepper = Pepper::from(generate::pepper());
+        vault.auth_token = generate::auth_token("key", &vault.pepper).unwrap();
+        let seed = vault.get(0).unwrap().clone();
+        let unprefixed = vault.password_verified(&seed, "key").unwrap();
+
+        vault.set_prefix(Some("ORG-".to_owned())).unwrap();
+        let prefixed = vault.password_verified(&seed, "key").unwrap();
+        assert!(prefixed.starts_with("ORG-"));
+        assert_eq!(prefixed.len(), 6);
+
+        let result = vault.set_prefix(Some("TOO-LONG".to_owned()));
+        assert!(matches!(result, Err(Error::InvalidSeed(..))));
+        assert_eq!(vault.prefix(), Some("ORG-"));
+
+        vault.set_prefix(None).unwrap();
+        assert_eq!(vault.password_verified(&seed, "key").unwrap(), unprefixed);
+    }
+
+    #[test]
+    fn observer() {
+        use std::sync::{Arc, Mutex};
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let mut vault = vault_with(Vec::new());
+        let observed = events.clone();
+        vault.set_observer(move |event| observed.lock().unwrap().push(*event));
+
+        vault.push(Seed::basic("a".to_owned(), None));
+        vault.push(Seed::basic("b".to_owned(), None));
+        vault.swap(0, 1).unwrap();
+        vault.rename_seed(0, "c".to_owned()).unwrap();
+        assert!(vault.edit_seed(5, Seed::basic("d".to_owned(), None)).is_err());
+        vault.remove(1).unwrap();
+        assert!(vault.remove(1).is_err());
+
+        assert_eq!(
+            *events.lock().unwrap(),
+            [
+                VaultEvent::Inserted(0),
+                VaultEvent::Inserted(1),
+                VaultEvent::Swapped(0, 1),
+                VaultEvent::Edited(0),
+                VaultEvent::Removed(1),
+            ]
+        );
+        vault.clear_observer();
+        vault.push(Seed::basic("e".to_owned(), None));
+        assert_eq!(events.lock().unwrap().len(), 5);
+    }
+
+    #[test]
+    fn sort_seeds() {
+        let mut b = Seed::basic("b".to_owned(), Some("amy".to_owned()));
+        b.notes = Some("kept".to_owned());
+        let mut vault = vault_with(vec![
+            b,
+            Seed::basic("C".to_owned(), None),
+            Seed::basic("a".to_owned(), Some("Zed".to_owned())),
+        ]);
+        let identifiers = |vault: &Vault| -> Vec<String> {
+            vault.seeds().iter().map(|seed| seed.identifier.clone()).collect()
+        };
+
+        vault.sort_seeds(SortKey::Identifier);
+        assert_eq!(identifiers(&vault), ["a", "b", "C"]);
+        assert_eq!(vault.get(1).unwrap().notes.as_deref(), Some("kept"));
+
+        vault.sort_seeds(SortKey::Username);
+        assert_eq!(identifiers(&vault), ["b", "a", "C"]);
+
+        vault.sort_seeds(SortKey::Url);
+        assert_eq!(identifiers(&vault), ["a", "b", "C"]);
+    }
+
+    #[test]
+    fn change_key() {
+        let mut vault = vault_with(vec![Seed::basic("a".to_owned(), None)]);
+        vault.pepper = Pepper::from(generate::pepper());
+        vault.auth_token = generate::auth_token("old", &vault.pepper).unwrap();
+
+        let result = vault.change_key("wrong", "new");
+        assert!(matches!(result, Err(Error::KeyVerificationFailed(_))));
+        assert!(vault.verify_key("old"));
+
+        let change = vault.change_key("old", "new").unwrap();
+        assert_eq!(change.changed_passwords, ["a"]);
+        assert!(vault.verify_key("new"));
+        assert!(!vault.verify_key("old"));
+    }
+
+    #[test]
+    fn upgrade_auth_params() {
+        let folder = std::env::temp_dir().join("svalbard-test-auth-params");
+        let _ = fs::remove_dir_all(&folder);
+        let mut vault = Vault::new(&folder, "upgrade".to_owned(), "key", Argon2Variant::Argon2id).unwrap();
+        let old_token = vault.auth_token.clone();
+        let bumped = Argon2Params {
+            time_cost: Argon2Params::default().time_cost + 1,
+            ..Argon2Params::default()
+        };
+        vault.set_auth_params(bumped);
+
+        // the stored token is verified with the parameters it was generated with
+        assert!(vault.verify_key("key"));
+        assert!(!vault.verify_and_upgrade("wrong").unwrap());
+        assert_eq!(vault.auth_params(), Argon2Params::default());
+
+        // unsaved changes are not saved along with the upgraded token
+        vault.push(Seed::basic("unsaved".to_owned(), None));
+        assert!(vault.verify_and_upgrade("key").unwrap());
+        assert_eq!(vault.auth_params(), bumped);
+        assert_ne!(vault.auth_token, old_token);
+        assert!(!vault.changed_on_disk().unwrap());
+
+        let mut loaded = Vault::load(&folder, "upgrade".to_owned()).unwrap();
+        assert_eq!(loaded.auth_params(), bumped);
+        assert!(loaded.seeds().is_empty());
+        assert!(loaded.verify_key("key"));
+        assert!(!loaded.verify_key("wrong"));
+
+        // without configured parameters, loaded tokens keep theirs
+        assert!(loaded.verify_and_upgrade("key").unwrap());
+        loaded.change_key("key", "new").unwrap();
+        assert_eq!(loaded.auth_params(), bumped);
+
+        // weaker parameters never replace stronger ones
+        loaded.set_auth_params(Argon2Params::default());
+        assert!(loaded.verify_and_upgrade("new").unwrap());
+        loaded.change_key("new", "key").unwrap();
+        assert_eq!(loaded.auth_params(), bumped);
+    }
+
+    #[test]
+    fn duplicate() {
+        let mut vault = vault_with(vec![
+            Seed::basic("a".to_owned(), None),
+            Seed::basic("b".to_owned(), None),
+        ]);
+
+        assert_eq!(vault.duplicate(0).unwrap(), 1);
+        assert_eq!(vault.duplicate(0).unwrap(), 1);
+        assert!(vault.duplicate(4).is_err());
+
+        let identifiers: Vec<&str> = vault.seeds().iter().map(|s| s.identifier.as_str()).collect();
+        assert_eq!(identifiers, ["a", "a copy 2", "a copy", "b"]);
+        assert_eq!(vault.get(1).unwrap().gen_tag, "a copy 2");
+    }
+
+    #[test]
+    fn try_push() {
+        let mut vault = vault_with(vec![Seed::basic("GitHub".to_owned(), None)]);
+
+        let result = vault.try_push(Seed::basic("github".to_owned(), None));
+        assert!(matches!(result, Err(Error::DuplicateSeed(identifier)) if identifier == "github"));
+        assert!(matches!(vault.try_push(Seed::basic(" ".to_owned(), None)), Err(Error::EmptyIdentifier)));
+        assert_eq!(vault.seeds().len(), 1);
+
+        vault.try_push(Seed::basic("GitLab".to_owned(), None)).unwrap();
+        assert_eq!(vault.get(1).unwrap().identifier, "GitLab");
+    }
+
+    #[test]
+    fn insert() {
+        let mut vault = vault_with(vec![Seed::basic("a".to_owned(), None)]);
+
+        vault.insert(0, Seed::basic("b".to_owned(), None)).unwrap();
+        vault.insert(2, Seed::basic("c".to_owned(), None)).unwrap();
+        assert!(matches!(vault.insert(4, Seed::basic("d".to_owned(), None)), Err(Error::SeedIndex(4))));
+        assert!(matches!(vault.insert(0, Seed::basic("A".to_owned(), None)), Err(Error::DuplicateSeed(_))));
+
+        let identifiers: Vec<&str> = vault.seeds().iter().map(|s| s.identifier.as_str()).collect();
+        assert_eq!(identifiers, ["b", "a", "c"]);
+        assert_eq!(vault.get(0).unwrap().gen_tag, "b");
+    }
+
+    #[test]
+    fn rename_seed() {
+        let mut vault = vault_with(vec![
+            Seed::basic("a".to_owned(), None),
+            Seed::basic("b".to_owned(), Some("user".to_owned())),
+        ]);
+
+        assert!(matches!(vault.rename_seed(1, "a".to_owned()), Err(Error::DuplicateSeed(_))));
+        assert!(matches!(vault.rename_seed(1, "A".to_owned()), Err(Error::DuplicateSeed(_))));
+        assert!(matches!(vault.rename_seed(1, " ".to_owned()), Err(Error::EmptyIdentifier)));
+        assert!(matches!(vault.rename_seed(2, "c".to_owned()), Err(Error::SeedIndex(2))));
+
+        vault.rename_seed(1, "B".to_owned()).unwrap();
+        vault.rename_seed(1, "c".to_owned()).unwrap();
+        assert_eq!(vault.get(1).unwrap().identifier, "c");
+        assert_eq!(vault.get(1).unwrap().gen_tag, "b");
+        assert_eq!(vault.get(1).unwrap().username.as_deref(), Some("user"));
+
+        // a new seed taking the old identifier must not reproduce the password of the renamed one
+        vault.try_push(Seed::basic("b".to_owned(), None)).unwrap();
+        assert_eq!(vault.get(2).unwrap().gen_tag, "b 2");
+        assert!(vault.check_collisions().is_empty());
+    }
+
+    #[test]
+    fn edit_seed() {
+        let mut vault = vault_with(vec![
+            Seed::basic("a".to_owned(), None),
+            Seed::basic("b".to_owned(), None),
+        ]);
+        let mut no_sets = Seed::basic("b".to_owned(), None);
+        no_sets.characters = seed::Characters::empty();
+
+        let result = vault.edit_seed(1, Seed::basic("a".to_owned(), None));
+        assert!(matches!(result, Err(Error::DuplicateSeed(_))));
+        let result = vault.edit_seed(1, Seed::basic("".to_owned(), None));
+        assert!(matches!(result, Err(Error::EmptyIdentifier)));
+        assert!(matches!(vault.edit_seed(1, no_sets), Err(Error::InvalidSeed(..))));
+        assert!(vault.edit_seed(2, Seed::basic("c".to_owned(), None)).is_err());
+        assert_eq!(vault.get(1).unwrap().identifier, "b");
+
+        vault.edit_seed(1, Seed::pin("b".to_owned(), None, 4)).unwrap();
+        assert_eq!(vault.get(1).unwrap().max_len, 4);
+        assert!(Error::DuplicateSeed("a".to_owned()).is_invalid_input());
+        assert!(!Error::SeedIndex(2).is_invalid_input());
+    }
+
+    #[test]
+    fn merge() {
+        let seeds = || vec![
+            Seed::basic("a".to_owned(), None),
+            Seed::basic("b".to_owned(), None),
+        ];
+        // the seeds were renamed in the other vault, keeping the generation tags of this one
+        let mut other = vault_with(vec![
+            Seed {
+                gen_tag: "a".to_owned(),
+                ..Seed::basic("b".to_owned(), Some("other".to_owned()))
+            },
+            Seed { gen_tag: "b".to_owned(), ..Seed::basic("c".to_owned(), None) },
+        ]);
+        other.path = PathBuf::from("other");
+
+        let data = [
+            (ConflictPolicy::Skip,    1, vec!["a", "b", "c"],        None),
+            (ConflictPolicy::Rename,  2, vec!["a", "b", "b 2", "c"], None),
+            (ConflictPolicy::Replace, 2, vec!["a", "b", "c"],        Some("other")),
+        ];
+
+        for (policy, count, identifiers, username) in data {
+            let mut vault = vault_with(seeds());
+            assert_eq!(vault.merge(&other, policy).unwrap(), count);
+
+            let merged: Vec<&str> = vault.seeds().iter().map(|s| s.identifier.as_str()).collect();
+            assert_eq!(merged, identifiers);
+            assert_eq!(vault.get(1).unwrap().username.as_deref(), username);
+            assert!(vault.check_collisions().is_empty());
+        }
+
+        let mut vault = vault_with(seeds());
+        let result = vault.merge(&vault_with(seeds()), ConflictPolicy::Skip);
+        assert!(matches!(result, Err(Error::SelfMerge(_))));
+    }
+
+    #[test]
+    fn exists() {
+        let folder = std::env::temp_dir().join("svalbard-test-exists");
+        fs::create_dir_all(&folder).unwrap();
+        fs::write(folder.join("hello_world.vault"), "").unwrap();
+
+        assert!(Vault::exists(&folder, "Hello world"));
+        assert!(!Vault::exists(&folder, "Goodbye world"));
+        assert!(!Vault::exists(&folder.join("missing"), "Hello world"));
+
+        fs::remove_dir_all(&folder).unwrap();
+    }
+
+    #[test]
+    fn list() {
+        let folder = std::env::temp_dir().join("svalbard-test-list");
+        let _ = fs::remove_dir_all(&folder);
+        assert!(Vault::list(&folder).unwrap().is_empty());
+
+        fs::create_dir_all(folder.join("folder.vault")).unwrap();
+        for file in ["b.vault", "a.vault", "a.vault.bak.1", "notes.txt", ".vault"] {
+            fs::write(folder.join(file), "").unwrap();
+        }
+        assert_eq!(Vault::list(&folder).unwrap(), ["a", "b"]);
+
+        let file = folder.join("notes.txt");
+        assert!(matches!(Vault::list(&file), Err(Error::NotADirectory(_))));
+
+        fs::remove_dir_all(&folder).unwrap();
+    }
+
+    #[test]
+    fn external_pepper() {
+        let folder = std::env::temp_dir().join("svalbard-test-external-pepper");
+        let pepper_path = folder.join("pepper");
+        let _ = fs::remove_dir_all(&folder);
+
+        let mut vault = Vault::new(&folder, "test".to_owned(), "key", Argon2Variant::Argon2id).unwrap();
+        vault.externalize_pepper(&pepper_path).unwrap();
+        vault.save().unwrap();
+        assert!(vault.externalize_pepper(&pepper_path).is_err());
+
+        let loaded = Vault::load(&folder, "test".to_owned()).unwrap();
+        assert_eq!(loaded.pepper(), vault.pepper());
+        assert!(loaded.verify_key("key"));
+
+        fs::remove_file(&pepper_path).unwrap();
+        let result = Vault::load(&folder, "test".to_owned());
+        assert!(matches!(result, Err(Error::PepperUnavailable(..))));
+
+        fs::remove_dir_all(&folder).unwrap();
+    }
+
+    #[test]
+    fn load_migrates_gen_tag() {
+        let folder = std::env::temp_dir().join("svalbard-test-gen-tag");
+        let _ = fs::remove_dir_all(&folder);
+
+        let mut vault = Vault::new(&folder, "test".to_owned(), "key", Argon2Variant::Argon2id).unwrap();
+        vault.push(Seed::basic("a".to_owned(), None));
+        vault.save().unwrap();
+
+        // strip the tag, as stored before it existed
+        let mut json: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(vault.path()).unwrap()).unwrap();
+        json["seeds"][0].as_object_mut().unwrap().remove("gen_tag");
+        fs::write(vault.path(), json.to_string()).unwrap();
+
+        let loaded = Vault::load(&folder, "test".to_owned()).unwrap();
+        assert_eq!(loaded.get(0).unwrap().gen_tag, "a");
+
+        fs::remove_dir_all(&folder).unwrap();
+    }
+
+    #[test]
+    fn backups() {
+        let folder = std::env::temp_dir().join("svalbard-test-backups");
+        let _ = fs::remove_dir_all(&folder);
+
+        let mut vault = Vault::new(&folder, "test".to_owned(), "key", Argon2Variant::Argon2id).unwrap();
+        vault.set_backup_count(2);
+        assert!(!vault.backup_path(1).exists());
+
+        for identifier in ["a", "b", "c"] {
+            vault.push(Seed::basic(identifier.to_owned(), None));
+            vault.save().unwrap();
+        }
+        assert!(!vault.backup_path(3).exists());
+
+        vault.restore_backup(2).unwrap();
+        assert_eq!(vault.seeds().len(), 1);
+        assert_eq!(vault.path(), Vault::path_of(&folder, "test"));
+        assert!(vault.verify_key("key"));
+        assert!(matches!(vault.restore_backup(3), Err(Error::IO(..))));
+
+        fs::remove_dir_all(&folder).unwrap();
+    }
+
+    #[test]
+    fn scratch() {
+        let folder = std::env::temp_dir().join("svalbard-test-scratch");
+        let _ = fs::remove_dir_all(&folder);
+
+        let mut vault = Vault::scratch("scratch".to_owned(), "key", Argon2Variant::Argon2id).unwrap();
+        vault.push(Seed::basic("a".to_owned(), None));
+        vault.save().unwrap();
+        assert!(vault.is_scratch() && !vault.changed_on_disk().unwrap());
+        assert!(!folder.exists());
+
+        Vault::new(&folder, "taken".to_owned(), "key", Argon2Variant::Argon2id).unwrap();
+        assert!(matches!(vault.save_as(&folder, "taken".to_owned()), Err(Error::VaultNameConflict(_))));
+        assert!(vault.is_scratch());
+
+        vault.save_as(&folder, "kept".to_owned()).unwrap();
+        assert!(!vault.is_scratch());
+        let loaded = Vault::load(&folder, "kept".to_owned()).unwrap();
+        assert_eq!(loaded.identifier(), "kept");
+        assert_eq!(loaded.seeds().len(), 1);
+        assert!(loaded.verify_key("key"));
+
+        fs::remove_dir_all(&folder).unwrap();
+    }
+
+    #[test]
+    fn atomic_save() {
+        let folder = std::env::temp_dir().join("svalbard-test-atomic-save");
+        let _ = fs::remove_dir_all(&folder);
+
+        let mut vault = Vault::new(&folder, "test".to_owned(), "key", Argon2Variant::Argon2id).unwrap();
+        vault.set_backup_count(0);
+        assert!(!vault.temp_path().exists());
+
+        // the file cannot be replaced by the temporary file while it is a folder
+        fs::remove_file(vault.path()).unwrap();
+        fs::create_dir(vault.path()).unwrap();
+        assert!(matches!(vault.save(), Err(Error::IO(..))));
+        assert!(!vault.temp_path().exists());
+
+        fs::remove_dir_all(&folder).unwrap();
+    }
+
+    #[test]
+    fn rename() {
+        let folder = std::env::temp_dir().join("svalbard-test-rename");
+        let _ = fs::remove_dir_all(&folder);
+
+        let mut vault = Vault::new(&folder, "old".to_owned(), "key", Argon2Variant::Argon2id).unwrap();
+        vault.save().unwrap();
+        Vault::new(&folder, "taken".to_owned(), "key", Argon2Variant::Argon2id).unwrap();
+        assert!(matches!(vault.rename("Taken".to_owned()), Err(Error::VaultNameConflict(_))));
+
+        vault.rename("New".to_owned()).unwrap();
+        assert!(!Vault::exists(&folder, "old") && !vault.backup_path(3).exists());
+        assert!(vault.backup_path(2).is_file());
+        assert_eq!(Vault::load(&folder, "new".to_owned()).unwrap().identifier(), "New");
+
+        // only the identifier changes if the file name is the same
+        vault.rename("NEW".to_owned()).unwrap();
+        assert_eq!(vault.path(), Vault::path_of(&folder, "new"));
+        assert_eq!(Vault::load(&folder, "new".to_owned()).unwrap().identifier(), "NEW");
+
+        vault.rename("GitHub".to_owned()).unwrap();
+        vault.rename("github".to_owned()).unwrap();
+        assert_eq!(vault.path(), Vault::path_of(&folder, "github"));
+        assert!(vault.backup_path(2).is_file());
+        assert_eq!(Vault::load(&folder, "github".to_owned()).unwrap().identifier(), "github");
+
+        fs::remove_dir_all(&folder).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let folder = std::env::temp_dir().join("svalbard-test-permissions");
+        let _ = fs::remove_dir_all(&folder);
+        let mode = |path: &Path| fs::metadata(path).unwrap().permissions().mode() & 0o777;
+
+        let vault = Vault::new(&folder, "test".to_owned(), "key", Argon2Variant::Argon2id).unwrap();
+        assert_eq!(mode(&folder), 0o700);
+        assert_eq!(mode(vault.path()), 0o600);
+
+        fs::set_permissions(vault.path(), fs::Permissions::from_mode(0o644)).unwrap();
+        vault.save().unwrap();
+        assert_eq!(mode(vault.path()), 0o600);
+        assert_eq!(mode(&vault.backup_path(1)), 0o600);
+
+        fs::remove_dir_all(&folder).unwrap();
+    }
+
+    #[test]
+    fn json_error_offset() {
+        let string = "{\n  \"identifier\": \"test\",\n  oops\n}";
+        let e = serde_json::from_str::<serde_json::Value>(string).unwrap_err();
+        let offset = byte_offset(string, e.line(), e.column());
+
+        assert_eq!(&string[offset..offset + 1], "o");
+    }
 
     #[test]
     fn path_of() {
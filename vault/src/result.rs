@@ -0,0 +1,98 @@
+//! Contains the [Error] and [Result] types of the crate.
+//!
+//! Every operation which can fail returns a [Result], with errors falling into these categories:
+//!
+//! * **Invalid input**, after which the vault is left unchanged and the operation may be retried
+//!   with other input. See [`Error::is_invalid_input`].
+//!     * [`Error::VaultNameConflict`]: a vault with the identifier already exists.
+//!     * [`Error::SelfMerge`]: a vault was merged into itself.
+//!     * [`Error::KeyMismatch`]: the key is incorrect.
+//!     * [`Error::KeyVerificationFailed`]: the current key is incorrect when changing the key.
+//!     * [`Error::EmptyIdentifier`]: a seed identifier is empty.
+//!     * [`Error::DuplicateSeed`]: a seed identifier is already used by another seed.
+//!     * [`Error::InvalidSeed`]: a seed cannot produce a password, e.g. since it selects no
+//!       character set.
+//!     * [`Error::InvalidCharacters`]: character sets are specified by unknown letters.
+//!     * [`Error::Hashing`]: argon2 rejects its parameters, e.g. since a seed overrides them with
+//!       zero lanes.
+//! * **Environment**, concerning files and folders on disk.
+//!     * [`Error::IO`]: a file or folder could not be read or written.
+//!     * [`Error::NotADirectory`]: the vault folder is a file.
+//!     * [`Error::PepperUnavailable`]: an external pepper could not be read.
+//!     * [`Error::JSON`]: a vault file contains corrupted data.
+//!     * [`Error::UnsupportedFormat`]: a vault file was written in a format this build can't read.
+//! * **Bugs**, which should never occur and are to be reported.
+//!     * [`Error::SeedIndex`]: a seed index is out-of-bounds.
+//!     * [`Error::UnmetRequirements`]: a generated password violates the parameters of its seed.
+//!     * [`Error::SelfTest`]: this build generates different passwords than previous builds.
+
+use std::{io, path::PathBuf, result};
+
+use thiserror::Error;
+
+/// Contains all [Vault](crate::Vault) errors and their respective messages. See the [module](self) for how they
+/// are categorized.
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("Vault name '{0}' already exists. Try a different name")]
+    VaultNameConflict(String),
+    #[error("Seed index {0} out-of-bounds. This is a bug, please report to Mr. Simon.")]
+    SeedIndex(usize),
+    #[error("{1}: {0}")]
+    IO(io::Error, PathBuf),
+    /// Contains the parse error, the path of the file, and the byte offset of the error in it.
+    #[error("Could not parse JSON in {1} at byte {2}. Attempt to fix manually and retry: {0}")]
+    JSON(serde_json::Error, PathBuf, usize),
+    #[error("Vault '{0}' cannot be merged into itself.")]
+    SelfMerge(String),
+    #[error("Incorrect key for vault '{0}'.")]
+    KeyMismatch(String),
+    /// Returned by [`Vault::change_key`](crate::Vault::change_key) instead of
+    /// [`Error::KeyMismatch`], such that front-ends may tell which of the keys entered is wrong.
+    #[error("Current key for vault '{0}' is incorrect. The key was not changed.")]
+    KeyVerificationFailed(String),
+    #[error("Could not read the pepper from {1}. If it is stored on removable media, make sure it is connected: {0}")]
+    PepperUnavailable(io::Error, PathBuf),
+    #[error("Self-test failed on {0}. Passwords generated by this build differ from previous builds.")]
+    SelfTest(String),
+    #[error("Password of seed '{0}' does not meet its requirements: {1}. This is a bug, please report to Mr. Simon.")]
+    UnmetRequirements(String, String),
+    #[error("Seed '{0}' cannot produce a password: {1}.")]
+    InvalidSeed(String, String),
+    #[error("Seed identifier cannot be empty.")]
+    EmptyIdentifier,
+    #[error("A seed named '{0}' already exists in the vault.")]
+    DuplicateSeed(String),
+    #[error("Vault folder path exists but is not a directory: {0}")]
+    NotADirectory(PathBuf),
+    #[error("Invalid character sets '{0}'. Sets are picked by the letters ULNSR.")]
+    InvalidCharacters(String),
+    /// Contains the path of the file and the reason its format is not supported.
+    #[error("Vault file {0} is not supported by this version of Svalbard: {1}.")]
+    UnsupportedFormat(PathBuf, String),
+    #[error("Could not hash with argon2: {0}.")]
+    Hashing(argon2::Error),
+}
+
+impl Error {
+    /// Returns whether the error is caused by the input of an operation, in which case the vault is
+    /// left unchanged and the operation may be retried. Front-ends may then report the error and
+    /// carry on, whereas other errors may be fatal.
+    pub fn is_invalid_input(&self) -> bool {
+        matches!(
+            self,
+            Error::VaultNameConflict(_)
+                | Error::SelfMerge(_)
+                | Error::KeyMismatch(_)
+                | Error::KeyVerificationFailed(_)
+                | Error::EmptyIdentifier
+                | Error::DuplicateSeed(_)
+                | Error::InvalidSeed(..)
+                | Error::InvalidCharacters(_)
+                | Error::Hashing(_)
+        )
+    }
+}
+
+/// Result type using the Svalbard [Error](crate::Error) enum.
+pub type Result<T> = result::Result<T, Error>;
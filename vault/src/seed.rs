@@ -1,11 +1,20 @@
 //! Contains data used to seed the [generate::password](crate::generate::password) algorithm.
 
 use bitflags::bitflags;
-use serde::{Deserialize, Serialize};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::{str::FromStr, time::SystemTime};
+
+use crate::{
+    generate::{self, Argon2Params},
+    Error, Result,
+};
 
 bitflags! {
     /// Utility to specify what character sets should be used in a [Seed].
-    #[derive(Serialize, Deserialize)]
+    ///
+    /// Serialized as the mask produced by [`ToString`], e.g. `"UL-S-"`, such that vault files may
+    /// be edited by hand. The `{ "bits": 11 }` form stored by earlier versions, and plain bits, are
+    /// read too.
     pub struct Characters: u8 {
         const UPPER_CASE = 1 << 0;
         const LOWER_CASE = 1 << 1;
@@ -19,7 +28,8 @@ impl Characters {
     /// Defines the available character sets to be used when encoding a password.
     ///
     /// Note that the following characters have been filtered out as they may be confused for one
-    /// another: `I, O, l, 0`.
+    /// another: `I, O, l, 0`. Also note that the rare set includes a space, which many services
+    /// reject or trim; see [`Seed::exclude_space`].
     pub const SETS: [&'static [u8]; 5] = [
         b"ABCDEFGHJKLMNPQRSTUVWXYZ",
         b"abcdefghijkmnopqrstuvwxyz",
@@ -27,6 +37,21 @@ impl Characters {
         b"!#&()*+,-.<=>?@[]_",
         b"\"$%/:;\\^{|}~ ",
     ];
+
+    /// The rare set without space, used by seeds with [`Seed::exclude_space`] set.
+    pub const RARE_WITHOUT_SPACE: &'static [u8] = b"\"$%/:;\\^{|}~";
+
+    /// Gets the flag, human-readable label, and characters of each set, in the order of
+    /// [`Characters::SETS`]. Allows front-ends to list the sets without hard-coding them.
+    pub fn descriptors() -> &'static [(Characters, &'static str, &'static [u8])] {
+        &[
+            (Characters::UPPER_CASE, "Upper case", Characters::SETS[0]),
+            (Characters::LOWER_CASE, "Lower case", Characters::SETS[1]),
+            (Characters::NUMERICAL,  "Numerical",  Characters::SETS[2]),
+            (Characters::SPECIAL,    "Special",    Characters::SETS[3]),
+            (Characters::RARE,       "Rare",       Characters::SETS[4]),
+        ]
+    }
     
     /// Gets the [String] forms of all character sets held.
     pub fn get(&self) -> Vec<&'static [u8]> {
@@ -39,9 +64,12 @@ impl Characters {
     }
 }
 
+/// Contains the letter of each character set in masks of [Characters], in the order of
+/// [`Characters::SETS`]. Sets not held are masked by `-`.
+const FLAG_CHARS: &str = "ULNSR";
+
 impl ToString for Characters {
     fn to_string(&self) -> String {
-        const FLAG_CHARS: &str = "ULNSR";
         FLAG_CHARS
             .char_indices()
             .map(|(i, c)| if self.bits & (1 << i) != 0 { c } else { '-' })
@@ -49,40 +77,305 @@ impl ToString for Characters {
     }
 }
 
+impl FromStr for Characters {
+    type Err = Error;
+
+    /// Parses character sets from the letters of the sets held, in any order and case, e.g. `ULN`
+    /// or `ul`. Dashes are ignored, such that masks produced by [`ToString`] round-trip.
+    ///
+    /// # Errors
+    /// * [`Error::InvalidCharacters`] if any other character is included.
+    fn from_str(s: &str) -> Result<Self> {
+        s.chars()
+            .filter(|&c| c != '-')
+            .try_fold(Characters::empty(), |characters, c| {
+                let i = FLAG_CHARS
+                    .find(c.to_ascii_uppercase())
+                    .ok_or_else(|| Error::InvalidCharacters(s.to_owned()))?;
+                Ok(characters | Characters::from_bits_truncate(1 << i))
+            })
+    }
+}
+
+impl Serialize for Characters {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Characters {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Stored {
+            Mask(String),
+            Bits(u8),
+            Legacy { bits: u8 },
+        }
+
+        let bits = match Stored::deserialize(deserializer)? {
+            Stored::Mask(mask) => return mask.parse().map_err(de::Error::custom),
+            Stored::Bits(bits) | Stored::Legacy { bits } => bits,
+        };
+        Characters::from_bits(bits)
+            .ok_or_else(|| de::Error::custom(format!("invalid character set bits {bits}")))
+    }
+}
+
+/// Specifies how the output of a [Seed] is encoded.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Encoding {
+    /// Picks characters from the [character sets](Characters) of the seed, guaranteeing that each
+    /// set is represented.
+    #[default]
+    CharsetTable,
+    /// Lower case hexadecimal, e.g. for API tokens.
+    Hex,
+    /// Upper case base32 without padding, as specified by RFC 4648.
+    Base32,
+    /// Base58 using the Bitcoin alphabet, which excludes the easily confused `0, O, I, l`.
+    Base58,
+}
+
+impl Encoding {
+    pub const ALL: [Encoding; 4] = [
+        Encoding::CharsetTable,
+        Encoding::Hex,
+        Encoding::Base32,
+        Encoding::Base58,
+    ];
+
+    /// Gets the short name of the encoding, e.g. for use in forms.
+    pub fn name(self) -> &'static str {
+        match self {
+            Encoding::CharsetTable => "charset",
+            Encoding::Hex          => "hex",
+            Encoding::Base32       => "base32",
+            Encoding::Base58       => "base58",
+        }
+    }
+
+    /// Gets the fixed alphabet of the encoding, or [None] if the character sets of the seed are
+    /// used.
+    pub fn alphabet(self) -> Option<&'static [u8]> {
+        match self {
+            Encoding::CharsetTable => None,
+            Encoding::Hex          => Some(b"0123456789abcdef"),
+            Encoding::Base32       => Some(b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567"),
+            Encoding::Base58       => Some(b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz"),
+        }
+    }
+}
+
+/// Specifies whether a [Seed] generates a password of characters or a passphrase of words.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub enum SeedKind {
+    /// Characters of the length range of the seed, encoded as per its [encoding](Encoding).
+    #[default]
+    Password,
+    /// Words picked from an embedded list of short, distinct English words, e.g.
+    /// `"crane-ivory-pluck-sauna"`. The length range and encoding of the seed are ignored, and its
+    /// character sets unless the passphrase is hybrid.
+    Passphrase {
+        /// Number of words. Takes the place of the length range, so the number of characters
+        /// varies with the words picked.
+        words: u8,
+        /// Text joining the words. Must not contain letters, such that the words can be told
+        /// apart.
+        separator: String,
+        /// Decorates each word according to the character sets of the seed, for services
+        /// requiring e.g. a number and a symbol: words are capitalized if upper case letters are
+        /// selected, and followed by a digit if numbers are selected and by a symbol if special
+        /// or rare characters are, e.g. `"Crane4#-Ivory7!"`. Excluded characters are never used.
+        /// The words picked differ from those of the same seed without decorations.
+        ///
+        /// Each word thus grows by up to two characters, while the length range still does not
+        /// apply.
+        #[serde(default)]
+        hybrid: bool,
+    },
+}
+
 /// Contains all parameters used to generate passwords.
 #[derive(Serialize, Deserialize, Debug, Clone, Hash)]
 pub struct Seed {
-    /// Unique seed identifier, e.g. "GitHub".
+    /// Unique seed identifier, e.g. "GitHub". Used for display only, and may be changed freely.
     pub identifier: String,
-    /// Specifies the minimum length.
+    /// Participates in output in place of the identifier, such that seeds may be renamed without
+    /// changing their passwords. Set to the identifier when the seed is created, and must not be
+    /// changed thereafter.
+    ///
+    /// Missing in seeds stored before it existed, whose identifier then participated in output.
+    /// Such seeds are migrated on load by [`Seed::init_gen_tag`].
+    #[serde(default)]
+    pub gen_tag: String,
+    /// Specifies the minimum length. Passwords are of a length within `min_len..=max_len`, picked
+    /// deterministically by the [password derivation](crate::generate::password_with), such that
+    /// services with rules like "8 to 16 characters" may be satisfied without picking a length.
+    ///
+    /// Stored as `length` by seeds of a fixed length before ranges existed.
+    #[serde(alias = "length")]
     pub min_len: u8,
-    /// Specifies the maximum length.
+    /// Specifies the maximum length. Equal to the minimum for fixed lengths.
+    ///
+    /// Missing in seeds stored before ranges existed, which are migrated on load by
+    /// [`Seed::init_max_len`].
+    #[serde(default)]
     pub max_len: u8,
     /// Facilitates modifying output without changing other parameters. Does not have to be
     /// cryptographically secure.
+    ///
+    /// Two seeds with the same generation tag and salt generate the same password if their other
+    /// output parameters are equal too, see [`Seed::generates_like`].
     pub salt: u64,
-    /// Specifies character sets to be used.
+    /// Specifies character sets to be used. Ignored unless [`Seed::encoding`] is
+    /// [`Encoding::CharsetTable`].
     pub characters: Characters,
+    /// Specifies an additional set of characters, e.g. `"-_."` for a service allowing only those
+    /// symbols. Participates alongside [`Seed::characters`] like the built-in sets, such that it is
+    /// represented in the password too. Ignored unless [`Seed::encoding`] is
+    /// [`Encoding::CharsetTable`]. Must be printable ASCII without duplicates, see
+    /// [`generate::validate`].
+    #[serde(default)]
+    pub custom_set: Option<String>,
     /// Contains username for service. Provided for convenience only; does not participate in
     /// output.
     pub username: Option<String>,
+    /// Contains URL to the login page of the service. Provided for convenience only; does not
+    /// participate in output.
+    #[serde(default)]
+    pub url: Option<String>,
+    /// Contains free-form notes about the service. Provided for convenience only; does not
+    /// participate in output.
+    #[serde(default)]
+    pub notes: Option<String>,
+    /// Links related seeds, such as the password and PIN of the same login, such that they may be
+    /// generated together. Provided for convenience only; does not participate in output.
+    #[serde(default)]
+    pub group: Option<String>,
+    /// Excludes space from the rare character set, since many services reject or trim it.
+    ///
+    /// Set for new seeds. Seeds stored before this option existed include space, such that their
+    /// passwords are unchanged. Setting it for such a seed changes its password, so the password
+    /// of the service must be updated too. Ignored unless [`Seed::encoding`] is
+    /// [`Encoding::CharsetTable`].
+    #[serde(default)]
+    pub exclude_space: bool,
+    /// Characters excluded from the character sets, e.g. symbols rejected by the service. Sets
+    /// emptied by the exclusions are skipped. Ignored unless [`Seed::encoding`] is
+    /// [`Encoding::CharsetTable`]. Missing in seeds stored before it existed, which exclude nothing.
+    #[serde(default)]
+    pub exclude: String,
+    /// Specifies how the output is encoded. Missing in seeds stored before it existed, which use
+    /// [`Encoding::CharsetTable`].
+    #[serde(default)]
+    pub encoding: Encoding,
+    /// Specifies whether a password or a passphrase is generated. Missing in seeds stored before
+    /// passphrases existed, which generate passwords.
+    #[serde(default)]
+    pub kind: SeedKind,
+    /// Selects among deterministic variants of the password, e.g. to pick a more memorable one.
+    /// Variant 0 is the default, and is the password generated before variants existed.
+    #[serde(default)]
+    pub variant: u32,
+    /// Overrides the cost of the argon2 hash generating the password, e.g. to lower it for a legacy
+    /// service. Any change alters the password. If [None], the [defaults](Argon2Params::default)
+    /// are used. The cost of the vault's key verification is unaffected.
+    #[serde(default)]
+    pub argon2: Option<Argon2Params>,
+    /// Notes the maximum password length accepted by the service, if known. Advisory only; does
+    /// not participate in output. See [`Seed::exceeds_site_length`].
+    #[serde(default)]
+    pub max_site_length: Option<u8>,
+    /// Time the password was last generated by a front-end, if ever, set by
+    /// [`Vault::mark_generated`](crate::Vault::mark_generated). Passwords not generated in a long
+    /// time are flagged by the audit as likely due for rotation. Does not participate in output.
+    #[serde(default)]
+    pub last_generated: Option<SystemTime>,
+}
+
+/// Specifies the parameters new seeds of a [Vault](crate::Vault) start out with, such that users
+/// with a house style don't have to enter it for each seed. See [`Seed::from_defaults`].
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SeedDefaults {
+    /// Specifies the minimum length.
+    pub min_len: u8,
+    /// Specifies the maximum length.
+    pub max_len: u8,
+    /// Specifies character sets to be used.
+    pub characters: Characters,
+    /// Specifies whether space is excluded from the rare set. See [`Seed::exclude_space`]. Missing
+    /// in defaults stored before it existed, which exclude it like new seeds did.
+    #[serde(default = "default_exclude_space")]
+    pub exclude_space: bool,
+    /// Specifies the encoding. See [`Seed::encoding`].
+    #[serde(default)]
+    pub encoding: Encoding,
+    /// Overrides the cost of the argon2 hash. See [`Seed::argon2`].
+    #[serde(default)]
+    pub argon2: Option<Argon2Params>,
+}
+
+impl Default for SeedDefaults {
+    /// Returns a strong baseline of length 20 using all sets but the rare one.
+    fn default() -> Self {
+        SeedDefaults {
+            min_len: 20,
+            max_len: 20,
+            characters: Characters::all() - Characters::RARE,
+            exclude_space: default_exclude_space(),
+            encoding: Encoding::default(),
+            argon2: None,
+        }
+    }
+}
+
+fn default_exclude_space() -> bool {
+    true
 }
 
 impl Seed {
+    /// Creates a seed with the parameters of `defaults`, and otherwise those of [`Seed::medium`].
+    pub fn from_defaults(identifier: String, username: Option<String>, defaults: &SeedDefaults) -> Seed {
+        Seed {
+            min_len: defaults.min_len,
+            max_len: defaults.max_len,
+            characters: defaults.characters,
+            exclude_space: defaults.exclude_space,
+            encoding: defaults.encoding,
+            argon2: defaults.argon2,
+            ..Seed::medium(identifier, username)
+        }
+    }
+
     pub fn basic(identifier: String, username: Option<String>) -> Seed {
         Seed {
+            gen_tag: identifier.clone(),
             identifier,
             min_len: 12,
             max_len: 20,
             salt: 0,
             characters: Characters::LOWER_CASE |
                         Characters::NUMERICAL,
+            custom_set: None,
             username,
+            url: None,
+            notes: None,
+            group: None,
+            exclude_space: true,
+            exclude: String::new(),
+            encoding: Encoding::CharsetTable,
+            kind: SeedKind::Password,
+            variant: 0,
+            argon2: None,
+            max_site_length: None,
+            last_generated: None,
         }
     }
 
     pub fn medium(identifier: String, username: Option<String>) -> Seed {
         Seed {
+            gen_tag: identifier.clone(),
             identifier,
             min_len: 20,
             max_len: 40,
@@ -91,31 +384,329 @@ impl Seed {
                         Characters::LOWER_CASE |
                         Characters::NUMERICAL  |
                         Characters::SPECIAL,
+            custom_set: None,
             username,
+            url: None,
+            notes: None,
+            group: None,
+            exclude_space: true,
+            exclude: String::new(),
+            encoding: Encoding::CharsetTable,
+            kind: SeedKind::Password,
+            variant: 0,
+            argon2: None,
+            max_site_length: None,
+            last_generated: None,
         }
     }
 
     pub fn advanced(identifier: String, username: Option<String>) -> Seed {
         Seed {
+            gen_tag: identifier.clone(),
             identifier,
             min_len: 40,
             max_len: 64,
             salt: 0,
             characters: Characters::all(),
+            custom_set: None,
             username,
+            url: None,
+            notes: None,
+            group: None,
+            exclude_space: true,
+            exclude: String::new(),
+            encoding: Encoding::CharsetTable,
+            kind: SeedKind::Password,
+            variant: 0,
+            argon2: None,
+            max_site_length: None,
+            last_generated: None,
         }
     }
 
     pub fn pin(identifier: String, username: Option<String>, length: u8) -> Seed {
         Seed {
+            gen_tag: identifier.clone(),
             identifier,
             min_len: length,
             max_len: length,
             salt: 0,
             characters: Characters::NUMERICAL,
+            custom_set: None,
             username,
+            url: None,
+            notes: None,
+            group: None,
+            exclude_space: true,
+            exclude: String::new(),
+            encoding: Encoding::CharsetTable,
+            kind: SeedKind::Password,
+            variant: 0,
+            argon2: None,
+            max_site_length: None,
+            last_generated: None,
+        }
+    }
+
+    /// Sets the generation tag to the identifier if it is unset, i.e. if the seed is new or stored
+    /// before generation tags existed. Leaves the passwords of such seeds unchanged.
+    pub fn init_gen_tag(&mut self) {
+        if self.gen_tag.is_empty() {
+            self.gen_tag = self.identifier.clone();
         }
     }
+
+    /// Sets the maximum length to the minimum length if it is unset, i.e. if the seed was stored
+    /// with a fixed length before length ranges existed.
+    pub fn init_max_len(&mut self) {
+        if self.max_len == 0 {
+            self.max_len = self.min_len;
+        }
+    }
+
+    /// Gets the character sets used to generate passwords, applying [`Seed::exclude_space`] and
+    /// [`Seed::exclude`]. The [custom set](Seed::custom_set) follows the built-in sets. Sets
+    /// emptied by the exclusions are skipped. If an encoding with a fixed alphabet is used, the
+    /// alphabet is the only set.
+    pub fn sets(&self) -> Vec<Vec<u8>> {
+        if let Some(alphabet) = self.encoding.alphabet() {
+            return vec![alphabet.to_vec()];
+        }
+        self.characters
+            .get()
+            .into_iter()
+            .map(|set| {
+                if self.exclude_space && set == Characters::SETS[4] {
+                    Characters::RARE_WITHOUT_SPACE
+                } else {
+                    set
+                }
+            })
+            .chain(self.custom_set.as_deref().map(str::as_bytes))
+            .map(|set| {
+                set.iter()
+                    .copied()
+                    .filter(|c| !self.exclude.as_bytes().contains(c))
+                    .collect::<Vec<u8>>()
+            })
+            .filter(|set| !set.is_empty())
+            .collect()
+    }
+
+    /// Returns whether the length range, character sets, encoding, and salt of the seed are those of
+    /// one of the presets, e.g. [`Seed::basic`]. Such seeds are likely not tailored to their
+    /// service.
+    pub fn uses_default_parameters(&self) -> bool {
+        let presets: [fn(String, Option<String>) -> Seed; 3] =
+            [Seed::basic, Seed::medium, Seed::advanced];
+
+        presets.iter().any(|preset| {
+            let preset = preset(String::new(), None);
+            self.min_len == preset.min_len
+                && self.max_len == preset.max_len
+                && self.characters == preset.characters
+                && self.encoding == preset.encoding
+                && self.exclude == preset.exclude
+                && self.custom_set == preset.custom_set
+                && self.kind == preset.kind
+                && self.salt == preset.salt
+        })
+    }
+
+    /// Returns whether this seed generates the same passwords as another, i.e. whether all
+    /// parameters participating in output are equal. This is possible despite the
+    /// [generation tag](Seed::gen_tag) being set to the identifier on creation, e.g. if a seed is
+    /// copied between vaults or renamed and another created with its old identifier.
+    pub fn generates_like(&self, other: &Seed) -> bool {
+        // character sets are ignored by encodings with a fixed alphabet
+        let same_characters = self.encoding != Encoding::CharsetTable
+            || self.characters == other.characters
+                && self.exclude_space == other.exclude_space
+                && self.exclude == other.exclude
+                && self.custom_set == other.custom_set;
+
+        self.gen_tag == other.gen_tag
+            && self.min_len == other.min_len
+            && self.max_len == other.max_len
+            && self.salt == other.salt
+            && self.encoding == other.encoding
+            && self.kind == other.kind
+            && self.variant == other.variant
+            && self.argon2.unwrap_or_default() == other.argon2.unwrap_or_default()
+            && same_characters
+    }
+
+    /// Returns whether a password generated from this seed is longer than the
+    /// [maximum length](Seed::max_site_length) accepted by the service, such that it may be
+    /// rejected.
+    pub fn exceeds_site_length(&self, password: &str) -> bool {
+        self.max_site_length
+            .is_some_and(|max| password.chars().count() > max as usize)
+    }
+
+    /// Scores how well the seed matches a fuzzy search query, where higher scores are better
+    /// matches. Both the identifier and the username are matched, and the best score of the two is
+    /// returned. Returns [None] if neither matches.
+    pub fn match_score(&self, query: &str) -> Option<isize> {
+        let score = |target: &str| sublime_fuzzy::best_match(query, target).map(|m| m.score());
+        let username = self.username.as_deref().and_then(score);
+        score(&self.identifier).max(username)
+    }
+
+    /// Estimates the entropy in bits of the passwords generated from this seed. See
+    /// [`generate::entropy`].
+    pub fn entropy_bits(&self) -> f64 {
+        generate::entropy(self)
+    }
+}
+
+/// Presets of the length and character sets of new seeds, for common kinds of logins.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeedTemplate {
+    /// Four digits, e.g. for phone or card PINs.
+    Pin,
+    /// Lower case and numerical of length 15 to 20, for sites with restrictive password rules.
+    Basic,
+    /// Upper case, lower case, numerical, and special of length 20 to 35.
+    Medium,
+    /// No preset, for parameters entered manually. Applies the [defaults](SeedDefaults) of new
+    /// seeds as is.
+    Custom,
+}
+
+impl SeedTemplate {
+    pub const ALL: [SeedTemplate; 4] = [
+        SeedTemplate::Pin,
+        SeedTemplate::Basic,
+        SeedTemplate::Medium,
+        SeedTemplate::Custom,
+    ];
+
+    /// Gets the name of the template, e.g. for use in menus.
+    pub fn name(self) -> &'static str {
+        match self {
+            SeedTemplate::Pin    => "PIN",
+            SeedTemplate::Basic  => "BASIC",
+            SeedTemplate::Medium => "MEDIUM",
+            SeedTemplate::Custom => "CUSTOM",
+        }
+    }
+
+    /// Creates a seed with the [defaults](SeedDefaults) of new seeds, e.g. those of a
+    /// [Vault](crate::Vault), overlaid with the length range and character sets of the template.
+    /// The other defaults, such as the encoding, are kept; an encoding with a fixed alphabet
+    /// therefore ignores the sets of the template.
+    pub fn apply(&self, identifier: String, defaults: &SeedDefaults) -> Seed {
+        let (min_len, max_len, characters) = match self {
+            SeedTemplate::Pin => (4, 4, Characters::NUMERICAL),
+            SeedTemplate::Basic => (15, 20, Characters::LOWER_CASE | Characters::NUMERICAL),
+            SeedTemplate::Medium => (20, 35, Characters::all() - Characters::RARE),
+            SeedTemplate::Custom => (defaults.min_len, defaults.max_len, defaults.characters),
+        };
+        Seed {
+            min_len,
+            max_len,
+            characters,
+            ..Seed::from_defaults(identifier, None, defaults)
+        }
+    }
+}
+
+/// Builds a validated [Seed] from the parameters commonly entered by users, leaving the others at
+/// those of [`Seed::medium`]. Parameters not specified default to a length of 24, salt 0, and the
+/// upper case, lower case, and numerical sets. E.g.:
+///
+/// ```
+/// use vault::seed::{Characters, SeedBuilder};
+///
+/// let seed = SeedBuilder::new()
+///     .identifier("GitHub".to_owned())
+///     .length(16)
+///     .characters(Characters::LOWER_CASE | Characters::NUMERICAL)
+///     .build()
+///     .unwrap();
+/// assert_eq!((seed.min_len, seed.max_len), (16, 16));
+/// ```
+#[derive(Debug, Clone)]
+pub struct SeedBuilder {
+    identifier: String,
+    min_len: u8,
+    max_len: u8,
+    salt: u64,
+    characters: Characters,
+    username: Option<String>,
+}
+
+impl Default for SeedBuilder {
+    fn default() -> Self {
+        SeedBuilder {
+            identifier: String::new(),
+            min_len: 24,
+            max_len: 24,
+            salt: 0,
+            characters: Characters::UPPER_CASE | Characters::LOWER_CASE | Characters::NUMERICAL,
+            username: None,
+        }
+    }
+}
+
+impl SeedBuilder {
+    pub fn new() -> Self {
+        SeedBuilder::default()
+    }
+
+    /// Sets the identifier, which is also the [generation tag](Seed::gen_tag). Required.
+    pub fn identifier(mut self, identifier: String) -> Self {
+        self.identifier = identifier;
+        self
+    }
+
+    /// Sets a fixed length.
+    pub fn length(self, length: u8) -> Self {
+        self.length_range(length, length)
+    }
+
+    /// Sets the minimum and maximum length.
+    pub fn length_range(mut self, min_len: u8, max_len: u8) -> Self {
+        self.min_len = min_len;
+        self.max_len = max_len;
+        self
+    }
+
+    pub fn salt(mut self, salt: u64) -> Self {
+        self.salt = salt;
+        self
+    }
+
+    pub fn characters(mut self, characters: Characters) -> Self {
+        self.characters = characters;
+        self
+    }
+
+    pub fn username(mut self, username: String) -> Self {
+        self.username = Some(username);
+        self
+    }
+
+    /// Builds the seed.
+    ///
+    /// # Errors
+    /// * [`Error::EmptyIdentifier`] if no identifier was set, or it is blank.
+    /// * [`Error::InvalidSeed`] if the seed cannot produce a password. See [`generate::validate`].
+    pub fn build(self) -> Result<Seed> {
+        if self.identifier.trim().is_empty() {
+            return Err(Error::EmptyIdentifier);
+        }
+        let seed = Seed {
+            min_len: self.min_len,
+            max_len: self.max_len,
+            salt: self.salt,
+            characters: self.characters,
+            ..Seed::medium(self.identifier, self.username)
+        };
+        generate::validate(&seed)?;
+        Ok(seed)
+    }
 }
 
 #[cfg(test)]
@@ -128,6 +719,26 @@ mod tests {
     const S: &[u8] = Characters::SETS[3];
     const R: &[u8] = Characters::SETS[4];
 
+    #[test]
+    fn seed_exceeds_site_length() {
+        let mut seed = Seed::basic("site".to_owned(), None);
+        assert!(!seed.exceeds_site_length(&"a".repeat(64)));
+
+        seed.max_site_length = Some(16);
+        assert!(!seed.exceeds_site_length(&"a".repeat(16)));
+        assert!(seed.exceeds_site_length(&"a".repeat(17)));
+    }
+
+    #[test]
+    fn seed_match_score() {
+        let seed = Seed::basic("github".to_owned(), Some("octocat".to_owned()));
+
+        assert!(seed.match_score("gh").is_some());
+        assert!(seed.match_score("octo").is_some());
+        assert!(seed.match_score("xyz").is_none());
+        assert!(seed.match_score("github") > seed.match_score("gb"));
+    }
+
     #[test]
     fn character_get() {
         assert_eq!(Characters::UPPER_CASE.get(), [U]);
@@ -164,4 +775,190 @@ mod tests {
             assert_eq!(set.to_string(), str);
         }
     }
+
+    #[test]
+    fn characters_from_str() {
+        let data = [
+            ("ULNSR", Characters::all()),
+            ("ul", Characters::UPPER_CASE | Characters::LOWER_CASE),
+            ("-L-S-", Characters::LOWER_CASE | Characters::SPECIAL),
+            ("", Characters::empty()),
+        ];
+        for (str, characters) in data {
+            assert_eq!(str.parse::<Characters>().unwrap(), characters);
+            assert_eq!(characters.to_string().parse::<Characters>().unwrap(), characters);
+        }
+        assert!(matches!("ULX".parse::<Characters>(), Err(Error::InvalidCharacters(_))));
+    }
+
+    #[test]
+    fn characters_serde() {
+        let json = serde_json::to_string(&(Characters::UPPER_CASE | Characters::SPECIAL)).unwrap();
+        assert_eq!(json, r#""U--S-""#);
+
+        let data = [
+            (r#""ULNSR""#, Characters::all()),
+            (r#""-L-S-""#, Characters::LOWER_CASE | Characters::SPECIAL),
+            (r#""-----""#, Characters::empty()),
+            (r#"{ "bits": 6 }"#, Characters::LOWER_CASE | Characters::NUMERICAL),
+            ("17", Characters::UPPER_CASE | Characters::RARE),
+        ];
+        for (json, characters) in data {
+            assert_eq!(serde_json::from_str::<Characters>(json).unwrap(), characters);
+        }
+        for json in [r#""ULX""#, "32", r#"{ "bits": 255 }"#] {
+            assert!(serde_json::from_str::<Characters>(json).is_err(), "{json}");
+        }
+    }
+
+    #[test]
+    fn seed_optional_defaults() {
+        let json = r#"{
+            "identifier": "GitHub",
+            "min_len": 20,
+            "max_len": 20,
+            "salt": 0,
+            "characters": { "bits": 31 },
+            "username": null
+        }"#;
+        let seed: Seed = serde_json::from_str(json).unwrap();
+        assert_eq!(seed.url, None);
+        assert_eq!(seed.notes, None);
+        assert!(!seed.exclude_space);
+        assert_eq!(seed.gen_tag, "");
+        assert_eq!(seed.encoding, Encoding::CharsetTable);
+    }
+
+    #[test]
+    fn seed_template() {
+        let defaults = SeedDefaults::default();
+        let pin = SeedTemplate::Pin.apply("Bank".to_owned(), &defaults);
+        assert_eq!((pin.min_len, pin.max_len), (4, 4));
+        assert_eq!(pin.characters, Characters::NUMERICAL);
+        assert_eq!(pin.gen_tag, "Bank");
+
+        let basic = SeedTemplate::Basic.apply("Forum".to_owned(), &defaults);
+        assert_eq!((basic.min_len, basic.max_len), (15, 20));
+        assert_eq!(basic.characters, Characters::LOWER_CASE | Characters::NUMERICAL);
+
+        for template in SeedTemplate::ALL {
+            assert!(generate::validate(&template.apply("site".to_owned(), &defaults)).is_ok());
+        }
+
+        // the other parameters are taken from the defaults
+        let argon2 = Argon2Params { mem_cost: 1024, time_cost: 1, lanes: 1 };
+        let defaults = SeedDefaults { exclude_space: false, argon2: Some(argon2), ..defaults };
+        let medium = SeedTemplate::Medium.apply("Mail".to_owned(), &defaults);
+        assert_eq!((medium.min_len, medium.max_len), (20, 35));
+        assert!(!medium.exclude_space);
+        assert_eq!(medium.argon2, Some(argon2));
+        let custom = SeedTemplate::Custom.apply("Mail".to_owned(), &defaults);
+        assert_eq!((custom.min_len, custom.characters), (defaults.min_len, defaults.characters));
+    }
+
+    #[test]
+    fn seed_fixed_length() {
+        let json = r#"{
+            "identifier": "GitHub",
+            "length": 16,
+            "salt": 0,
+            "characters": "ULN--",
+            "username": null
+        }"#;
+        let mut seed: Seed = serde_json::from_str(json).unwrap();
+        seed.init_max_len();
+        assert_eq!((seed.min_len, seed.max_len), (16, 16));
+
+        seed.min_len = 8;
+        seed.init_max_len();
+        assert_eq!((seed.min_len, seed.max_len), (8, 16));
+    }
+
+    #[test]
+    fn seed_init_gen_tag() {
+        let mut seed = Seed::basic(String::new(), None);
+        seed.identifier = "GitHub".to_owned();
+        seed.init_gen_tag();
+        assert_eq!(seed.gen_tag, "GitHub");
+
+        seed.identifier = "GitHub (work)".to_owned();
+        seed.init_gen_tag();
+        assert_eq!(seed.gen_tag, "GitHub");
+    }
+
+    #[test]
+    fn character_descriptors() {
+        let descriptors = Characters::descriptors();
+        let all = descriptors.iter().fold(Characters::empty(), |all, &(flag, ..)| all | flag);
+
+        assert_eq!(all, Characters::all());
+        for &(flag, _, set) in descriptors {
+            assert_eq!(flag.get(), [set]);
+        }
+    }
+
+    #[test]
+    fn seed_sets() {
+        let mut seed = Seed::advanced("".to_owned(), None);
+        assert_eq!(seed.sets(), [U, L, N, S, Characters::RARE_WITHOUT_SPACE]);
+
+        seed.exclude_space = false;
+        assert_eq!(seed.sets(), [U, L, N, S, R]);
+
+        seed.characters = Characters::LOWER_CASE;
+        assert_eq!(seed.sets(), [L]);
+
+        seed.encoding = Encoding::Hex;
+        assert_eq!(seed.sets(), [b"0123456789abcdef"]);
+    }
+
+    #[test]
+    fn seed_sets_exclude() {
+        let mut seed = Seed::advanced("".to_owned(), None);
+        seed.characters = Characters::NUMERICAL | Characters::SPECIAL;
+        seed.exclude = "@#123456789".to_owned();
+        assert_eq!(seed.sets(), [b"!&()*+,-.<=>?[]_"]);
+
+        // fixed alphabets ignore the exclusions
+        seed.encoding = Encoding::Hex;
+        assert_eq!(seed.sets(), [b"0123456789abcdef"]);
+
+        let json = r#"{ "identifier": "old", "min_len": 8, "max_len": 8, "salt": 0,
+            "characters": "-L---", "username": null }"#;
+        assert_eq!(serde_json::from_str::<Seed>(json).unwrap().exclude, "");
+    }
+
+    #[test]
+    fn seed_entropy_bits() {
+        let pin = Seed::pin("".to_owned(), None, 4);
+        assert!((pin.entropy_bits() - 4.0 * 9_f64.log2()).abs() < 1e-9);
+
+        let mut empty = Seed::basic("".to_owned(), None);
+        empty.characters = Characters::empty();
+        assert_eq!(empty.entropy_bits(), 0.0);
+    }
+
+    #[test]
+    fn seed_builder() {
+        let seed = SeedBuilder::new().identifier("a".to_owned()).build().unwrap();
+        assert_eq!((seed.min_len, seed.max_len, seed.salt), (24, 24, 0));
+        assert_eq!(seed.characters, Characters::UPPER_CASE | Characters::LOWER_CASE | Characters::NUMERICAL);
+        assert_eq!((seed.gen_tag.as_str(), seed.username), ("a", None));
+
+        let seed = SeedBuilder::new()
+            .identifier("b".to_owned())
+            .length_range(8, 12)
+            .salt(3)
+            .username("user".to_owned())
+            .build()
+            .unwrap();
+        assert_eq!((seed.min_len, seed.max_len, seed.salt), (8, 12, 3));
+        assert_eq!(seed.username.as_deref(), Some("user"));
+
+        assert!(matches!(SeedBuilder::new().build(), Err(Error::EmptyIdentifier)));
+        let builder = SeedBuilder::new().identifier("c".to_owned());
+        assert!(matches!(builder.clone().length(0).build(), Err(Error::InvalidSeed(..))));
+        assert!(matches!(builder.clone().length_range(12, 8).build(), Err(Error::InvalidSeed(..))));
+        assert!(matches!(builder.characters(Characters::empty()).build(), Err(Error::InvalidSeed(..))));
+    }
 }
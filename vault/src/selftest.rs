@@ -0,0 +1,284 @@
+//! Contains frozen input vectors of the [generative algorithms](crate::generate) and their expected
+//! outputs.
+//!
+//! Since generated passwords are used as real credentials, any change to their outputs would lock
+//! users out of their services. The vectors guard against such changes, both in the test suite and
+//! through [`run`], which allows users to confirm that a new build produces the same outputs as the
+//! previous one. Changes to the algorithms must therefore update these vectors deliberately.
+
+use data_encoding::HEXLOWER;
+
+use crate::{
+    generate::{self, Argon2Params, Argon2Variant, Normalization},
+    seed::{Characters, Encoding, Seed, SeedKind},
+    Error, Result,
+};
+
+/// Contains vectors of [`generate::auth_token`] as `(key, pepper, hex encoded token)`.
+const AUTH_TOKEN_VECTORS: &[(&str, &[u8], &str)] = &[
+    (
+        "correct horse battery staple",
+        b"svalbard pepper 0",
+        "be57415f4fbac9e05987e16c7808036cc275cfd1855c0f140224526adb59c5cc",
+    ),
+    (
+        "",
+        &[0; 16],
+        "47afcb3a3685481d676ceead09bd2ef4c7cccc5b700a60f16c7df9c2844725d2",
+    ),
+    (
+        "ünïcödé 🔑",
+        b"0123456789abcdef0123",
+        "5701ffe80f4210755fc6354354f9c7e56aeb834956828614e1be2f3fbd2eeff3",
+    ),
+];
+
+/// Contains the output parameters of a [`generate::password_with`] vector. Vectors list those
+/// differing from [`BASE`].
+struct PasswordVector {
+    key: &'static str,
+    pepper: &'static [u8],
+    identifier: &'static str,
+    min_len: u8,
+    max_len: u8,
+    salt: u64,
+    characters: Characters,
+    custom_set: Option<&'static str>,
+    exclude_space: bool,
+    exclude: &'static str,
+    encoding: Encoding,
+    /// Number of words, separator, and whether the passphrase is hybrid, or [None] for passwords.
+    passphrase: Option<(u8, &'static str, bool)>,
+    variant: u32,
+    argon2: Option<Argon2Params>,
+    normalization: Normalization,
+    argon2_variant: Argon2Variant,
+    expected: &'static str,
+}
+
+/// Contains the parameters shared by the [password vectors](PASSWORD_VECTORS), which are those of
+/// passwords generated by [`generate::password`].
+const BASE: PasswordVector = PasswordVector {
+    key: "correct horse battery staple",
+    pepper: b"svalbard pepper 0",
+    identifier: "example.com",
+    min_len: 16,
+    max_len: 16,
+    salt: 0,
+    characters: Characters::UPPER_CASE.union(Characters::LOWER_CASE).union(Characters::NUMERICAL),
+    custom_set: None,
+    exclude_space: true,
+    exclude: "",
+    encoding: Encoding::CharsetTable,
+    passphrase: None,
+    variant: 0,
+    argon2: None,
+    normalization: Normalization::None,
+    argon2_variant: Argon2Variant::Argon2d,
+    expected: "",
+};
+
+/// Contains vectors of [`generate::password_with`], covering each parameter of seeds which
+/// participates in output.
+const PASSWORD_VECTORS: &[PasswordVector] = &[
+    // fixed length
+    PasswordVector {
+        expected: "UYCFaLD9MGnkEbu6",
+        ..BASE
+    },
+    // length range
+    PasswordVector {
+        min_len: 8,
+        max_len: 24,
+        expected: "gJxzCJVK94xbug",
+        ..BASE
+    },
+    // upper case only
+    PasswordVector {
+        characters: Characters::UPPER_CASE,
+        expected: "UYCFLLDQMGVNEKCZ",
+        ..BASE
+    },
+    // lower case only
+    PasswordVector {
+        characters: Characters::LOWER_CASE,
+        expected: "tsvxakwgcfnkzbuy",
+        ..BASE
+    },
+    // numerical only
+    PasswordVector {
+        characters: Characters::NUMERICAL,
+        expected: "1899821934878466",
+        ..BASE
+    },
+    // special only
+    PasswordVector {
+        characters: Characters::SPECIAL,
+        expected: "!]-_]<.-=>,+](@*",
+        ..BASE
+    },
+    // rare only, including space
+    PasswordVector {
+        characters: Characters::RARE,
+        exclude_space: false,
+        expected: "; $%/}%~\\:;{^\\ }",
+        ..BASE
+    },
+    // rare only, excluding space
+    PasswordVector {
+        characters: Characters::RARE,
+        expected: "\\}%;}}/%~\\^\":|%~",
+        ..BASE
+    },
+    // all sets with exclusions
+    PasswordVector {
+        characters: Characters::all(),
+        exclude: "aeiou!#",
+        expected: ")YCFp>DjM\\k\"g46.",
+        ..BASE
+    },
+    // custom set only
+    PasswordVector {
+        characters: Characters::empty(),
+        custom_set: Some("-_."),
+        expected: "-_..__-..-_-_-..",
+        ..BASE
+    },
+    // custom set alongside built-in sets
+    PasswordVector {
+        custom_set: Some("-_."),
+        expected: "UY9FaLDgM4nk_b.6",
+        ..BASE
+    },
+    // hex encoding
+    PasswordVector {
+        encoding: Encoding::Hex,
+        expected: "8a12f98eb1aaf6c5",
+        ..BASE
+    },
+    // base32 encoding
+    PasswordVector {
+        encoding: Encoding::Base32,
+        expected: "RIJPTDVRVL3MKGX2",
+        ..BASE
+    },
+    // base58 encoding
+    PasswordVector {
+        encoding: Encoding::Base58,
+        expected: "AHz3Y7G975iESwWS",
+        ..BASE
+    },
+    // passphrase
+    PasswordVector {
+        passphrase: Some((5, "-", false)),
+        expected: "insignia-guild-ensure-jury-lovable",
+        ..BASE
+    },
+    // hybrid passphrase
+    PasswordVector {
+        characters: Characters::all(),
+        passphrase: Some((4, ".", true)),
+        expected: "Move4).Swerve6/.Travel4@.Crazily1;",
+        ..BASE
+    },
+    // argon2id
+    PasswordVector {
+        argon2_variant: Argon2Variant::Argon2id,
+        expected: "P1tobb9Xum83y5xW",
+        ..BASE
+    },
+    // argon2 cost override
+    PasswordVector {
+        argon2: Some(Argon2Params { mem_cost: 8192, time_cost: 2, lanes: 2 }),
+        expected: "WbvXrfeMn7pDQ2xR",
+        ..BASE
+    },
+    // unnormalized non-ASCII key
+    PasswordVector {
+        key: "Svalbard A\u{30a}",
+        expected: "r68ZdUxhanunCGf2",
+        ..BASE
+    },
+    // NFC normalized non-ASCII key
+    PasswordVector {
+        key: "Svalbard A\u{30a}",
+        normalization: Normalization::Nfc,
+        expected: "FzuS8TGT7GqxJfxR",
+        ..BASE
+    },
+    // non-zero variant
+    PasswordVector {
+        variant: 3,
+        expected: "kFdNM3JXshS5RdD6",
+        ..BASE
+    },
+];
+
+impl PasswordVector {
+    /// Creates the seed of the vector.
+    fn seed(&self) -> Seed {
+        let mut seed = Seed::basic(self.identifier.to_owned(), None);
+        seed.min_len = self.min_len;
+        seed.max_len = self.max_len;
+        seed.salt = self.salt;
+        seed.characters = self.characters;
+        seed.custom_set = self.custom_set.map(str::to_owned);
+        seed.exclude_space = self.exclude_space;
+        seed.exclude = self.exclude.to_owned();
+        seed.encoding = self.encoding;
+        seed.variant = self.variant;
+        seed.argon2 = self.argon2;
+
+        if let Some((words, separator, hybrid)) = self.passphrase {
+            seed.kind = SeedKind::Passphrase { words, separator: separator.to_owned(), hybrid };
+        }
+        seed
+    }
+}
+
+/// Reruns all vectors and returns the number checked.
+///
+/// # Errors
+/// * [`Error::SelfTest`] describing the first vector whose output differs from the expected one.
+pub fn run() -> Result<usize> {
+    for (i, &(key, pepper, expected)) in AUTH_TOKEN_VECTORS.iter().enumerate() {
+        let token = HEXLOWER.encode(&generate::auth_token(key, pepper)?);
+
+        if token != expected {
+            return Err(Error::SelfTest(format!("authentication token vector {i}")));
+        }
+    }
+
+    for (i, vector) in PASSWORD_VECTORS.iter().enumerate() {
+        let password = generate::password_with(
+            vector.key,
+            vector.pepper,
+            &vector.seed(),
+            vector.normalization,
+            vector.argon2_variant,
+        )?;
+
+        if *password != vector.expected {
+            return Err(Error::SelfTest(format!("password vector {i}")));
+        }
+    }
+    Ok(AUTH_TOKEN_VECTORS.len() + PASSWORD_VECTORS.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vectors() {
+        assert_eq!(run().unwrap(), AUTH_TOKEN_VECTORS.len() + PASSWORD_VECTORS.len());
+    }
+
+    #[test]
+    fn base_vector_is_legacy() {
+        // the parameters of the first vector are those of the plain entry point
+        let vector = &PASSWORD_VECTORS[0];
+        let password = generate::password(vector.key, vector.pepper, &vector.seed()).unwrap();
+        assert_eq!(*password, vector.expected);
+    }
+}
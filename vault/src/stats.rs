@@ -0,0 +1,94 @@
+//! Contains summaries of the [Seeds](Seed) in a [Vault](crate::Vault).
+
+use std::{collections::BTreeMap, fmt};
+
+use crate::seed::{Characters, Seed};
+
+/// Summarizes the seed metadata of a [Vault](crate::Vault). Since no passwords are involved, no
+/// key is needed to compute it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VaultStats {
+    /// Number of seeds.
+    pub seed_count: usize,
+    /// Mean of the midpoints of each seed's length range, or 0 if there are no seeds.
+    pub average_length: f64,
+    /// Number of seeds using each combination of character sets.
+    pub characters: BTreeMap<Characters, usize>,
+    /// Number of seeds without a username.
+    pub missing_username: usize,
+}
+
+impl VaultStats {
+    pub fn new(seeds: &[Seed]) -> Self {
+        let mut characters = BTreeMap::new();
+
+        for seed in seeds {
+            *characters.entry(seed.characters).or_insert(0) += 1;
+        }
+        let average_length = if seeds.is_empty() {
+            0.0
+        } else {
+            let total: f64 = seeds
+                .iter()
+                .map(|seed| (seed.min_len as f64 + seed.max_len as f64) / 2.0)
+                .sum();
+            total / seeds.len() as f64
+        };
+
+        VaultStats {
+            seed_count: seeds.len(),
+            average_length,
+            characters,
+            missing_username: seeds.iter().filter(|seed| seed.username.is_none()).count(),
+        }
+    }
+}
+
+impl fmt::Display for VaultStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Seeds:            {}", self.seed_count)?;
+        writeln!(f, "Average length:   {:.1}", self.average_length)?;
+        writeln!(f, "Missing username: {}", self.missing_username)?;
+        write!(f, "Character sets:")?;
+
+        for (characters, count) in &self.characters {
+            write!(f, "\n    {}  {count}", characters.to_string())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stats() {
+        let seeds = [
+            Seed::basic("a".to_owned(), None),
+            Seed::basic("b".to_owned(), Some("user".to_owned())),
+            Seed::pin("c".to_owned(), None, 4),
+        ];
+        let stats = VaultStats::new(&seeds);
+
+        assert_eq!(stats.seed_count, 3);
+        assert_eq!(stats.average_length, (16.0 + 16.0 + 4.0) / 3.0);
+        assert_eq!(stats.missing_username, 2);
+        assert_eq!(
+            stats.characters.into_iter().collect::<Vec<_>>(),
+            [
+                (Characters::NUMERICAL, 1),
+                (Characters::LOWER_CASE | Characters::NUMERICAL, 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn stats_empty() {
+        let stats = VaultStats::new(&[]);
+
+        assert_eq!(stats.seed_count, 0);
+        assert_eq!(stats.average_length, 0.0);
+        assert!(stats.characters.is_empty());
+    }
+}
@@ -0,0 +1,146 @@
+//! Exercises the full life cycle of a vault on disk: creating it, adding a seed, saving, reloading,
+//! and generating passwords.
+
+use rand::{rngs::StdRng, SeedableRng};
+use std::{env, fs, path::PathBuf};
+use vault::{
+    generate::Argon2Variant,
+    header::{self, Header},
+    seed::Seed,
+    Error, Vault,
+};
+
+const KEY: &str = "correct horse battery staple";
+
+/// Returns an empty folder specific to a test.
+fn test_folder(name: &str) -> PathBuf {
+    let folder = env::temp_dir().join(format!("svalbard-test-{name}"));
+    let _ = fs::remove_dir_all(&folder);
+    folder
+}
+
+/// Creates a seed of fixed length whose password is derived through the default charset table.
+fn basic_seed() -> Seed {
+    let mut seed = Seed::basic("example.com".to_owned(), Some("user".to_owned()));
+    seed.min_len = 16;
+    seed.max_len = 16;
+    seed
+}
+
+#[test]
+fn new_push_save_load_password() {
+    let folder = test_folder("lifecycle");
+    let mut rng = StdRng::seed_from_u64(0);
+
+    let mut vault =
+        Vault::new_with_rng(&folder, "lifecycle".to_owned(), KEY, Argon2Variant::Argon2d, &mut rng).unwrap();
+    vault.push(basic_seed());
+    vault.save().unwrap();
+    let created = vault.password_verified(vault.get(0).unwrap(), KEY).unwrap();
+
+    let loaded = Vault::load(&folder, "lifecycle".to_owned()).unwrap();
+    assert!(loaded.verify_key(KEY));
+    assert_eq!(loaded.seeds().len(), 1);
+    assert_eq!(loaded.seeds()[0].username.as_deref(), Some("user"));
+    let reloaded = loaded.password_verified(loaded.get(0).unwrap(), KEY).unwrap();
+
+    assert_eq!(created, reloaded);
+    assert_eq!(created.as_str(), "js55m2cbtbjgjssm");
+}
+
+#[test]
+fn seeded_rng_is_deterministic() {
+    let folder = test_folder("deterministic");
+    let new = |identifier: &str, rng_seed| {
+        let mut rng = StdRng::seed_from_u64(rng_seed);
+        Vault::new_with_rng(&folder, identifier.to_owned(), KEY, Argon2Variant::Argon2d, &mut rng)
+            .unwrap()
+    };
+    let (a, b, c) = (new("a", 1), new("b", 1), new("c", 2));
+
+    assert_eq!(a.pepper(), b.pepper());
+    assert_ne!(a.pepper(), c.pepper());
+}
+
+#[test]
+fn folder_is_a_file() {
+    let folder = test_folder("file");
+    fs::write(&folder, "").unwrap();
+
+    let created = Vault::new(&folder, "vault".to_owned(), KEY, Argon2Variant::Argon2id);
+    assert!(matches!(created, Err(Error::NotADirectory(path)) if path == folder));
+
+    let loaded = Vault::load(&folder, "vault".to_owned());
+    assert!(matches!(loaded, Err(Error::NotADirectory(_))));
+    fs::remove_file(&folder).unwrap();
+}
+
+#[test]
+fn header() {
+    let folder = test_folder("header");
+    let vault = Vault::new(&folder, "header".to_owned(), KEY, Argon2Variant::Argon2d).unwrap();
+    let contents = fs::read_to_string(vault.path()).unwrap();
+    assert_eq!(Header::parse(&contents).unwrap(), Header::current());
+
+    // files without a header are version 1, and are upgraded once saved
+    let mut json: serde_json::Value = serde_json::from_str(&contents).unwrap();
+    json.as_object_mut().unwrap().remove("header");
+    fs::write(vault.path(), json.to_string()).unwrap();
+    Vault::load(&folder, "header".to_owned()).unwrap().save().unwrap();
+    let contents = fs::read_to_string(vault.path()).unwrap();
+    assert_eq!(Header::parse(&contents).unwrap(), Header::current());
+
+    let newer = Header { version: header::VERSION + 1, ..Header::current() };
+    json["header"] = serde_json::to_value(newer).unwrap();
+    fs::write(vault.path(), json.to_string()).unwrap();
+    let loaded = Vault::load(&folder, "header".to_owned());
+    assert!(matches!(loaded, Err(Error::UnsupportedFormat(..))));
+}
+
+#[test]
+fn argon2_variant() {
+    let folder = test_folder("variant");
+    let vault = Vault::new(&folder, "variant".to_owned(), KEY, Argon2Variant::Argon2id).unwrap();
+    let contents = fs::read_to_string(vault.path()).unwrap();
+    assert_eq!(Header::parse(&contents).unwrap().kdf, "argon2id");
+
+    let loaded = Vault::load(&folder, "variant".to_owned()).unwrap();
+    assert_eq!(loaded.argon2_variant(), Argon2Variant::Argon2id);
+
+    // vaults stored before the variant could be chosen use argon2d
+    let mut json: serde_json::Value = serde_json::from_str(&contents).unwrap();
+    json.as_object_mut().unwrap().remove("header");
+    json.as_object_mut().unwrap().remove("argon2_variant");
+    fs::write(vault.path(), json.to_string()).unwrap();
+    let loaded = Vault::load(&folder, "variant".to_owned()).unwrap();
+    assert_eq!(loaded.argon2_variant(), Argon2Variant::Argon2d);
+}
+
+#[test]
+fn external_changes() {
+    let folder = test_folder("external");
+    let mut vault = Vault::new(&folder, "external".to_owned(), KEY, Argon2Variant::Argon2id).unwrap();
+    vault.push(Seed::basic("ours".to_owned(), None));
+    assert!(!vault.changed_on_disk().unwrap());
+
+    // another instance adds a seed, as if the file were edited by hand
+    let mut other = Vault::load(&folder, "external".to_owned()).unwrap();
+    other.push(Seed::basic("theirs".to_owned(), None));
+    other.save().unwrap();
+
+    assert!(vault.changed_on_disk().unwrap());
+    let diff = vault.diff_on_disk().unwrap();
+    assert_eq!(diff.only_on_disk, ["theirs"]);
+    assert_eq!(diff.only_in_vault, ["ours"]);
+    assert!(diff.changed.is_empty());
+
+    assert_eq!(vault.merge_from_disk().unwrap(), 1);
+    assert!(!vault.changed_on_disk().unwrap());
+    vault.save().unwrap();
+
+    let loaded = Vault::load(&folder, "external".to_owned()).unwrap();
+    assert_eq!(loaded.seeds().len(), 2);
+
+    other.reload().unwrap();
+    assert_eq!(other.seeds().len(), 2);
+}